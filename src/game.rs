@@ -20,17 +20,38 @@ pub mod inventory;
 pub mod item;
 pub mod level;
 pub mod map;
-mod picking;
+pub mod picking;
 pub mod rendering;
+pub mod replay;
 pub mod resources;
+pub mod rng;
+pub mod save;
 pub mod selection;
 pub mod systems;
 pub mod tile_set;
+pub mod tower;
+pub mod trap;
+pub mod undo;
+pub mod victory;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         audio::plugin,
         assets::plugin,
+        rules_plugin,
+        picking::PointerPickingPlugin,
+        rendering::MapRenderingPlugin,
+        rendering::damage_effects::DamageEffectsPlugin,
+        save::AutosavePlugin,
+        replay::ReplayFilePlugin,
+    ));
+}
+
+/// Game mechanics that do not need a window, renderer or audio.
+///
+/// Shared by the game and the headless [`SimulationPlugin`](crate::simulation::SimulationPlugin).
+pub fn rules_plugin(app: &mut App) {
+    app.add_plugins((
         cycle::CyclePlugin,
         tile_set::TileSetPlugin,
         level::LevelPlugin,
@@ -39,7 +60,13 @@ pub(super) fn plugin(app: &mut App) {
         picking::PickingPlugin,
         actors::ActorPlugin,
         item::ItemPlugin,
-        rendering::MapRenderingPlugin,
+        rng::GameRngPlugin,
+        replay::ReplayPlugin,
+        save::SavePlugin,
+        tower::TowerPlugin,
+        trap::TrapPlugin,
+        undo::UndoPlugin,
+        victory::VictoryPlugin,
     ));
 }
 
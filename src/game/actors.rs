@@ -5,22 +5,31 @@
 use crate::path_finding::tiles::TileDir;
 use crate::screen::Screen;
 use bevy::prelude::*;
+use damage::{ActorDied, DamagePlugin, DamageSystems};
 use enemy::EnemyActorsPlugin;
+use push::PushPlugin;
 use rand::prelude::SliceRandom;
-use rand::thread_rng;
+use rand::Rng;
 use spawn::SpawnActorsPlugin;
-use stats::{ActorName, Health, Movement};
+use stats::{ActorName, Armour, Health, Movement};
+use status::{StatusEffects, StatusEffectsPlugin};
 
 use self::spawn::DespawnAnimation;
 
-use super::components::{ActorTileLayer, PopulationCapacity};
+use super::components::{Abilities, Ability, ActorTileLayer, PopulationCapacity};
 use super::constants::HOUSE_POPULATION_CAPACITY;
 use super::map::VillageMap;
 
+pub mod ai;
+pub mod damage;
 pub mod enemy;
+pub mod enemy_asset;
+pub mod intent;
 pub mod player;
+pub mod push;
 pub mod spawn;
 pub mod stats;
+pub mod status;
 
 /// Character names generated from some random name generator
 pub const NAMES: &[&str] = &[
@@ -81,14 +90,18 @@ pub struct AvailableActorNames(pub Vec<&'static str>);
 
 impl Default for AvailableActorNames {
     fn default() -> Self {
-        let mut names_vec: Vec<&'static str> = NAMES.to_vec();
-        let mut rng = thread_rng();
-        names_vec.shuffle(&mut rng);
-        AvailableActorNames(names_vec)
+        AvailableActorNames(NAMES.to_vec())
     }
 }
 
 impl AvailableActorNames {
+    /// Every name in a random order.
+    pub fn shuffled(rng: &mut impl Rng) -> Self {
+        let mut names_vec: Vec<&'static str> = NAMES.to_vec();
+        names_vec.shuffle(rng);
+        AvailableActorNames(names_vec)
+    }
+
     pub fn next_name(&mut self) -> String {
         self.0
             .pop()
@@ -100,36 +113,46 @@ pub struct ActorPlugin;
 
 impl Plugin for ActorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((EnemyActorsPlugin, SpawnActorsPlugin))
-            .add_systems(Update, health_ui.run_if(in_state(Screen::Playing)));
+        app.add_plugins((
+            DamagePlugin,
+            EnemyActorsPlugin,
+            PushPlugin,
+            SpawnActorsPlugin,
+            StatusEffectsPlugin,
+        ))
+        .add_systems(
+            Update,
+            remove_dead_actors
+                .after(DamageSystems::Apply)
+                .run_if(in_state(Screen::Playing)),
+        );
     }
 }
 
-fn health_ui(
+fn remove_dead_actors(
     mut commands: Commands,
-    mut q_hit_points: Query<(Entity, &Health, &Transform), Changed<Health>>,
+    mut events: EventReader<ActorDied>,
+    q_hit_points: Query<(&Health, &Transform)>,
     q_is_player: Query<(), With<PlayerActor>>,
     mut village_map: ResMut<VillageMap>,
-    // icon_set: Res<IconSet>,
 ) {
-    for (entity, health, transform) in q_hit_points.iter_mut() {
-        if health.value == 0 {
-            // Object dies
-            let mut despawn_animation =
-                DespawnAnimation::new(transform.translation).with_recursive(true);
-
-            if q_is_player.contains(entity) {
-                despawn_animation = despawn_animation.with_hide_only(true);
-                // Player actor will only have 1 health for the next round
-                commands.entity(entity).insert(Health {
-                    value: 1,
-                    ..*health
-                });
-            }
-            commands.entity(entity).insert(despawn_animation);
-            village_map.actors.remove_entity(entity);
-            return;
+    for ActorDied { entity, .. } in events.read() {
+        let Ok((health, transform)) = q_hit_points.get(*entity) else {
+            continue;
+        };
+        let mut despawn_animation =
+            DespawnAnimation::new(transform.translation).with_recursive(true);
+
+        if q_is_player.contains(*entity) {
+            despawn_animation = despawn_animation.with_hide_only(true);
+            // Player actor will only have 1 health for the next round
+            commands.entity(*entity).insert(Health {
+                value: 1,
+                ..*health
+            });
         }
+        commands.entity(*entity).insert(despawn_animation);
+        village_map.actors.remove_entity(*entity);
     }
 }
 
@@ -149,30 +172,78 @@ pub struct IsAirborne;
 #[derive(Component, Default, Clone, Debug)]
 pub struct Directions(pub Vec<TileDir>);
 
-/// Has actor moved or performed an action yet.
+/// Moves and actions the actor made this turn.
 /// Needs to be reset to default after each turn (Not good?).
-#[derive(Component, Default, Debug)]
+///
+/// An actor normally moves once and then acts once, [`Abilities`] bend these rules.
+#[derive(Component, Default, Debug, Clone, PartialEq, Eq)]
 pub struct ActorTurnState {
-    pub used_move: bool,
-    pub used_action: bool,
+    pub moves: u32,
+    pub actions: u32,
+    /// Held in place by a status effect, can neither move nor act.
+    pub held: bool,
 }
 
 impl ActorTurnState {
     pub fn reset(&mut self) {
         *self = Self::default();
     }
+
+    pub fn has_moved(&self) -> bool {
+        0 < self.moves
+    }
+
+    pub fn has_acted(&self) -> bool {
+        0 < self.actions
+    }
+
+    pub fn can_move(&self, abilities: &Abilities) -> bool {
+        if self.held {
+            return false;
+        }
+        match (self.moves, self.actions) {
+            (0, 0) => true,
+            // Runners can move a second time instead of acting
+            (1, 0) => abilities.has(Ability::Runner),
+            // Tacticians can move after acting
+            (0, _) => abilities.has(Ability::Tactician),
+            _ => false,
+        }
+    }
+
+    pub fn can_act(&self, abilities: &Abilities) -> bool {
+        if self.held {
+            return false;
+        }
+        match (self.moves, self.actions) {
+            (0 | 1, 0) => true,
+            // Operators can act twice if they don't move
+            (0, 1) => abilities.has(Ability::Operator),
+            _ => false,
+        }
+    }
+
+    pub fn moved(&mut self) {
+        self.moves += 1;
+    }
+
+    pub fn acted(&mut self) {
+        self.actions += 1;
+    }
 }
 
 #[derive(Bundle)]
 pub struct ActorBundle<T: Component> {
     pub name: ActorName,
     pub health: Health,
+    pub armour: Armour,
     pub movement: Movement,
     pub turn_state: ActorTurnState,
     pub actor: T,
     pub layer_marker: ActorTileLayer,
     pub directions: Directions,
-    // pub abilities: Abilities,
+    pub abilities: Abilities,
+    pub status_effects: StatusEffects,
 }
 
 impl<T: Component> ActorBundle<T>
@@ -183,11 +254,14 @@ where
         Self {
             name: ActorName(String::from(name)),
             health: Health::new(2),
+            armour: Armour::default(),
             movement: Movement(2),
             turn_state: ActorTurnState::default(),
             actor: T::default(),
             layer_marker: ActorTileLayer,
             directions: Directions(directions),
+            abilities: Abilities::default(),
+            status_effects: StatusEffects::default(),
         }
     }
 }
@@ -198,10 +272,20 @@ impl<T: Component> ActorBundle<T> {
         self
     }
 
+    pub fn with_armour(mut self, value: u32) -> Self {
+        self.armour = Armour(value);
+        self
+    }
+
     pub fn with_movement(mut self, movement: u32) -> Self {
         self.movement = Movement(movement);
         self
     }
+
+    pub fn with_abilities(mut self, abilities: &[Ability]) -> Self {
+        self.abilities = Abilities::new(abilities);
+        self
+    }
 }
 
 /// Marker component for a building
@@ -226,3 +310,60 @@ impl Default for StructureBundle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn_state(moves: u32, actions: u32) -> ActorTurnState {
+        ActorTurnState {
+            moves,
+            actions,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_move_then_act() {
+        let abilities = Abilities::default();
+        assert!(turn_state(0, 0).can_move(&abilities));
+        assert!(turn_state(1, 0).can_act(&abilities));
+        assert!(!turn_state(1, 0).can_move(&abilities));
+        assert!(!turn_state(0, 1).can_move(&abilities));
+        assert!(!turn_state(0, 1).can_act(&abilities));
+    }
+
+    #[test]
+    fn test_held_actor_cannot_move_or_act() {
+        let held = ActorTurnState {
+            held: true,
+            ..default()
+        };
+        let abilities = Abilities::new(&[Ability::Runner, Ability::Operator]);
+        assert!(!held.can_move(&abilities));
+        assert!(!held.can_act(&abilities));
+    }
+
+    #[test]
+    fn test_runner_moves_instead_of_acting() {
+        let abilities = Abilities::new(&[Ability::Runner]);
+        assert!(turn_state(1, 0).can_move(&abilities));
+        assert!(!turn_state(2, 0).can_act(&abilities));
+        assert!(!turn_state(1, 1).can_move(&abilities));
+    }
+
+    #[test]
+    fn test_operator_acts_twice_without_moving() {
+        let abilities = Abilities::new(&[Ability::Operator]);
+        assert!(turn_state(0, 1).can_act(&abilities));
+        assert!(!turn_state(0, 2).can_act(&abilities));
+        assert!(!turn_state(1, 1).can_act(&abilities));
+    }
+
+    #[test]
+    fn test_tactician_moves_after_acting() {
+        let abilities = Abilities::new(&[Ability::Tactician]);
+        assert!(turn_state(0, 1).can_move(&abilities));
+        assert!(!turn_state(1, 1).can_move(&abilities));
+    }
+}
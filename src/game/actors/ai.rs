@@ -0,0 +1,363 @@
+//! Enemy decision making.
+//!
+//! An enemy scores every tile it can move to, paired with each attack it could make from there,
+//! and takes the best scoring [`AiAction`]. What scores well depends on its [`Behaviour`].
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::components::Abilities;
+use crate::game::map::{Footprint, VillageMap};
+use crate::path_finding::tiles::{Tile, TileDir};
+
+use super::stats::Health;
+
+/// Any attack is worth more than moving closer to a target.
+const ATTACK_SCORE: i32 = 1000;
+/// Bonus for attacking a target the behaviour prefers.
+const PREFERRED_SCORE: i32 = 100;
+
+/// How an enemy picks its targets.
+#[derive(Component, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Behaviour {
+    /// Goes after heroes, structures only get attacked when no hero is in reach.
+    HuntHeroes,
+    /// Goes after structures, heroes only get attacked when no structure is in reach.
+    RazeStructures,
+    /// Goes after whatever has the least health left.
+    HarassWeakest,
+}
+
+impl Behaviour {
+    fn attack_score(self, target: &AiTarget) -> i32 {
+        match (self, target.kind) {
+            (Behaviour::HuntHeroes, TargetKind::Hero)
+            | (Behaviour::RazeStructures, TargetKind::Structure) => PREFERRED_SCORE,
+            (Behaviour::HarassWeakest, _) => PREFERRED_SCORE - target.health as i32,
+            _ => 0,
+        }
+    }
+
+    /// Targets the enemy moves towards when it can't attack yet.
+    fn goals(self, targets: &[AiTarget]) -> Vec<&AiTarget> {
+        let goals: Vec<_> = match self {
+            Behaviour::HuntHeroes => targets
+                .iter()
+                .filter(|target| target.kind == TargetKind::Hero)
+                .collect(),
+            Behaviour::RazeStructures => targets
+                .iter()
+                .filter(|target| target.kind == TargetKind::Structure)
+                .collect(),
+            Behaviour::HarassWeakest => {
+                let weakest = targets.iter().map(|target| target.health).min();
+                targets
+                    .iter()
+                    .filter(|target| Some(target.health) == weakest)
+                    .collect()
+            }
+        };
+        match goals.is_empty() {
+            true => targets.iter().collect(),
+            false => goals,
+        }
+    }
+}
+
+/// Enemies flee once their health drops to a third or less.
+pub fn is_fleeing(health: &Health) -> bool {
+    !health.is_empty() && health.value * 3 <= health.max
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetKind {
+    Hero,
+    Structure,
+}
+
+/// Something on the map enemies can attack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AiTarget {
+    pub footprint: Footprint,
+    pub kind: TargetKind,
+    pub health: u32,
+}
+
+impl AiTarget {
+    /// Distance from `tile` to the nearest tile of the target.
+    pub fn distance_rook(&self, tile: Tile) -> i32 {
+        self.footprint
+            .tiles()
+            .map(|target_tile| target_tile.distance_rook(tile))
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+/// The enemy making a decision.
+#[derive(Debug, Clone, Copy)]
+pub struct AiActor<'a> {
+    pub tile: Tile,
+    /// Tiles the enemy can still move, 0 if it can't move.
+    pub movement: u32,
+    pub directions: &'a [TileDir],
+    pub abilities: &'a Abilities,
+    pub can_act: bool,
+    pub fleeing: bool,
+}
+
+/// Move to `tile`, then attack the actor on `attack`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AiAction {
+    pub tile: Tile,
+    pub attack: Option<Tile>,
+}
+
+/// Score every move and attack the actor can make and return the best one.
+///
+/// Ties go to the shorter move.
+pub fn choose_action(
+    village_map: &VillageMap,
+    actor: &AiActor,
+    behaviour: Behaviour,
+    targets: &[AiTarget],
+) -> AiAction {
+    let mut tiles: Vec<Tile> = village_map
+        .flood(
+            actor.tile,
+            actor.movement,
+            actor.directions,
+            actor.abilities,
+        )
+        .into_iter()
+        .collect();
+    tiles.sort_by_key(|tile| (tile.distance_rook(actor.tile), tile.y(), tile.x()));
+
+    let mut best = AiAction {
+        tile: actor.tile,
+        attack: None,
+    };
+    let mut best_score = i32::MIN;
+    for tile in tiles {
+        for (action, score) in score_actions(tile, actor, behaviour, targets) {
+            if best_score < score {
+                best = action;
+                best_score = score;
+            }
+        }
+    }
+    best
+}
+
+/// Moving to the tile without attacking, followed by every attack that can be made from it.
+fn score_actions(
+    tile: Tile,
+    actor: &AiActor,
+    behaviour: Behaviour,
+    targets: &[AiTarget],
+) -> Vec<(AiAction, i32)> {
+    let distance_to = |goals: &[&AiTarget]| {
+        goals
+            .iter()
+            .map(|goal| goal.distance_rook(tile))
+            .min()
+            .unwrap_or(0)
+    };
+
+    if actor.fleeing {
+        let heroes: Vec<_> = targets
+            .iter()
+            .filter(|target| target.kind == TargetKind::Hero)
+            .collect();
+        return vec![(AiAction { tile, attack: None }, distance_to(&heroes))];
+    }
+
+    let mut actions = vec![(
+        AiAction { tile, attack: None },
+        -distance_to(&behaviour.goals(targets)),
+    )];
+    if actor.can_act {
+        actions.extend(actor.directions.iter().filter_map(|direction| {
+            let attack_tile = tile.step(*direction);
+            let target = targets
+                .iter()
+                .find(|target| target.footprint.contains(attack_tile))?;
+            Some((
+                AiAction {
+                    tile,
+                    attack: Some(attack_tile),
+                },
+                ATTACK_SCORE + behaviour.attack_score(target),
+            ))
+        }));
+    }
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path_finding::tiles::TileDim;
+
+    fn place(village_map: &mut VillageMap, targets: &[AiTarget]) {
+        for (index, target) in targets.iter().enumerate() {
+            village_map
+                .actors
+                .place(target.footprint, Entity::from_raw(index as u32 + 1));
+        }
+    }
+
+    fn target(tile: Tile, kind: TargetKind, health: u32) -> AiTarget {
+        AiTarget {
+            footprint: Footprint::single(tile),
+            kind,
+            health,
+        }
+    }
+
+    fn actor(tile: Tile, abilities: &Abilities) -> AiActor<'_> {
+        AiActor {
+            tile,
+            movement: 2,
+            directions: &TileDir::EDGES,
+            abilities,
+            can_act: true,
+            fleeing: false,
+        }
+    }
+
+    #[test]
+    fn test_hunter_prefers_heroes() {
+        let mut village_map = VillageMap::new(TileDim(7, 7));
+        let targets = [
+            target(Tile(2, 3), TargetKind::Structure, 1),
+            target(Tile(4, 3), TargetKind::Hero, 3),
+        ];
+        place(&mut village_map, &targets);
+        let abilities = Abilities::default();
+        let actor = actor(Tile(3, 5), &abilities);
+
+        let action = choose_action(&village_map, &actor, Behaviour::HuntHeroes, &targets);
+        assert_eq!(action.attack, Some(Tile(4, 3)));
+
+        let action = choose_action(&village_map, &actor, Behaviour::RazeStructures, &targets);
+        assert_eq!(action.attack, Some(Tile(2, 3)));
+    }
+
+    #[test]
+    fn test_harasser_picks_the_weakest() {
+        let mut village_map = VillageMap::new(TileDim(7, 7));
+        let targets = [
+            target(Tile(2, 3), TargetKind::Hero, 3),
+            target(Tile(4, 3), TargetKind::Hero, 1),
+        ];
+        place(&mut village_map, &targets);
+        let abilities = Abilities::default();
+        let actor = actor(Tile(3, 5), &abilities);
+
+        let action = choose_action(&village_map, &actor, Behaviour::HarassWeakest, &targets);
+        assert_eq!(action.attack, Some(Tile(4, 3)));
+    }
+
+    #[test]
+    fn test_attacks_without_moving() {
+        let mut village_map = VillageMap::new(TileDim(5, 5));
+        let targets = [target(Tile(2, 1), TargetKind::Hero, 3)];
+        place(&mut village_map, &targets);
+        let abilities = Abilities::default();
+        let actor = actor(Tile(2, 2), &abilities);
+
+        let action = choose_action(&village_map, &actor, Behaviour::HuntHeroes, &targets);
+        assert_eq!(
+            action,
+            AiAction {
+                tile: Tile(2, 2),
+                attack: Some(Tile(2, 1)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_approaches_out_of_reach_target() {
+        let mut village_map = VillageMap::new(TileDim(9, 3));
+        let targets = [target(Tile(8, 1), TargetKind::Structure, 2)];
+        place(&mut village_map, &targets);
+        let abilities = Abilities::default();
+        let actor = actor(Tile(0, 1), &abilities);
+
+        let action = choose_action(&village_map, &actor, Behaviour::RazeStructures, &targets);
+        assert_eq!(
+            action,
+            AiAction {
+                tile: Tile(2, 1),
+                attack: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_approaches_the_nearest_tile_of_a_footprint() {
+        let mut village_map = VillageMap::new(TileDim(9, 3));
+        let targets = [AiTarget {
+            footprint: Footprint::new(Tile(4, 0), TileDim(1, 3)),
+            kind: TargetKind::Structure,
+            health: 2,
+        }];
+        place(&mut village_map, &targets);
+        let abilities = Abilities::default();
+        let actor = actor(Tile(0, 2), &abilities);
+
+        let action = choose_action(&village_map, &actor, Behaviour::RazeStructures, &targets);
+        assert_eq!(
+            action,
+            AiAction {
+                tile: Tile(2, 2),
+                attack: None,
+            }
+        );
+
+        let actor = AiActor {
+            tile: Tile(2, 1),
+            ..actor
+        };
+        let action = choose_action(&village_map, &actor, Behaviour::RazeStructures, &targets);
+        assert_eq!(
+            action,
+            AiAction {
+                tile: Tile(3, 1),
+                attack: Some(Tile(4, 1)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_fleeing_runs_from_heroes() {
+        let mut village_map = VillageMap::new(TileDim(9, 1));
+        let targets = [target(Tile(3, 0), TargetKind::Hero, 3)];
+        place(&mut village_map, &targets);
+        let abilities = Abilities::default();
+        let actor = AiActor {
+            fleeing: true,
+            ..actor(Tile(4, 0), &abilities)
+        };
+
+        let action = choose_action(&village_map, &actor, Behaviour::HuntHeroes, &targets);
+        assert_eq!(
+            action,
+            AiAction {
+                tile: Tile(6, 0),
+                attack: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_is_fleeing() {
+        let mut health = Health::new(3);
+        assert!(!is_fleeing(&health));
+        health.value = 1;
+        assert!(is_fleeing(&health));
+        health.value = 0;
+        assert!(!is_fleeing(&health));
+    }
+}
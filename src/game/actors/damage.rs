@@ -0,0 +1,249 @@
+//! Every change to an actor's health goes through [`DamageEvent`] and [`HealEvent`].
+//!
+//! Damage is gathered into [`PendingDamage`], adjusted by the modifiers in [`DamageSystems::Modify`]
+//! and then applied to [`Health`]. New modifiers like terrain or buffs belong in that set.
+//! [`ActorDied`] is the only place deaths are reported.
+
+use bevy::prelude::*;
+
+use super::stats::{Armour, Health};
+use super::status::{InflictStatus, StatusEffect, StatusEffects};
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingDamage>()
+            .add_event::<DamageEvent>()
+            .add_event::<HealEvent>()
+            .add_event::<DamageDealt>()
+            .add_event::<Healed>()
+            .add_event::<ActorDied>()
+            .configure_sets(
+                Update,
+                (
+                    DamageSystems::Collect,
+                    DamageSystems::Modify,
+                    DamageSystems::Apply,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    collect_damage.in_set(DamageSystems::Collect),
+                    (block_attacks, absorb_with_armour)
+                        .chain()
+                        .in_set(DamageSystems::Modify),
+                    (apply_damage, apply_healing)
+                        .chain()
+                        .in_set(DamageSystems::Apply),
+                ),
+            );
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DamageSystems {
+    /// Turns [`DamageEvent`]s into [`PendingDamage`], send damage before this set.
+    Collect,
+    /// Adjusts the [`PendingDamage`] before it is applied.
+    Modify,
+    /// Applies damage and healing, reports the results and deaths.
+    Apply,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    /// Claws and weapons, can be blocked and absorbed by armour.
+    Physical,
+    /// Goes straight to health.
+    Poison,
+    /// Pushed into water without being able to swim, goes straight to health.
+    Drowning,
+}
+
+/// Deal damage to the target actor.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DamageEvent {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub amount: u32,
+    pub damage_type: DamageType,
+    /// Inflicted on the target unless the attack is blocked.
+    pub status: Option<StatusEffect>,
+}
+
+impl DamageEvent {
+    pub fn new(
+        source: Option<Entity>,
+        target: Entity,
+        amount: u32,
+        damage_type: DamageType,
+    ) -> Self {
+        Self {
+            source,
+            target,
+            amount,
+            damage_type,
+            status: None,
+        }
+    }
+
+    pub fn with_status(mut self, status: Option<StatusEffect>) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+/// Heal the target actor, health never goes over its max.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct HealEvent {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub amount: u32,
+}
+
+/// Sent after damage was applied, for effects like sounds, screen shake and damage numbers.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DamageDealt {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub damage_type: DamageType,
+    /// Health lost by the target.
+    pub amount: u32,
+    /// Damage taken by armour instead.
+    pub absorbed: u32,
+    pub blocked: bool,
+}
+
+/// Sent after healing was applied.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Healed {
+    pub target: Entity,
+    /// Health gained by the target.
+    pub amount: u32,
+}
+
+/// Sent once when an actor's health drops to 0.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ActorDied {
+    pub entity: Entity,
+    /// Source of the damage that killed the actor, if any.
+    pub killer: Option<Entity>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PendingHit {
+    pub event: DamageEvent,
+    /// Damage left after modifiers.
+    pub amount: u32,
+    pub absorbed: u32,
+    pub blocked: bool,
+}
+
+/// Damage of this update waiting to be applied.
+#[derive(Resource, Default, Debug)]
+pub struct PendingDamage(pub Vec<PendingHit>);
+
+fn collect_damage(mut events: EventReader<DamageEvent>, mut pending: ResMut<PendingDamage>) {
+    pending.0.extend(events.read().map(|event| PendingHit {
+        event: *event,
+        amount: event.amount,
+        absorbed: 0,
+        blocked: false,
+    }));
+}
+
+fn block_attacks(
+    mut pending: ResMut<PendingDamage>,
+    mut q_status_effects: Query<&mut StatusEffects>,
+) {
+    for hit in pending.0.iter_mut() {
+        if hit.event.damage_type != DamageType::Physical {
+            continue;
+        }
+        if let Ok(mut status_effects) = q_status_effects.get_mut(hit.event.target) {
+            if status_effects.take_block() {
+                hit.blocked = true;
+                hit.amount = 0;
+            }
+        }
+    }
+}
+
+fn absorb_with_armour(mut pending: ResMut<PendingDamage>, mut q_armours: Query<&mut Armour>) {
+    for hit in pending.0.iter_mut() {
+        if hit.event.damage_type != DamageType::Physical {
+            continue;
+        }
+        if let Ok(mut armour) = q_armours.get_mut(hit.event.target) {
+            let amount = armour.absorb(hit.amount);
+            hit.absorbed += hit.amount - amount;
+            hit.amount = amount;
+        }
+    }
+}
+
+fn apply_damage(
+    mut pending: ResMut<PendingDamage>,
+    mut q_healths: Query<&mut Health>,
+    mut damage_dealt_evt: EventWriter<DamageDealt>,
+    mut inflict_status_evt: EventWriter<InflictStatus>,
+    mut actor_died_evt: EventWriter<ActorDied>,
+) {
+    for hit in pending.0.drain(..) {
+        let Ok(mut health) = q_healths.get_mut(hit.event.target) else {
+            continue;
+        };
+        // Already dead
+        if health.is_empty() {
+            continue;
+        }
+
+        let amount = match hit.amount {
+            0 => 0,
+            amount => health.hurt(amount),
+        };
+        damage_dealt_evt.send(DamageDealt {
+            source: hit.event.source,
+            target: hit.event.target,
+            damage_type: hit.event.damage_type,
+            amount,
+            absorbed: hit.absorbed,
+            blocked: hit.blocked,
+        });
+        if health.is_empty() {
+            actor_died_evt.send(ActorDied {
+                entity: hit.event.target,
+                killer: hit.event.source,
+            });
+        }
+
+        if let (false, Some(status)) = (hit.blocked, hit.event.status) {
+            inflict_status_evt.send(InflictStatus {
+                target: hit.event.target,
+                effect: status,
+            });
+        }
+    }
+}
+
+fn apply_healing(
+    mut events: EventReader<HealEvent>,
+    mut q_healths: Query<&mut Health>,
+    mut healed_evt: EventWriter<Healed>,
+) {
+    for event in events.read() {
+        let Ok(mut health) = q_healths.get_mut(event.target) else {
+            continue;
+        };
+        if health.is_empty() {
+            continue;
+        }
+        healed_evt.send(Healed {
+            target: event.target,
+            amount: health.heal(event.amount),
+        });
+    }
+}
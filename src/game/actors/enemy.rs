@@ -1,21 +1,31 @@
-use bevy::{color::palettes::css, math::uvec2, prelude::*};
-use bevy_trauma_shake::TraumaCommands;
+use bevy::{color::palettes::css, prelude::*};
+use rand::Rng;
 
 use crate::game::actors::spawn::SpawnAnimation;
 use crate::game::actors::ActorBundle;
 use crate::game::actors_list::PlayerActorList;
-use crate::game::cycle::{Season, TimeOfDay, Turn, TURN_PER_DAY};
-use crate::game::level::Terrain;
+use crate::game::components::{Abilities, Ability};
+use crate::game::cycle::{MoonPhase, Season, TimeOfDay, Turn, TURN_PER_DAY};
+use crate::game::level::level_asset::SpawnZone;
+use crate::game::level::{LevelZones, Terrain};
 use crate::game::map::VillageMap;
+use crate::game::rng::GameRng;
 use crate::game::selection::SelectionMap;
 use crate::game::tile_set::{tile_coord_translation, TileSet, TILE_ANCHOR};
-use crate::path_finding::tiles::{TileDir, Tile};
+use crate::game::trap::TrapTriggered;
+use crate::path_finding::tiles::Tile;
 use crate::screen::playing::GameState;
 use crate::screen::Screen;
 use crate::ui::icon_set::IconSet;
 
+use super::damage::{DamageDealt, DamageEvent, DamageSystems, DamageType, HealEvent};
+use super::enemy_asset::{EnemyAssetPlugin, EnemyDefs, EnemyDefsHandle, EnemySpawn, SpecialAttack};
+use super::intent::{ai_targets, plan_intent, EnemyIntentPlugin, IntentData, TargetData};
+use super::push::{push_actors, push_direction, PushEvent, Pushed, Pushes};
 use super::spawn::DespawnAnimation;
-use super::{Directions, EnemyActor, Health, IsAirborne, Movement};
+use super::stats::AttackDamage;
+use super::status::InflictsStatus;
+use super::{ActorTurnState, Directions, EnemyActor, IsAirborne};
 
 /// Distance from border that the enemy will spawn in.
 pub const ENEMY_SPAWN_RANGE: u32 = 2;
@@ -23,32 +33,59 @@ pub const ENEMY_SPAWN_RANGE: u32 = 2;
 pub const CLAW_ANIM_DURATAION: f32 = 1.0;
 const SPAWN_TRIAL: usize = 10;
 const ENEMY_MOVE_SPEED: f32 = 4.0;
+const BOSS_COLOR: Srgba = css::LIGHT_CORAL;
 
 pub struct EnemyActorsPlugin;
 
 impl Plugin for EnemyActorsPlugin {
     fn build(&self, app: &mut App) {
-        app.init_state::<EnemyActionState>()
+        app.add_plugins((EnemyAssetPlugin, EnemyIntentPlugin))
+            .init_state::<EnemyActionState>()
             .add_systems(OnEnter(TimeOfDay::Night), spawn_enemies)
+            .add_systems(
+                Update,
+                (tint_bosses, drain_health.after(DamageSystems::Apply))
+                    .run_if(in_state(Screen::Playing)),
+            )
             .add_systems(OnEnter(GameState::EnemyTurn), find_movement_path)
             .add_systems(
                 Update,
                 (
-                    perform_attack.run_if(in_state(EnemyActionState::Attack)),
+                    perform_attack
+                        .run_if(in_state(EnemyActionState::Attack))
+                        .before(DamageSystems::Collect)
+                        .before(push_actors),
                     move_enemies
                         .run_if(in_state(EnemyActionState::Move))
-                        .after(find_movement_path),
+                        .after(find_movement_path)
+                        .in_set(EnemyMovement),
                 )
                     .run_if(in_state(Screen::Playing).and_then(in_state(GameState::EnemyTurn))),
             );
     }
 }
 
+/// Steps enemies along their [`TilePath`], react to their steps after this set.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EnemyMovement;
+
 fn perform_attack(
     mut commands: Commands,
-    mut q_enemy_attacks: Query<(Entity, &mut EnemyAttack), With<EnemyActor>>,
+    mut q_enemy_attacks: Query<
+        (
+            Entity,
+            &mut EnemyAttack,
+            &AttackDamage,
+            &Directions,
+            Option<&InflictsStatus>,
+            Option<&SpecialAttack>,
+            Option<&Pushes>,
+        ),
+        With<EnemyActor>,
+    >,
     q_not_enemy_units: Query<(), Without<EnemyActor>>,
-    mut q_health: Query<&mut Health>,
+    mut damage_evt: EventWriter<DamageEvent>,
+    mut push_evt: EventWriter<PushEvent>,
     mut q_vis: Query<&mut Visibility>,
     village_map: Res<VillageMap>,
     selection_map: Res<SelectionMap>,
@@ -56,7 +93,9 @@ fn perform_attack(
     icon_set: Res<IconSet>,
     time: Res<Time>,
 ) {
-    let Some((entity, mut enemy_attack)) = q_enemy_attacks.iter_mut().next() else {
+    let Some((entity, mut enemy_attack, damage, directions, inflicts, special_attack, pushes)) =
+        q_enemy_attacks.iter_mut().next()
+    else {
         next_enemy_action_state.set(EnemyActionState::Move);
         return;
     };
@@ -76,20 +115,51 @@ fn perform_attack(
             despawn_anim: DespawnAnimation::new(translation)
                 .with_extra_progress(CLAW_ANIM_DURATAION),
         });
-        commands.add_trauma(0.5);
     }
 
     enemy_attack.factor += time.delta_seconds();
     if enemy_attack.factor >= CLAW_ANIM_DURATAION {
         // Deal damage
-        if let Some(mut health) = village_map
+        if let Some(target) = village_map
             .actors
             .get(enemy_attack.tile)
             // Can only deal damage to non enemy units
             .filter(|e| q_not_enemy_units.contains(*e))
-            .and_then(|e| q_health.get_mut(e).ok())
         {
-            health.value = health.value.saturating_sub(1);
+            let hit = |target| {
+                DamageEvent::new(Some(entity), target, damage.0, DamageType::Physical)
+                    .with_status(inflicts.map(|InflictsStatus(effect)| *effect))
+            };
+            damage_evt.send(hit(target));
+
+            let enemy_tile = village_map
+                .actors
+                .locate(entity)
+                .map(|footprint| footprint.origin);
+            let direction = enemy_tile.and_then(|tile| push_direction(tile, enemy_attack.tile));
+            if let (Some(Pushes(distance)), Some(direction)) = (pushes, direction) {
+                push_evt.send(PushEvent {
+                    source: Some(entity),
+                    target,
+                    direction,
+                    distance: *distance,
+                });
+            }
+
+            match special_attack {
+                Some(SpecialAttack::Cleave) => {
+                    let cleaved = directions
+                        .0
+                        .iter()
+                        .filter_map(|direction| {
+                            village_map.actors.get(enemy_tile?.step(*direction))
+                        })
+                        .filter(|e| *e != target && q_not_enemy_units.contains(*e));
+                    damage_evt.send_batch(cleaved.map(hit));
+                }
+                // Heals once the damage dealt is known, see `drain_health`.
+                Some(SpecialAttack::Drain) | None => {}
+            }
         }
         // Hide marked tile
         if let Some(mut vis) = selection_map
@@ -103,10 +173,36 @@ fn perform_attack(
     }
 }
 
+/// Enemies with [`SpecialAttack::Drain`] heal by the health their attacks took.
+fn drain_health(
+    mut damage_dealt: EventReader<DamageDealt>,
+    q_special_attacks: Query<&SpecialAttack>,
+    mut heal_evt: EventWriter<HealEvent>,
+) {
+    for hit in damage_dealt.read() {
+        let Some(source) = hit.source else {
+            continue;
+        };
+        if hit.amount > 0 && q_special_attacks.get(source) == Ok(&SpecialAttack::Drain) {
+            heal_evt.send(HealEvent {
+                source: Some(source),
+                target: source,
+                amount: hit.amount,
+            });
+        }
+    }
+}
+
 fn move_enemies(
     mut commands: Commands,
     mut q_enemy_units: Query<
-        (Entity, &mut Transform, &Directions, Option<&mut TilePath>),
+        (
+            Entity,
+            &mut Transform,
+            &Abilities,
+            &mut ActorTurnState,
+            Option<&mut TilePath>,
+        ),
         With<EnemyActor>,
     >,
     q_not_enemy_units: Query<(), Without<EnemyActor>>,
@@ -119,6 +215,7 @@ fn move_enemies(
     player_unit_list: Res<PlayerActorList>,
     turn: Res<Turn>,
     time: Res<Time>,
+    mut trap_evt: EventWriter<TrapTriggered>,
 ) {
     if turn.0 != 0 && turn.0 % TURN_PER_DAY == 0 {
         // Next day starts, clear all enemy units
@@ -152,7 +249,7 @@ fn move_enemies(
         return;
     }
 
-    let Some((entity, mut transform, directions, path)) =
+    let Some((entity, mut transform, abilities, mut turn_state, path)) =
         q_enemy_units.iter_mut().find(|(.., path)| path.is_some())
     else {
         next_enemy_action_state.set(EnemyActionState::Attack);
@@ -166,34 +263,28 @@ fn move_enemies(
     if path.index >= path.path.len() - 1 {
         commands.entity(entity).remove::<TilePath>();
 
-        if let Some(enemy_tile) = path.path.last() {
-            // Already in the best tile, find surroundings to attack!
-            // for direction in enemy.
-            for direction in directions.0.iter() {
-                let attack_tile = enemy_tile.step(*direction);
-                let Some(attack_entity) = village_map.actors.get(attack_tile) else {
-                    continue;
-                };
-
-                if q_not_enemy_units.contains(attack_entity) {
-                    // Mark tile for attack in the next enemy turn.
-                    commands
-                        .entity(entity)
-                        .insert(EnemyAttack::new(attack_tile));
-
-                    // Add a red marker for indication
-                    if let Some((mut sprite, mut vis)) = selection_map
-                        .thick_borders
-                        .get(&attack_tile)
-                        .and_then(|e| q_sprites.get_mut(*e).ok())
-                    {
-                        sprite.color = css::RED.into();
-                        *vis = Visibility::Inherited;
-                    }
-
-                    // Only 1 object can be attacked at the same time
-                    break;
-                }
+        // Attack the target the enemy picked while planning its move.
+        if let Some(attack_tile) = path.attack.filter(|attack_tile| {
+            turn_state.can_act(abilities)
+                && village_map
+                    .actors
+                    .get(*attack_tile)
+                    .is_some_and(|e| q_not_enemy_units.contains(e))
+        }) {
+            // Mark tile for attack in the next enemy turn.
+            commands
+                .entity(entity)
+                .insert(EnemyAttack::new(attack_tile));
+            turn_state.acted();
+
+            // Add a red marker for indication
+            if let Some((mut sprite, mut vis)) = selection_map
+                .thick_borders
+                .get(&attack_tile)
+                .and_then(|e| q_sprites.get_mut(*e).ok())
+            {
+                sprite.color = css::RED.into();
+                *vis = Visibility::Inherited;
             }
         }
         return;
@@ -222,41 +313,36 @@ fn move_enemies(
         // Increment the index to move towards the next path
         path.index += 1;
         path.factor = 0.0;
+
+        if let Some(trap) = village_map.traps.get(&path.path[path.index]) {
+            trap_evt.send(TrapTriggered {
+                trap: *trap,
+                target: entity,
+            });
+        }
     }
 }
 
 fn find_movement_path(
     mut commands: Commands,
-    mut q_enemy_units: Query<
-        (Entity, &Movement, &Directions, Option<&IsAirborne>),
-        With<EnemyActor>,
-    >,
+    mut q_enemy_units: Query<(IntentData, &mut ActorTurnState), With<EnemyActor>>,
+    q_targets: Query<TargetData, Without<EnemyActor>>,
     mut village_map: ResMut<VillageMap>,
 ) {
-    // Regenerate heat map to check for player units as well.
-    village_map.generate_heat_map(|e| q_enemy_units.contains(e));
-
-    for (entity, movement, directions, airborne) in q_enemy_units.iter_mut() {
-        let Some(enemy_tile) = village_map.actors.locate(entity) else {
-            continue;
-        };
+    let targets = ai_targets(&village_map, &q_targets);
 
-        let is_airborne = airborne.is_some();
-        let Some(best_tile) =
-            village_map.get_best_tile(enemy_tile, movement.0, &directions.0, is_airborne)
-        else {
+    let mut enemies: Vec<_> = q_enemy_units.iter_mut().collect();
+    enemies.sort_by_key(|(enemy, _)| enemy.0);
+    for (enemy, mut turn_state) in enemies {
+        let entity = enemy.0;
+        let Some(intent) = plan_intent(&mut village_map, enemy, &mut turn_state, &targets) else {
             continue;
         };
-
-        let Some((path, _)) =
-            village_map.pathfind(&enemy_tile, &best_tile, &directions.0, is_airborne)
-        else {
-            continue;
-        };
-
-        commands.entity(entity).insert(TilePath::new(path));
-        village_map.actors.remove(enemy_tile);
-        village_map.actors.set(best_tile, entity);
+        // A push that is still sliding would take over the move.
+        commands
+            .entity(entity)
+            .remove::<Pushed>()
+            .insert(TilePath::new(intent.path).with_attack(intent.attack));
     }
 }
 
@@ -264,50 +350,35 @@ fn spawn_enemies(
     mut commands: Commands,
     mut village_map: ResMut<VillageMap>,
     season: Res<Season>,
+    moon_phase: Res<MoonPhase>,
     tile_set: Res<TileSet>,
+    level_zones: Res<LevelZones>,
+    enemy_defs: Res<Assets<EnemyDefs>>,
+    enemy_defs_handle: Res<EnemyDefsHandle>,
+    mut game_rng: ResMut<GameRng>,
 ) {
-    debug_assert!(
-        village_map.size.x() == village_map.size.y(),
-        "Map is not square."
-    );
-    let width = village_map.size.x();
-
-    if ENEMY_SPAWN_RANGE * 2 > width as u32 {
-        warn!("Enemy spawn range ({ENEMY_SPAWN_RANGE} * 2) is larger than map size: {width}");
+    if level_zones.spawn.is_empty() {
+        warn!("Level has no enemy spawn zones");
         return;
     }
 
-    let enemies = match *season {
-        Season::Summer => {
-            // 2 werewolfs
-            vec![EnemySpawn::WEREWOLF; 2]
-        }
-        Season::Autumn => {
-            // 2 werewolfs, 1 slime
-            vec![
-                EnemySpawn::WEREWOLF,
-                EnemySpawn::WEREWOLF,
-                EnemySpawn::SLIME,
-            ]
-        }
-        Season::Winter => {
-            // 2 werewolfs, 1 slime, 2 bats
-            vec![
-                EnemySpawn::WEREWOLF,
-                EnemySpawn::WEREWOLF,
-                EnemySpawn::SLIME,
-                EnemySpawn::BAT,
-                EnemySpawn::BAT,
-            ]
-        }
+    let Some(enemy_defs) = enemy_defs.get(&enemy_defs_handle.0) else {
+        warn!("Enemy definitions are not loaded");
+        return;
     };
 
-    for enemy in enemies {
+    let wave = enemy_defs
+        .wave(*season)
+        .into_iter()
+        .map(|enemy| (enemy, false));
+    let bosses = enemy_defs
+        .bosses(*moon_phase)
+        .into_iter()
+        .map(|enemy| (enemy, true));
+    for (enemy, is_boss) in wave.chain(bosses) {
         let mut tile_coord = Tile::ZERO;
         for _ in 0..SPAWN_TRIAL {
-            tile_coord = random_border_tile_coord(width as u32, ENEMY_SPAWN_RANGE)
-                .as_ivec2()
-                .into();
+            tile_coord = random_zone_tile(&level_zones.spawn, &mut *game_rng);
 
             // There is something blocking the spawning location
             if village_map.actors.get(tile_coord).is_some() {
@@ -320,95 +391,86 @@ fn spawn_enemies(
             };
 
             match terrain {
-                // Airborne enemy can be on top of anything water
-                Terrain::Water if enemy.is_airborne == false => continue,
+                // Flying and aquatic enemies can be on top of water
+                Terrain::Water if !enemy.abilities().stays_on_water() => continue,
                 _ => break,
             }
         }
 
-        let translation = tile_coord_translation(tile_coord.x() as f32, tile_coord.y() as f32, 2.0);
-        let mut enemy_entity = commands.spawn((
-            SpriteBundle {
-                sprite: Sprite {
-                    anchor: TILE_ANCHOR,
-                    ..default()
-                },
-                texture: tile_set.get(enemy.name),
-                ..default()
-            },
-            ActorBundle::<EnemyActor>::new(enemy.name, enemy.directions.to_vec())
-                .with_health(enemy.hit_points)
-                .with_health(enemy.hit_points)
-                .with_movement(enemy.movement),
-            SpawnAnimation::new(translation),
-            StateScoped(Screen::Playing),
-        ));
-        if enemy.is_airborne {
-            enemy_entity.insert(IsAirborne);
+        let id = spawn_enemy(&mut commands, &tile_set, enemy, tile_coord);
+        if is_boss {
+            commands.entity(id).insert(Boss);
+            if *moon_phase == MoonPhase::Eclipse {
+                commands.entity(id).insert(FinalBoss);
+            }
         }
-        village_map.actors.set(tile_coord, enemy_entity.id());
+        village_map.actors.set(tile_coord, id);
     }
 }
 
-/// Get a random coordinate that is at the border of the grid.
-pub fn random_border_tile_coord(width: u32, range: u32) -> UVec2 {
-    let max_index = width - 1;
-    let side = rand::random::<u32>() % 4;
-    // |---------------| -> width
-    //             |---| -> range
-    // == == == == .. ..
-    // == == == == .. ..
-    let side_coord = uvec2(
-        rand::random::<u32>() % (width - range),
-        rand::random::<u32>() % range,
-    );
-
-    // Convert side coordinate into tile coordinate by performing 2d rotations based on side.
-    match side {
-        0 => side_coord,
-        1 => uvec2(max_index - side_coord.y, side_coord.x),
-        2 => uvec2(max_index - side_coord.x, max_index - side_coord.y),
-        3 => uvec2(side_coord.y, max_index - side_coord.x),
-        _ => unreachable!("Side should be within [0, 4)"),
+/// Tint bosses so they stand out from the other enemies.
+fn tint_bosses(mut q_bosses: Query<&mut Sprite, Added<Boss>>) {
+    for mut sprite in q_bosses.iter_mut() {
+        sprite.color = BOSS_COLOR.into();
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct EnemySpawn {
-    pub name: &'static str,
-    pub hit_points: u32,
-    pub movement: u32,
-    pub is_airborne: bool,
-    pub directions: &'static [TileDir],
+/// Spawn an enemy, the caller is responsible for placing it on the map.
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    tile_set: &TileSet,
+    enemy: &EnemySpawn,
+    tile: Tile,
+) -> Entity {
+    let translation = tile_coord_translation(tile.x() as f32, tile.y() as f32, 2.0);
+    let mut enemy_entity = commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                anchor: TILE_ANCHOR,
+                ..default()
+            },
+            texture: tile_set.get(&enemy.texture),
+            ..default()
+        },
+        ActorBundle::<EnemyActor>::new(&enemy.name, enemy.directions.directions().to_vec())
+            .with_health(enemy.hit_points)
+            .with_movement(enemy.movement)
+            .with_abilities(&enemy.abilities),
+        AttackDamage(enemy.damage),
+        SpawnAnimation::new(translation),
+        enemy.behaviour,
+        StateScoped(Screen::Playing),
+    ));
+    if let Some(effect) = enemy.inflicts {
+        enemy_entity.insert(InflictsStatus(effect));
+    }
+    if let Some(special_attack) = enemy.special_attack {
+        enemy_entity.insert(special_attack);
+    }
+    if 0 < enemy.push {
+        enemy_entity.insert(Pushes(enemy.push));
+    }
+    if enemy.abilities().has(Ability::Flying) {
+        enemy_entity.insert(IsAirborne);
+    }
+    enemy_entity.id()
 }
 
-impl EnemySpawn {
-    pub const WEREWOLF: Self = Self {
-        name: "werewolf",
-        hit_points: 3,
-        movement: 3,
-        is_airborne: false,
-        directions: &TileDir::EDGES,
-    };
-    pub const SLIME: Self = Self {
-        name: "slime",
-        hit_points: 4,
-        movement: 2,
-        is_airborne: false,
-        directions: &TileDir::EDGES,
-    };
-    pub const BAT: Self = Self {
-        name: "bat",
-        hit_points: 2,
-        movement: 4,
-        is_airborne: true,
-        directions: &TileDir::ALL,
-    };
+/// Get a random tile inside a random spawn zone.
+pub fn random_zone_tile(zones: &[SpawnZone], rng: &mut impl Rng) -> Tile {
+    let zone = zones[rng.gen_range(0..zones.len())].area.tile_rect();
+    let (min, size) = (zone.min(), zone.size());
+    Tile(
+        min.x() + rng.gen_range(0..size.x()),
+        min.y() + rng.gen_range(0..size.y()),
+    )
 }
 
 #[derive(Component, Default, Debug, Clone)]
 pub struct TilePath {
     pub path: Vec<Tile>,
+    /// Tile to attack at the end of the path.
+    pub attack: Option<Tile>,
     /// Current path index that the entity is located at.
     pub index: usize,
     /// Animation factor between 2 tiles.
@@ -419,6 +481,11 @@ impl TilePath {
     pub fn new(path: Vec<Tile>) -> Self {
         Self { path, ..default() }
     }
+
+    pub fn with_attack(mut self, attack: Option<Tile>) -> Self {
+        self.attack = attack;
+        self
+    }
 }
 
 #[derive(Component, Default, Debug, Clone)]
@@ -434,6 +501,14 @@ impl EnemyAttack {
     }
 }
 
+/// Marker component for bosses, they only join the wave on a full moon or during the eclipse.
+#[derive(Component, Default, Copy, Clone, Debug)]
+pub struct Boss;
+
+/// Marker component for the eclipse boss, the eclipse lasts until it dies.
+#[derive(Component, Default, Copy, Clone, Debug)]
+pub struct FinalBoss;
+
 #[derive(Bundle)]
 pub struct ClawMarkBundle {
     pub sprite: SpriteBundle,
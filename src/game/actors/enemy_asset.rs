@@ -0,0 +1,261 @@
+//! Enemy stats and the waves they attack in, loaded from [`ENEMIES_PATH`].
+//!
+//! The file is hot reloaded in native dev builds, enemies spawned after a change use the new values.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::game::components::{Abilities, Ability};
+use crate::game::cycle::{MoonPhase, Season};
+use crate::game::tile_set::TILE_NAMES;
+use crate::path_finding::tiles::TileDir;
+
+use super::ai::Behaviour;
+use super::status::StatusEffect;
+
+/// Path of the [`EnemyDefs`] asset.
+pub const ENEMIES_PATH: &str = "enemies/enemies.json";
+
+pub struct EnemyAssetPlugin;
+
+impl Plugin for EnemyAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<EnemyDefs>()
+            .init_asset_loader::<EnemyDefsLoader>()
+            .add_systems(PreStartup, load_enemy_defs)
+            .add_systems(PreUpdate, log_enemy_defs_reload);
+    }
+}
+
+#[derive(Resource)]
+pub struct EnemyDefsHandle(pub Handle<EnemyDefs>);
+
+fn load_enemy_defs(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(EnemyDefsHandle(asset_server.load(ENEMIES_PATH)));
+}
+
+fn log_enemy_defs_reload(mut events: EventReader<AssetEvent<EnemyDefs>>) {
+    for event in events.read() {
+        if let AssetEvent::Modified { .. } = event {
+            info!("Reloaded enemy definitions");
+        }
+    }
+}
+
+/// Every kind of enemy and the waves they spawn in.
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Clone)]
+pub struct EnemyDefs {
+    pub enemies: Vec<EnemySpawn>,
+    /// Names of the enemies that spawn each night of a season.
+    pub waves: HashMap<Season, Vec<String>>,
+    /// Names of the bosses joining the wave on a full moon or during the eclipse.
+    #[serde(default)]
+    pub bosses: HashMap<MoonPhase, Vec<String>>,
+}
+
+impl EnemyDefs {
+    pub fn get(&self, name: &str) -> Option<&EnemySpawn> {
+        self.enemies.iter().find(|enemy| enemy.name == name)
+    }
+
+    /// Enemies spawning during a night of the season.
+    pub fn wave(&self, season: Season) -> Vec<&EnemySpawn> {
+        self.waves
+            .get(&season)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| self.get(name))
+            .collect()
+    }
+
+    /// Bosses spawning during a night with this moon.
+    pub fn bosses(&self, moon_phase: MoonPhase) -> Vec<&EnemySpawn> {
+        self.bosses
+            .get(&moon_phase)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| self.get(name))
+            .collect()
+    }
+
+    /// Every wave may only name enemies that are defined, and every enemy needs a known texture.
+    pub fn validate(&self) -> Result<(), EnemyDefsLoaderError> {
+        if let Some(enemy) = self
+            .enemies
+            .iter()
+            .find(|enemy| !TILE_NAMES.contains(&enemy.texture.as_str()))
+        {
+            return Err(EnemyDefsLoaderError::UnknownTexture {
+                name: enemy.name.clone(),
+                texture: enemy.texture.clone(),
+            });
+        }
+        let waves = self
+            .waves
+            .iter()
+            .map(|(season, names)| (season.to_string(), names));
+        let bosses = self
+            .bosses
+            .iter()
+            .map(|(moon_phase, names)| (format!("{moon_phase:?}"), names));
+        for (wave, names) in waves.chain(bosses) {
+            if let Some(name) = names.iter().find(|name| self.get(name).is_none()) {
+                return Err(EnemyDefsLoaderError::UnknownEnemy {
+                    wave,
+                    name: name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stats of a kind of enemy.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EnemySpawn {
+    pub name: String,
+    /// Tile set texture of the enemy.
+    pub texture: String,
+    pub hit_points: u32,
+    pub movement: u32,
+    /// Damage dealt by each attack.
+    #[serde(default = "default_damage")]
+    pub damage: u32,
+    #[serde(default)]
+    pub abilities: Vec<Ability>,
+    pub directions: DirectionSet,
+    pub behaviour: Behaviour,
+    /// Status effect inflicted by the enemy's attacks.
+    #[serde(default)]
+    pub inflicts: Option<StatusEffect>,
+    #[serde(default)]
+    pub special_attack: Option<SpecialAttack>,
+    /// Tiles the enemy's attacks knock their target back.
+    #[serde(default)]
+    pub push: u32,
+}
+
+fn default_damage() -> u32 {
+    1
+}
+
+impl EnemySpawn {
+    pub fn abilities(&self) -> Abilities {
+        Abilities::new(&self.abilities)
+    }
+}
+
+/// Extra effect of an enemy's attacks.
+#[derive(Component, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialAttack {
+    /// Also hits every other hero or structure next to the enemy.
+    Cleave,
+    /// The enemy heals by the damage of each attack.
+    Drain,
+}
+
+/// Directions an enemy can move and attack in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectionSet {
+    Edges,
+    Corners,
+    All,
+}
+
+impl DirectionSet {
+    pub fn directions(self) -> &'static [TileDir] {
+        match self {
+            DirectionSet::Edges => &TileDir::EDGES,
+            DirectionSet::Corners => &TileDir::CORNERS,
+            DirectionSet::All => &TileDir::ALL,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct EnemyDefsLoader;
+
+impl AssetLoader for EnemyDefsLoader {
+    type Asset = EnemyDefs;
+    type Settings = ();
+    type Error = EnemyDefsLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let enemy_defs = serde_json::from_slice::<EnemyDefs>(&bytes)?;
+        enemy_defs.validate()?;
+
+        Ok(enemy_defs)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json"]
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum EnemyDefsLoaderError {
+    #[error("Could not load json file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not deserialize using serde: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("Unknown enemy {name:?} in the {wave} wave")]
+    UnknownEnemy { wave: String, name: String },
+    #[error("Unknown texture {texture:?} of enemy {name:?}")]
+    UnknownTexture { name: String, texture: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enemy_defs() -> EnemyDefs {
+        serde_json::from_str(include_str!("../../../assets/enemies/enemies.json")).unwrap()
+    }
+
+    #[test]
+    fn test_enemy_defs_are_valid() {
+        let enemy_defs = enemy_defs();
+        enemy_defs.validate().unwrap();
+        for season in [Season::Summer, Season::Autumn, Season::Winter] {
+            assert!(!enemy_defs.wave(season).is_empty());
+        }
+        assert!(!enemy_defs.bosses(MoonPhase::Eclipse).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_enemy_in_wave() {
+        let mut enemy_defs = enemy_defs();
+        enemy_defs
+            .waves
+            .insert(Season::Summer, vec!["dragon".to_string()]);
+        assert!(matches!(
+            enemy_defs.validate(),
+            Err(EnemyDefsLoaderError::UnknownEnemy { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_enemy_texture() {
+        let mut enemy_defs = enemy_defs();
+        enemy_defs.enemies[0].texture = "dragon".to_string();
+        assert!(matches!(
+            enemy_defs.validate(),
+            Err(EnemyDefsLoaderError::UnknownTexture { .. })
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+//! Enemy intents, planned ahead so the player can see what every enemy is about to do.
+//!
+//! Intents are planned when the battle turn starts and planned again whenever the map or an
+//! actor's health, status effects or turn state changes, the enemy turn then follows the same plan
+//! unless something else changed in between.
+
+use bevy::ecs::query::QueryItem;
+use bevy::prelude::*;
+
+use crate::game::components::Abilities;
+use crate::game::map::VillageMap;
+use crate::path_finding::tiles::Tile;
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+
+use super::ai::{choose_action, is_fleeing, AiActor, AiTarget, Behaviour, TargetKind};
+use super::stats::{Health, Movement};
+use super::status::StatusEffects;
+use super::{ActorTurnState, Directions, EnemyActor, PlayerActor};
+
+pub struct EnemyIntentPlugin;
+
+impl Plugin for EnemyIntentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::BattleTurn), plan_enemy_intents)
+            .add_systems(
+                Update,
+                plan_enemy_intents
+                    .run_if(intents_outdated)
+                    .run_if(in_state(Screen::Playing).and_then(in_state(GameState::BattleTurn))),
+            )
+            .add_systems(OnExit(GameState::BattleTurn), clear_enemy_intents);
+    }
+}
+
+/// Where an enemy moves and what it attacks at the end of its move.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct EnemyIntent {
+    /// Tiles walked through, starting with the tile the enemy stands on.
+    pub path: Vec<Tile>,
+    /// Tile marked for attack once the enemy reaches the end of its path.
+    pub attack: Option<Tile>,
+}
+
+impl EnemyIntent {
+    pub fn destination(&self) -> Tile {
+        // The path always starts with the tile of the enemy.
+        *self.path.last().unwrap()
+    }
+}
+
+pub type IntentData = (
+    Entity,
+    &'static Movement,
+    &'static Directions,
+    &'static Abilities,
+    &'static Behaviour,
+    &'static Health,
+);
+
+pub type TargetData = (&'static Health, Has<PlayerActor>);
+
+/// Every hero and structure on the map enemies can attack.
+pub fn ai_targets(
+    village_map: &VillageMap,
+    q_targets: &Query<TargetData, Without<EnemyActor>>,
+) -> Vec<AiTarget> {
+    village_map
+        .actors
+        .footprints()
+        .filter_map(|(entity, footprint)| {
+            let (health, is_hero) = q_targets.get(entity).ok()?;
+            Some(AiTarget {
+                footprint,
+                kind: match is_hero {
+                    true => TargetKind::Hero,
+                    false => TargetKind::Structure,
+                },
+                health: health.value,
+            })
+        })
+        .collect()
+}
+
+/// Plan the move and attack of an enemy.
+///
+/// The enemy is moved to its destination on the map so that enemies planned after it path around it.
+pub fn plan_intent(
+    village_map: &mut VillageMap,
+    (entity, movement, directions, abilities, behaviour, health): QueryItem<IntentData>,
+    turn_state: &mut ActorTurnState,
+    targets: &[AiTarget],
+) -> Option<EnemyIntent> {
+    let enemy_tile = village_map.actors.locate(entity)?.origin;
+
+    let mut path = vec![enemy_tile];
+    let mut tile = enemy_tile;
+    // Runners keep moving if there is nothing to attack after their first move.
+    loop {
+        let actor = AiActor {
+            tile,
+            movement: match turn_state.can_move(abilities) {
+                true => movement.0,
+                false => 0,
+            },
+            directions: &directions.0,
+            abilities,
+            can_act: turn_state.can_act(abilities),
+            fleeing: is_fleeing(health),
+        };
+        let action = choose_action(village_map, &actor, *behaviour, targets);
+
+        if action.tile != tile {
+            let Some((steps, _)) =
+                village_map.pathfind(&tile, &action.tile, &directions.0, abilities)
+            else {
+                return Some(EnemyIntent { path, attack: None });
+            };
+            path.extend(steps.into_iter().skip(1));
+            village_map.actors.set(action.tile, entity);
+            turn_state.moved();
+        }
+
+        if action.tile == tile || action.attack.is_some() {
+            return Some(EnemyIntent {
+                path,
+                attack: action.attack,
+            });
+        }
+        tile = action.tile;
+    }
+}
+
+/// Hits, statuses and spent actions change what enemies can and want to do, not only the map.
+fn intents_outdated(
+    village_map: Option<Res<VillageMap>>,
+    q_changed: Query<
+        (),
+        Or<(
+            Changed<Health>,
+            Changed<StatusEffects>,
+            Changed<ActorTurnState>,
+        )>,
+    >,
+) -> bool {
+    village_map.is_some_and(|village_map| village_map.is_changed()) || !q_changed.is_empty()
+}
+
+/// Plan every enemy on a copy of the map, the enemies themselves stay where they are.
+fn plan_enemy_intents(
+    mut commands: Commands,
+    q_enemies: Query<(IntentData, &ActorTurnState), With<EnemyActor>>,
+    q_targets: Query<TargetData, Without<EnemyActor>>,
+    village_map: Res<VillageMap>,
+) {
+    let targets = ai_targets(&village_map, &q_targets);
+    let mut planning_map = village_map.clone();
+    let mut enemies: Vec<_> = q_enemies.iter().collect();
+    // Same order as the enemy turn plans them in.
+    enemies.sort_by_key(|(enemy, _)| enemy.0);
+    for (enemy, turn_state) in enemies {
+        let entity = enemy.0;
+        // Turn states are only reset once the battle turn ends.
+        let mut turn_state = ActorTurnState {
+            held: turn_state.held,
+            ..default()
+        };
+        match plan_intent(&mut planning_map, enemy, &mut turn_state, &targets) {
+            Some(intent) => commands.entity(entity).insert(intent),
+            None => commands.entity(entity).remove::<EnemyIntent>(),
+        };
+    }
+}
+
+fn clear_enemy_intents(mut commands: Commands, q_intents: Query<Entity, With<EnemyIntent>>) {
+    for entity in q_intents.iter() {
+        commands.entity(entity).remove::<EnemyIntent>();
+    }
+}
@@ -4,13 +4,18 @@ use crate::game::cycle::EndTurn;
 
 use crate::game::inventory::MaxInventorySize;
 
-use crate::game::inventory::Inventory;
+use crate::game::inventory::{find_item_template, Inventory};
 
 use crate::game::actors_list::PlayerActorList;
+use crate::game::level::level_asset::{LevelAsset, Levels};
+use crate::game::level::SelectedLevel;
 use crate::game::map::VillageMap;
 pub use crate::game::picking::TilePressedEvent;
+use crate::game::replay::{CommandLog, GameCommand};
+use crate::game::rng::GameRng;
 use crate::game::selection::SelectedActor;
 use crate::game::tile_set::tile_coord_translation;
+use crate::game::undo::HeroActed;
 use crate::path_finding::tiles::TileDir;
 use crate::screen::playing::GameState;
 
@@ -38,11 +43,48 @@ pub fn add_starting_player_units(
     mut available_names: ResMut<AvailableActorNames>,
     mut player_unit_list: ResMut<PlayerActorList>,
     mut commands: Commands,
+    mut game_rng: ResMut<GameRng>,
+    levels: Res<Levels>,
+    selected_level: Res<SelectedLevel>,
+    level_assets: Res<Assets<LevelAsset>>,
 ) {
     player_unit_list.0.clear();
-    for _ in 0..INITIAL_PLAYER_UNITS {
-        let name = available_names.next_name();
+    *available_names = AvailableActorNames::shuffled(&mut *game_rng);
+    let starting_heroes = levels
+        .selected(&selected_level)
+        .and_then(|level| level_assets.get(&level.handle))
+        .and_then(|level_asset| level_asset.starting_heroes.as_ref());
+
+    let Some(starting_heroes) = starting_heroes else {
+        for _ in 0..INITIAL_PLAYER_UNITS {
+            let name = available_names.next_name();
+            let id = spawn_player_unit(&mut commands, name);
+            player_unit_list.0.push(id);
+        }
+        return;
+    };
+
+    for hero in starting_heroes {
+        let name = match &hero.name {
+            Some(name) => {
+                available_names.0.retain(|available| *available != *name);
+                name.clone()
+            }
+            None => available_names.next_name(),
+        };
         let id = spawn_player_unit(&mut commands, name);
+        commands
+            .entity(id)
+            .insert((Health::new(hero.health), Movement(hero.movement)));
+        if let Some(items) = &hero.items {
+            let mut item_slots: Vec<_> =
+                items.iter().map(|name| find_item_template(name)).collect();
+            item_slots.resize(item_slots.len().max(3), None);
+            commands.entity(id).insert((
+                MaxInventorySize(item_slots.len() as u32),
+                Inventory::from_slots(item_slots),
+            ));
+        }
         player_unit_list.0.push(id);
     }
 }
@@ -50,11 +92,15 @@ pub fn add_starting_player_units(
 pub fn move_unit(
     mut event_reader: EventReader<TilePressedEvent>,
     selected_unit: Res<SelectedActor>,
+    player_unit_list: Res<PlayerActorList>,
+    mut command_log: ResMut<CommandLog>,
+    mut hero_acted_evt: EventWriter<HeroActed>,
     mut village_map: ResMut<VillageMap>,
     mut turn_state_query: Query<
         (
             &mut ActorTurnState,
             &Movement,
+            &Abilities,
             &mut Visibility,
             &mut Sprite,
             &mut Transform,
@@ -67,30 +113,38 @@ pub fn move_unit(
             return;
         };
 
-        let Ok((mut turn_state, movement, mut vis, mut sprite, mut transform)) =
+        let Ok((mut turn_state, movement, abilities, mut vis, mut sprite, mut transform)) =
             turn_state_query.get_mut(selected)
         else {
             return;
         };
 
-        if turn_state.used_move || movement.0 == 0 {
+        if !turn_state.can_move(abilities) || movement.0 == 0 {
             return;
         }
 
         let Some(current_pos) = village_map
             .actors
             .locate(selected)
+            .map(|footprint| footprint.origin)
             .filter(|pos| *pos != *target)
         else {
             return;
         };
 
         if village_map
-            .flood(current_pos, movement.0, &TileDir::EDGES, false)
+            .flood(current_pos, movement.0, &TileDir::EDGES, abilities)
             .contains(target)
         {
             village_map.actors.set(*target, selected);
-            turn_state.used_move = true;
+            turn_state.moved();
+            hero_acted_evt.send(HeroActed);
+            if let Some(hero) = player_unit_list.index_of(selected) {
+                command_log.record(GameCommand::Move {
+                    hero,
+                    tile: *target,
+                });
+            }
             transform.translation =
                 tile_coord_translation(target.x() as f32, target.y() as f32, 2.);
             transform.scale = Vec3::ONE;
@@ -0,0 +1,314 @@
+//! Forced movement, actors knocked back along a [`TileDir`] by attacks and items.
+//!
+//! A pushed actor slides tile by tile until it moved the full distance. Running into another actor
+//! or a structure stops it and hurts both, the map border just stops it.
+//! Actors that can't swim drown when pushed into water. Structures never budge.
+
+use bevy::prelude::*;
+
+use crate::game::components::Abilities;
+use crate::game::level::Terrain;
+use crate::game::map::VillageMap;
+use crate::game::tile_set::tile_coord_translation;
+use crate::path_finding::tiles::{Tile, TileDir};
+use crate::screen::Screen;
+
+use super::damage::{DamageEvent, DamageSystems, DamageType};
+use super::enemy::TilePath;
+use super::stats::Health;
+use super::Structure;
+
+/// Damage dealt to the pushed actor and to what it runs into.
+pub const COLLISION_DAMAGE: u32 = 1;
+/// Tiles per second a pushed actor slides.
+const PUSH_SPEED: f32 = 8.0;
+
+pub struct PushPlugin;
+
+impl Plugin for PushPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PushEvent>().add_systems(
+            Update,
+            (push_actors.before(DamageSystems::Collect), animate_pushes)
+                .run_if(in_state(Screen::Playing)),
+        );
+    }
+}
+
+/// Push the target actor away, send before [`DamageSystems::Collect`].
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PushEvent {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub direction: TileDir,
+    /// Number of tiles the target slides.
+    pub distance: u32,
+}
+
+/// Tiles an enemy's attacks knock their target back.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Pushes(pub u32);
+
+/// Component for actors sliding along their [`TilePath`] after being pushed.
+#[derive(Component, Default, Copy, Clone, Debug)]
+pub struct Pushed;
+
+/// Where a push ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushOutcome {
+    /// Tiles the actor slides through, starting with the tile it was pushed from.
+    pub path: Vec<Tile>,
+    /// Actor the pushed actor ran into.
+    pub collision: Option<Entity>,
+    /// Pushed into water it can't swim in.
+    pub drowned: bool,
+}
+
+impl PushOutcome {
+    pub fn destination(&self) -> Tile {
+        // The path always starts with the tile the actor was pushed from.
+        *self.path.last().unwrap()
+    }
+}
+
+/// Slide an actor standing on `start` along `direction`, the map is left untouched.
+///
+/// Swimmers can't stay on water, pushed into it they drift on to the next land tile
+/// and drown if there is none.
+pub fn resolve_push(
+    village_map: &VillageMap,
+    start: Tile,
+    direction: TileDir,
+    distance: u32,
+    abilities: &Abilities,
+) -> PushOutcome {
+    let mut outcome = PushOutcome {
+        path: vec![start],
+        collision: None,
+        drowned: false,
+    };
+    let mut tile = start;
+    for _ in 0..distance {
+        let next = tile.step(direction);
+        if village_map.is_out_of_bounds(next) {
+            break;
+        }
+        if let Some(blocker) = village_map.actors.get(next) {
+            outcome.collision = Some(blocker);
+            break;
+        }
+        tile = next;
+        outcome.path.push(tile);
+        if village_map.get_terrain(tile) == Some(Terrain::Water) && !abilities.passes_water() {
+            outcome.drowned = true;
+            break;
+        }
+    }
+    while village_map.get_terrain(tile) == Some(Terrain::Water)
+        && !abilities.stays_on_water()
+        && !outcome.drowned
+    {
+        let next = tile.step(direction);
+        if village_map.is_out_of_bounds(next) || village_map.actors.is_occupied(next) {
+            outcome.drowned = true;
+            break;
+        }
+        tile = next;
+        outcome.path.push(tile);
+    }
+    outcome
+}
+
+/// Direction of a push away from `from`, diagonal unless both tiles share a row or column.
+pub fn push_direction(from: Tile, to: Tile) -> Option<TileDir> {
+    let offset = (to.to_ivec2() - from.to_ivec2()).signum();
+    TileDir::ALL
+        .into_iter()
+        .find(|direction| Tile::ZERO.step(*direction).to_ivec2() == offset)
+}
+
+pub fn push_actors(
+    mut commands: Commands,
+    mut events: EventReader<PushEvent>,
+    q_pushable: Query<(&Abilities, &Health), Without<Structure>>,
+    mut village_map: ResMut<VillageMap>,
+    mut damage_evt: EventWriter<DamageEvent>,
+) {
+    for event in events.read() {
+        let Ok((abilities, health)) = q_pushable.get(event.target) else {
+            continue;
+        };
+        if health.is_empty() {
+            continue;
+        }
+        let Some(start) = village_map
+            .actors
+            .locate(event.target)
+            .map(|footprint| footprint.origin)
+        else {
+            continue;
+        };
+
+        let outcome = resolve_push(
+            &village_map,
+            start,
+            event.direction,
+            event.distance,
+            abilities,
+        );
+        if outcome.destination() != start {
+            village_map.actors.set(outcome.destination(), event.target);
+            commands
+                .entity(event.target)
+                .insert((TilePath::new(outcome.path.clone()), Pushed));
+        }
+
+        let hit = |target, amount, damage_type| {
+            DamageEvent::new(event.source, target, amount, damage_type)
+        };
+        if let Some(blocker) = outcome.collision {
+            damage_evt.send(hit(event.target, COLLISION_DAMAGE, DamageType::Physical));
+            damage_evt.send(hit(blocker, COLLISION_DAMAGE, DamageType::Physical));
+        }
+        if outcome.drowned {
+            damage_evt.send(hit(event.target, health.value, DamageType::Drowning));
+        }
+    }
+}
+
+fn animate_pushes(
+    mut commands: Commands,
+    mut q_pushed: Query<(Entity, &mut Transform, &mut TilePath), With<Pushed>>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, mut path) in q_pushed.iter_mut() {
+        if path.index >= path.path.len() - 1 {
+            commands.entity(entity).remove::<(TilePath, Pushed)>();
+            continue;
+        }
+
+        let current_tile = path.path[path.index].to_ivec2().as_vec2();
+        let next_tile = path.path[path.index + 1].to_ivec2().as_vec2();
+        let length = current_tile.distance(next_tile);
+        path.factor = f32::min(
+            path.factor + PUSH_SPEED * time.delta_seconds() / length,
+            1.0,
+        );
+
+        let tile_coord = current_tile.lerp(next_tile, path.factor);
+        transform.translation = tile_coord_translation(tile_coord.x, tile_coord.y, 2.0);
+
+        if path.factor >= 1.0 {
+            path.index += 1;
+            path.factor = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::components::Ability;
+    use crate::path_finding::tiles::TileDim;
+
+    fn village_map() -> VillageMap {
+        VillageMap::new(TileDim(5, 5))
+    }
+
+    #[test]
+    fn test_push_slides_the_full_distance() {
+        let village_map = village_map();
+        let outcome = resolve_push(
+            &village_map,
+            Tile(0, 2),
+            TileDir::West,
+            2,
+            &Abilities::default(),
+        );
+        assert_eq!(outcome.path, vec![Tile(0, 2), Tile(1, 2), Tile(2, 2)]);
+        assert_eq!(outcome.collision, None);
+        assert!(!outcome.drowned);
+    }
+
+    #[test]
+    fn test_push_stops_at_the_border() {
+        let village_map = village_map();
+        let outcome = resolve_push(
+            &village_map,
+            Tile(3, 2),
+            TileDir::West,
+            3,
+            &Abilities::default(),
+        );
+        assert_eq!(outcome.destination(), Tile(4, 2));
+        assert_eq!(outcome.collision, None);
+    }
+
+    #[test]
+    fn test_push_collides_with_actor() {
+        let mut village_map = village_map();
+        let blocker = Entity::from_raw(1);
+        village_map.actors.set(Tile(2, 2), blocker);
+        let outcome = resolve_push(
+            &village_map,
+            Tile(0, 2),
+            TileDir::West,
+            3,
+            &Abilities::default(),
+        );
+        assert_eq!(outcome.destination(), Tile(1, 2));
+        assert_eq!(outcome.collision, Some(blocker));
+    }
+
+    #[test]
+    fn test_push_into_water_drowns_non_swimmers() {
+        let mut village_map = village_map();
+        village_map.set_terrain(Tile(1, 2), Terrain::Water);
+        let push = |abilities: &Abilities| {
+            resolve_push(&village_map, Tile(0, 2), TileDir::West, 2, abilities)
+        };
+
+        let outcome = push(&Abilities::default());
+        assert_eq!(outcome.destination(), Tile(1, 2));
+        assert!(outcome.drowned);
+
+        let outcome = push(&Abilities::new(&[Ability::Swimmer]));
+        assert_eq!(outcome.destination(), Tile(2, 2));
+        assert!(!outcome.drowned);
+    }
+
+    #[test]
+    fn test_push_carries_swimmers_out_of_water() {
+        let mut village_map = village_map();
+        village_map.set_terrain(Tile(1, 2), Terrain::Water);
+        village_map.set_terrain(Tile(2, 2), Terrain::Water);
+        let swimmer = Abilities::new(&[Ability::Swimmer]);
+
+        let outcome = resolve_push(&village_map, Tile(0, 2), TileDir::West, 1, &swimmer);
+        assert_eq!(outcome.destination(), Tile(3, 2));
+        assert!(village_map.can_stop_on(outcome.destination(), &swimmer));
+        assert!(!outcome.drowned);
+
+        // Nowhere to climb out.
+        village_map.actors.set(Tile(3, 2), Entity::from_raw(1));
+        let outcome = resolve_push(&village_map, Tile(0, 2), TileDir::West, 1, &swimmer);
+        assert_eq!(outcome.destination(), Tile(2, 2));
+        assert!(outcome.drowned);
+
+        // Aquatic actors stay where they were pushed.
+        let aquatic = Abilities::new(&[Ability::Aquatic]);
+        let outcome = resolve_push(&village_map, Tile(0, 2), TileDir::West, 1, &aquatic);
+        assert_eq!(outcome.destination(), Tile(1, 2));
+        assert!(!outcome.drowned);
+    }
+
+    #[test]
+    fn test_push_direction() {
+        assert_eq!(push_direction(Tile(2, 2), Tile(2, 1)), Some(TileDir::North));
+        assert_eq!(
+            push_direction(Tile(2, 2), Tile(4, 3)),
+            Some(TileDir::SouthWest)
+        );
+        assert_eq!(push_direction(Tile(2, 2), Tile(2, 2)), None);
+    }
+}
@@ -1,15 +1,26 @@
 //! Components representing common properties of game actors
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 /// Amount of armour the actor has
 /// Takes damage first instead of health
 /// Lost armour points aren't regained by healing potions
+#[derive(Component, Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Armour(pub u32);
 
+impl Armour {
+    /// Take as much of the damage as possible, returns the damage left for health.
+    pub fn absorb(&mut self, damage: u32) -> u32 {
+        let absorbed = damage.min(self.0);
+        self.0 -= absorbed;
+        damage - absorbed
+    }
+}
+
 /// Amount of health the actor has.
 /// When health drops to 0 the unit is destroyed
-#[derive(Component, Copy, Clone, Debug, PartialEq)]
+#[derive(Component, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Health {
     pub value: u32,
     pub max: u32,
@@ -28,21 +39,29 @@ impl Health {
         self.value == 0
     }
 
-    pub fn heal(mut self, value: u32) -> u32 {
-        self.value = (self.value + value).min(self.max);
-        self.value
+    /// Heal up to the max health, returns the health gained.
+    pub fn heal(&mut self, value: u32) -> u32 {
+        let before = self.value;
+        self.value = (self.value + value).min(self.max).max(before);
+        self.value - before
     }
 
-    pub fn hurt(mut self, value: u32) -> u32 {
+    /// Returns the health lost.
+    pub fn hurt(&mut self, value: u32) -> u32 {
+        let before = self.value;
         self.value = self.value.saturating_sub(value);
-        self.value
+        before - self.value
     }
 }
 
 /// Number of tiles an actor can move per turn.
-#[derive(Component, Copy, Clone, Debug, Deref, DerefMut, PartialEq)]
+#[derive(Component, Copy, Clone, Debug, Deref, DerefMut, PartialEq, Serialize, Deserialize)]
 pub struct Movement(pub u32);
 
+/// Damage dealt by each of the actor's attacks.
+#[derive(Component, Copy, Clone, Debug, Deref, DerefMut, PartialEq, Serialize, Deserialize)]
+pub struct AttackDamage(pub u32);
+
 pub struct StatsBundle {
     health: Health,
     movement: Movement,
@@ -52,3 +71,26 @@ pub struct StatsBundle {
 /// Does not have to be unique
 #[derive(Component, Default, PartialEq, Debug)]
 pub struct ActorName(pub String);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heal_and_hurt() {
+        let mut health = Health { value: 1, max: 3 };
+        assert_eq!(health.heal(5), 2);
+        assert!(health.is_full());
+        assert_eq!(health.hurt(5), 3);
+        assert!(health.is_empty());
+    }
+
+    #[test]
+    fn test_armour_absorbs_damage_first() {
+        let mut armour = Armour(2);
+        assert_eq!(armour.absorb(1), 0);
+        assert_eq!(armour.absorb(3), 2);
+        assert_eq!(armour, Armour(0));
+        assert_eq!(armour.absorb(1), 1);
+    }
+}
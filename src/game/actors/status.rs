@@ -0,0 +1,326 @@
+//! Timed status effects, ticked at the end of every turn.
+//!
+//! Heroes can also spend their action to block, see [`BlockAction`].
+
+use bevy::color::palettes::{css, tailwind};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::actors_list::PlayerActorList;
+use crate::game::cycle::EndTurn;
+use crate::game::replay::{CommandLog, GameCommand};
+use crate::game::selection::SelectedActor;
+use crate::game::undo::HeroActed;
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+
+use super::damage::{DamageEvent, DamageSystems, DamageType};
+use super::player::reset_unit_turn_states;
+use super::stats::{Health, Movement};
+use super::{Abilities, ActorTurnState};
+
+/// Turn ends a block from [`BlockAction`] lasts, long enough to cover the enemy turn.
+pub const BLOCK_ACTION_TURNS: u32 = 2;
+
+pub struct StatusEffectsPlugin;
+
+impl Plugin for StatusEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<InflictStatus>()
+            .add_event::<BlockAction>()
+            .add_systems(
+                Update,
+                (
+                    block_with_selected_hero
+                        .before(inflict_status)
+                        .run_if(in_state(GameState::BattleTurn)),
+                    inflict_status.after(DamageSystems::Apply),
+                    tick_status_effects
+                        .after(reset_unit_turn_states)
+                        .before(DamageSystems::Collect),
+                )
+                    .run_if(in_state(Screen::Playing)),
+            )
+            // Status effects wear off when the night ends.
+            .add_systems(
+                OnTransition {
+                    exited: GameState::EnemyTurn,
+                    entered: GameState::BuildingTurn,
+                },
+                clear_status_effects,
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StatusKind {
+    /// Lose 1 health at the end of every turn.
+    Poison,
+    /// Can't move or act.
+    Webbed,
+    /// Lose 1 max health and 1 movement.
+    Disease,
+    /// The next attack against the actor does no harm.
+    Block,
+}
+
+impl StatusKind {
+    pub fn is_harmful(self) -> bool {
+        self != StatusKind::Block
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            StatusKind::Poison => "fire_potion",
+            StatusKind::Webbed => "hourglass",
+            StatusKind::Disease => "claw_mark",
+            StatusKind::Block => "bandage",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            StatusKind::Poison => tailwind::GREEN_400.into(),
+            StatusKind::Webbed => css::WHITE.into(),
+            StatusKind::Disease => tailwind::PURPLE_400.into(),
+            StatusKind::Block => tailwind::BLUE_300.into(),
+        }
+    }
+
+    /// Applied to the actor's stats when the effect starts.
+    fn start(self, health: &mut Health, movement: &mut Movement) {
+        if self == StatusKind::Disease {
+            health.max = health.max.saturating_sub(1);
+            health.value = health.value.min(health.max);
+            movement.0 = movement.0.saturating_sub(1);
+        }
+    }
+
+    /// Undoes [`StatusKind::start`] when the effect wears off.
+    fn end(self, health: &mut Health, movement: &mut Movement) {
+        if self == StatusKind::Disease {
+            health.max += 1;
+            movement.0 += 1;
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// Number of turn ends the effect lasts.
+    pub turns: u32,
+}
+
+impl StatusEffect {
+    pub const fn new(kind: StatusKind, turns: u32) -> Self {
+        Self { kind, turns }
+    }
+}
+
+/// Status effects currently affecting an actor, at most one of each kind.
+#[derive(Component, Default, Debug, Clone, PartialEq)]
+pub struct StatusEffects(Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.0.iter()
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.0.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// Add an effect, an effect of the same kind is extended instead.
+    ///
+    /// Returns true if the actor didn't have an effect of this kind yet.
+    pub fn add(&mut self, effect: StatusEffect) -> bool {
+        match self.0.iter_mut().find(|e| e.kind == effect.kind) {
+            Some(existing) => {
+                existing.turns = existing.turns.max(effect.turns);
+                false
+            }
+            None => {
+                self.0.push(effect);
+                true
+            }
+        }
+    }
+
+    /// Use up a block, returns true if the actor had one.
+    pub fn take_block(&mut self) -> bool {
+        let before = self.0.len();
+        self.0.retain(|effect| effect.kind != StatusKind::Block);
+        self.0.len() != before
+    }
+
+    /// Count down all effects by one turn, returns the kinds that wore off.
+    pub fn tick(&mut self) -> Vec<StatusKind> {
+        let mut expired = Vec::new();
+        self.0.retain_mut(|effect| {
+            effect.turns = effect.turns.saturating_sub(1);
+            if effect.turns == 0 {
+                expired.push(effect.kind);
+            }
+            0 < effect.turns
+        });
+        expired
+    }
+
+    fn clear(&mut self) -> Vec<StatusKind> {
+        self.0.drain(..).map(|effect| effect.kind).collect()
+    }
+}
+
+/// Inflicts a status effect on the target actor.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct InflictStatus {
+    pub target: Entity,
+    pub effect: StatusEffect,
+}
+
+/// The selected hero spends its action to block the next attack against it.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BlockAction;
+
+/// Status effect an enemy inflicts with its attacks.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct InflictsStatus(pub StatusEffect);
+
+type StatusData = (
+    &'static mut StatusEffects,
+    &'static mut Health,
+    &'static mut Movement,
+    &'static mut ActorTurnState,
+);
+
+pub fn inflict_status(mut events: EventReader<InflictStatus>, mut q_actors: Query<StatusData>) {
+    for InflictStatus { target, effect } in events.read() {
+        let Ok((mut status_effects, mut health, mut movement, mut turn_state)) =
+            q_actors.get_mut(*target)
+        else {
+            continue;
+        };
+        if health.is_empty() {
+            continue;
+        }
+
+        if status_effects.add(*effect) {
+            effect.kind.start(&mut health, &mut movement);
+        }
+        if effect.kind == StatusKind::Webbed {
+            turn_state.held = true;
+        }
+    }
+}
+
+fn block_with_selected_hero(
+    mut events: EventReader<BlockAction>,
+    selected_actor: Res<SelectedActor>,
+    player_actor_list: Res<PlayerActorList>,
+    mut q_heroes: Query<(&mut ActorTurnState, &Abilities)>,
+    mut command_log: ResMut<CommandLog>,
+    mut inflict_status_evt: EventWriter<InflictStatus>,
+    mut hero_acted_evt: EventWriter<HeroActed>,
+) {
+    for _ in events.read() {
+        let Some(entity) = selected_actor.entity else {
+            continue;
+        };
+        let Some(hero) = player_actor_list.index_of(entity) else {
+            continue;
+        };
+        let Ok((mut turn_state, abilities)) = q_heroes.get_mut(entity) else {
+            continue;
+        };
+        if !turn_state.can_act(abilities) {
+            continue;
+        }
+
+        turn_state.acted();
+        inflict_status_evt.send(InflictStatus {
+            target: entity,
+            effect: StatusEffect::new(StatusKind::Block, BLOCK_ACTION_TURNS),
+        });
+        hero_acted_evt.send(HeroActed);
+        command_log.record(GameCommand::Block { hero });
+    }
+}
+
+fn tick_status_effects(
+    mut events: EventReader<EndTurn>,
+    mut q_actors: Query<(Entity, StatusData)>,
+    mut damage_evt: EventWriter<DamageEvent>,
+) {
+    for _ in events.read() {
+        for (entity, (mut status_effects, mut health, mut movement, mut turn_state)) in
+            q_actors.iter_mut()
+        {
+            if status_effects.0.is_empty() || health.is_empty() {
+                continue;
+            }
+
+            if status_effects.has(StatusKind::Poison) {
+                damage_evt.send(DamageEvent::new(None, entity, 1, DamageType::Poison));
+            }
+            for kind in status_effects.tick() {
+                kind.end(&mut health, &mut movement);
+            }
+            // Turn states were just reset, webbed actors sit out this turn too.
+            turn_state.held = status_effects.has(StatusKind::Webbed);
+        }
+    }
+}
+
+fn clear_status_effects(mut q_actors: Query<StatusData>) {
+    for (mut status_effects, mut health, mut movement, mut turn_state) in q_actors.iter_mut() {
+        for kind in status_effects.clear() {
+            kind.end(&mut health, &mut movement);
+        }
+        turn_state.held = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_extends_existing_effect() {
+        let mut status_effects = StatusEffects::default();
+        assert!(status_effects.add(StatusEffect::new(StatusKind::Poison, 1)));
+        assert!(!status_effects.add(StatusEffect::new(StatusKind::Poison, 3)));
+        assert_eq!(status_effects.iter().count(), 1);
+        assert_eq!(status_effects.iter().next().unwrap().turns, 3);
+    }
+
+    #[test]
+    fn test_tick_expires_effects() {
+        let mut status_effects = StatusEffects::default();
+        status_effects.add(StatusEffect::new(StatusKind::Webbed, 1));
+        status_effects.add(StatusEffect::new(StatusKind::Poison, 2));
+        assert_eq!(status_effects.tick(), vec![StatusKind::Webbed]);
+        assert!(status_effects.has(StatusKind::Poison));
+        assert_eq!(status_effects.tick(), vec![StatusKind::Poison]);
+        assert!(status_effects.iter().next().is_none());
+    }
+
+    #[test]
+    fn test_take_block() {
+        let mut status_effects = StatusEffects::default();
+        assert!(!status_effects.take_block());
+        status_effects.add(StatusEffect::new(StatusKind::Block, 2));
+        assert!(status_effects.take_block());
+        assert!(!status_effects.has(StatusKind::Block));
+    }
+
+    #[test]
+    fn test_disease_is_restored() {
+        let mut health = Health::new(3);
+        let mut movement = Movement(2);
+        StatusKind::Disease.start(&mut health, &mut movement);
+        assert_eq!((health.value, health.max, movement.0), (2, 2, 1));
+        StatusKind::Disease.end(&mut health, &mut movement);
+        assert_eq!((health.value, health.max, movement.0), (2, 3, 2));
+    }
+}
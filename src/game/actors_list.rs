@@ -2,6 +2,7 @@ use super::actors::stats::ActorName;
 use super::actors::PlayerActor;
 use super::inventory::Inventory;
 use super::selection::SelectedActor;
+use super::tower::Garrison;
 use super::INVENTORY_CAPACITY;
 use crate::ui::palette::LABEL_SIZE;
 use crate::ui::prelude::InteractionPalette;
@@ -34,6 +35,12 @@ pub fn actor_list_layout(ui: &mut UiBuilder<Entity>) {
 #[derive(Resource, Default, Clone)]
 pub struct PlayerActorList(pub Vec<Entity>);
 
+impl PlayerActorList {
+    pub fn index_of(&self, entity: Entity) -> Option<usize> {
+        self.0.iter().position(|hero| *hero == entity)
+    }
+}
+
 #[derive(Component)]
 pub struct SelectPlayerActorButton(pub Entity);
 
@@ -211,9 +218,10 @@ pub fn select_player_actor_btn_interaction(
 pub fn inventory_list_layout_vis(
     selected_actor: Res<SelectedActor>,
     mut query: Query<&mut Visibility, With<InventoryListLayout>>,
-    pq: Query<&PlayerActor>,
+    pq: Query<(), Or<(With<PlayerActor>, With<Garrison>)>>,
 ) {
     if let Some(entity) = selected_actor.entity {
+        // Manned towers are aimed with their arrows like a hero's items
         if pq.contains(entity) {
             for mut v in query.iter_mut() {
                 *v = Visibility::Visible;
@@ -1,7 +1,8 @@
 use bevy::prelude::*;
 use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Ability {
     /// Intagible actors can move through all other actors, walls and buildings.
     Intangible,
@@ -32,6 +33,31 @@ pub enum Ability {
 #[derive(Component, Default, Debug, Clone)]
 pub struct Abilities(pub HashSet<Ability>);
 
+impl Abilities {
+    pub fn new(abilities: &[Ability]) -> Self {
+        Self(abilities.iter().copied().collect())
+    }
+
+    pub fn has(&self, ability: Ability) -> bool {
+        self.0.contains(&ability)
+    }
+
+    /// Can pass through tiles occupied by other actors.
+    pub fn passes_actors(&self) -> bool {
+        self.has(Ability::Intangible)
+    }
+
+    /// Can move through water tiles.
+    pub fn passes_water(&self) -> bool {
+        self.has(Ability::Flying) || self.has(Ability::Aquatic) || self.has(Ability::Swimmer)
+    }
+
+    /// Can end a move and act on a water tile.
+    pub fn stays_on_water(&self) -> bool {
+        self.has(Ability::Flying) || self.has(Ability::Aquatic)
+    }
+}
+
 #[derive(Component, Debug)]
 pub struct GroundTileLayer;
 
@@ -1,4 +1,7 @@
 use bevy::color::palettes::css::GREEN_YELLOW;
+use bevy::color::palettes::css::TOMATO;
+use bevy::color::palettes::tailwind::ORANGE_300;
+use bevy::color::palettes::tailwind::RED_500;
 use bevy::color::palettes::tailwind::YELLOW_300;
 use bevy::ui::Val;
 use bevy::ui::ZIndex;
@@ -33,3 +36,9 @@ pub const UNIT_LIST_ZINDEX: ZIndex = ZIndex::Global(150);
 pub const CURSOR_COLOR: bevy::prelude::Srgba = YELLOW_300;
 
 pub const DEPLOYMENT_ZONE_COLOR: bevy::prelude::Srgba = GREEN_YELLOW;
+
+pub const SPAWN_ZONE_COLOR: bevy::prelude::Srgba = TOMATO;
+
+pub const ENEMY_INTENT_COLOR: bevy::prelude::Srgba = ORANGE_300;
+
+pub const ENEMY_TARGET_COLOR: bevy::prelude::Srgba = RED_500;
@@ -1,14 +1,16 @@
 use bevy::color::palettes::css;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
 use sickle_ui::prelude::*;
 
 use crate::path_finding::is_any_path;
-use crate::path_finding::tiles::Tile;
+use crate::path_finding::tiles::{Tile, TileDim};
 use crate::screen::Screen;
 use crate::ui::prelude::InteractionPalette;
 
 use super::actors::spawn::SpawnAnimation;
+use super::actors::stats::Health;
 use super::actors::Structure;
 use super::actors::StructureBundle;
 use super::components::ArcherTower;
@@ -16,26 +18,35 @@ use super::components::Blacksmith;
 use super::components::BuildingProgressLabel;
 use super::components::ConstructionWorkers;
 use super::components::House;
+use super::components::PopulationCapacity;
 use super::components::RemainingConstructionTurns;
 use super::components::Tavern;
 use super::constants::BIG_TEXT_SIZE;
+use super::constants::HOUSE_POPULATION_CAPACITY;
 use super::constants::ICON_SIZE;
 use super::constants::TEXT_SIZE;
 use super::events::EndDayTurn;
 use super::events::SelectStructureTypeEvent;
 use super::game_params::Game;
-use super::map::VillageMap;
+use super::level::Terrain;
+use super::map::{Footprint, VillageMap};
 use super::picking::PickableTile;
 use super::picking::TilePressedEvent;
+use super::replay::CommandLog;
+use super::replay::GameCommand;
+use super::replay::ReplayButtonPress;
 use super::resources::SelectedStructueType;
+use super::resources::SelectedStructureRotation;
 use super::resources::VillageEmployment;
 use super::resources::VillageGold;
 use super::resources::VillagePopulation;
-use super::tile_set::tile_coord_translation;
+use super::tile_set::footprint_translation;
 use super::tile_set::TileSet;
 use super::tile_set::TILE_ANCHOR;
+use super::trap::TrapButton;
+use super::trap::TrapType;
 
-#[derive(Component, Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Component, Reflect, Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum StructureType {
     SmallHouse,
     House,
@@ -43,16 +54,25 @@ pub enum StructureType {
     Tavern,
     ArcherTower,
     Blacksmith,
+    /// Blocks monsters, can't be placed where it cuts a structure off from the map border.
+    Wall,
+    /// Blocks monsters like a wall but villagers pass, so it may close off structures.
+    Gate,
+    /// Dug next to water, turns its tile into water once finished.
+    Moat,
 }
 
 impl StructureType {
-    pub const ALL: [Self; 6] = [
+    pub const ALL: [Self; 9] = [
         Self::SmallHouse,
         Self::House,
         Self::StrongHouse,
         Self::Tavern,
         Self::ArcherTower,
         Self::Blacksmith,
+        Self::Wall,
+        Self::Gate,
+        Self::Moat,
     ];
 
     pub fn name(&self) -> &str {
@@ -63,6 +83,9 @@ impl StructureType {
             StructureType::Tavern => "tavern",
             StructureType::ArcherTower => "tower",
             StructureType::Blacksmith => "blacksmith",
+            StructureType::Wall => "wall",
+            StructureType::Gate => "gate",
+            StructureType::Moat => "moat",
         }
     }
 
@@ -74,6 +97,67 @@ impl StructureType {
             StructureType::Tavern => "tavern",
             StructureType::ArcherTower => "tower",
             StructureType::Blacksmith => "blacksmith",
+            StructureType::Wall => "block_grey",
+            StructureType::Gate => "block_orange",
+            StructureType::Moat => "waterblock",
+        }
+    }
+
+    /// Health of the finished structure.
+    pub fn health(&self) -> u32 {
+        match self {
+            StructureType::Wall => 6,
+            StructureType::Gate => 4,
+            _ => 2,
+        }
+    }
+
+    /// Villagers that can live in the finished structure.
+    pub fn population_capacity(&self) -> u32 {
+        match self.is_defensive() {
+            true => 0,
+            false => HOUSE_POPULATION_CAPACITY,
+        }
+    }
+
+    /// Walls, gates and moats only protect the village, they do not count as part of it.
+    pub fn is_defensive(&self) -> bool {
+        matches!(
+            self,
+            StructureType::Wall | StructureType::Gate | StructureType::Moat
+        )
+    }
+
+    /// Tiles covered by the structure before rotating it.
+    pub fn size(&self) -> TileDim {
+        match self {
+            StructureType::Tavern | StructureType::Blacksmith => TileDim(2, 1),
+            _ => TileDim::ONE,
+        }
+    }
+
+    /// Tiles covered by the structure built from `origin`, rotated structures swap width and depth.
+    pub fn footprint(&self, origin: Tile, rotated: bool) -> Footprint {
+        let footprint = Footprint::new(origin, self.size());
+        match rotated {
+            true => footprint.rotated(),
+            false => footprint,
+        }
+    }
+
+    /// Counts as open ground when checking that every structure can be reached.
+    pub fn is_passable(&self) -> bool {
+        *self == StructureType::Gate
+    }
+
+    /// Structure type of an object tile placed in a level asset.
+    pub fn from_tile_texture(texture: &str) -> Option<Self> {
+        match texture {
+            "house1" => Some(StructureType::House),
+            "tavern" => Some(StructureType::Tavern),
+            "tower" => Some(StructureType::ArcherTower),
+            "blacksmith" => Some(StructureType::Blacksmith),
+            _ => None,
         }
     }
 }
@@ -81,6 +165,10 @@ impl StructureType {
 #[derive(Component)]
 pub struct BuildButton(pub StructureType);
 
+/// Turns the structures covering several tiles a quarter turn.
+#[derive(Component)]
+pub struct RotateStructureButton;
+
 pub struct StructureCost {
     pub turns: u32,
     pub workers: u32,
@@ -149,6 +237,33 @@ impl Default for StructureCosts {
                     is_exclusive: true,
                 },
             ),
+            (
+                StructureType::Wall,
+                StructureCost {
+                    turns: 1,
+                    workers: 5,
+                    gold: 15,
+                    is_exclusive: false,
+                },
+            ),
+            (
+                StructureType::Gate,
+                StructureCost {
+                    turns: 2,
+                    workers: 5,
+                    gold: 30,
+                    is_exclusive: false,
+                },
+            ),
+            (
+                StructureType::Moat,
+                StructureCost {
+                    turns: 2,
+                    workers: 10,
+                    gold: 20,
+                    is_exclusive: false,
+                },
+            ),
         ]
         .into_iter()
         .collect();
@@ -191,52 +306,81 @@ pub fn building_panel_layout(mut commands: Commands, costs: Res<StructureCosts>)
                 let Some(cost) = costs.get(&building_type) else {
                     continue;
                 };
+                cost_button(
+                    ui,
+                    BuildButton(building_type),
+                    building_type.name(),
+                    &[
+                        ("icons/gold_coins.png", cost.gold),
+                        ("icons/population.png", cost.workers),
+                        ("icons/hourglass.png", cost.turns),
+                    ],
+                );
+            }
 
-                ui.container(ButtonBundle { ..default() }, |ui| {
-                    ui.insert((
-                        BuildButton(building_type),
-                        InteractionPalette {
-                            none: css::BLACK.into(),
-                            hovered: css::TEAL.into(),
-                            pressed: css::INDIAN_RED.into(),
-                        },
-                    ))
-                    .style()
-                    .border(UiRect::all(Val::Px(2.)))
-                    .border_color(Color::WHITE)
-                    .padding(UiRect::all(Val::Px(5.)));
-
-                    ui.row(|ui| {
-                        ui.style().justify_content(JustifyContent::SpaceBetween);
-                        ui.label(LabelConfig::from(building_type.name()))
-                            .style()
-                            .font_size(TEXT_SIZE);
-
-                        ui.column(|ui| {
-                            ui.style().justify_content(JustifyContent::End);
-                            ui.row(|ui| {
-                                ui.style().column_gap(Val::Px(10.));
-                                for (icon, value) in [
-                                    ("icons/gold_coins.png", cost.gold.to_string()),
-                                    ("icons/population.png", cost.workers.to_string()),
-                                    ("icons/hourglass.png", cost.turns.to_string()),
-                                ] {
-                                    ui.row(|ui| {
-                                        ui.style()
-                                            .justify_content(JustifyContent::End)
-                                            .column_gap(Val::Px(1.));
-                                        ui.icon(icon).style().width(ICON_SIZE).height(ICON_SIZE);
-
-                                        ui.label(LabelConfig::from(value))
-                                            .style()
-                                            .font_size(TEXT_SIZE);
-                                    });
-                                }
-                            });
+            cost_button(ui, RotateStructureButton, "rotate", &[]);
+
+            ui.label(LabelConfig::from("Traps"))
+                .style()
+                .font_size(BIG_TEXT_SIZE);
+
+            for trap_type in TrapType::ALL {
+                cost_button(
+                    ui,
+                    TrapButton(trap_type),
+                    trap_type.name(),
+                    &[("icons/gold_coins.png", trap_type.gold())],
+                );
+            }
+        });
+    });
+}
+
+/// A button of the building panel, labelled with its name and costs.
+fn cost_button(
+    ui: &mut UiBuilder<Entity>,
+    button: impl Bundle,
+    name: &str,
+    costs: &[(&'static str, u32)],
+) {
+    ui.container(ButtonBundle { ..default() }, |ui| {
+        ui.insert((
+            button,
+            InteractionPalette {
+                none: css::BLACK.into(),
+                hovered: css::TEAL.into(),
+                pressed: css::INDIAN_RED.into(),
+            },
+        ))
+        .style()
+        .border(UiRect::all(Val::Px(2.)))
+        .border_color(Color::WHITE)
+        .padding(UiRect::all(Val::Px(5.)));
+
+        ui.row(|ui| {
+            ui.style().justify_content(JustifyContent::SpaceBetween);
+            ui.label(LabelConfig::from(name))
+                .style()
+                .font_size(TEXT_SIZE);
+
+            ui.column(|ui| {
+                ui.style().justify_content(JustifyContent::End);
+                ui.row(|ui| {
+                    ui.style().column_gap(Val::Px(10.));
+                    for (icon, value) in costs {
+                        ui.row(|ui| {
+                            ui.style()
+                                .justify_content(JustifyContent::End)
+                                .column_gap(Val::Px(1.));
+                            ui.icon(*icon).style().width(ICON_SIZE).height(ICON_SIZE);
+
+                            ui.label(LabelConfig::from(value.to_string()))
+                                .style()
+                                .font_size(TEXT_SIZE);
                         });
-                    });
+                    }
                 });
-            }
+            });
         });
     });
 }
@@ -275,6 +419,45 @@ pub fn update_build_panel(
     }
 }
 
+pub fn rotate_btn_interaction(
+    q_interactions: Query<&Interaction, (Changed<Interaction>, With<RotateStructureButton>)>,
+    mut rotation: ResMut<SelectedStructureRotation>,
+) {
+    for interaction in q_interactions.iter() {
+        if *interaction == Interaction::Pressed {
+            rotation.0 = !rotation.0;
+        }
+    }
+}
+
+pub fn update_rotate_button(
+    rotation: Res<SelectedStructureRotation>,
+    mut q_buttons: Query<
+        (&mut InteractionPalette, &mut BackgroundColor),
+        With<RotateStructureButton>,
+    >,
+) {
+    for (mut palette, mut color) in q_buttons.iter_mut() {
+        if rotation.0 {
+            palette.none = css::RED.into();
+        } else {
+            palette.none = Color::BLACK;
+            color.0 = Color::BLACK;
+        }
+    }
+}
+
+pub fn press_replayed_build_buttons(
+    mut events: EventReader<ReplayButtonPress>,
+    mut rotation: ResMut<SelectedStructureRotation>,
+) {
+    for ReplayButtonPress(command) in events.read() {
+        if let GameCommand::Build { rotated, .. } = command {
+            rotation.0 = *rotated;
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct BuildingSite;
 
@@ -284,12 +467,14 @@ pub fn spawn_in_progress_building(
     //mut village_map: ResMut<VillageMap>,
     tile_set: Res<TileSet>,
     selected_structure_type: Res<SelectedStructueType>,
+    rotation: Res<SelectedStructureRotation>,
     structure_cost: Res<StructureCosts>,
     population: Res<VillagePopulation>,
     mut working_population: ResMut<VillageEmployment>,
     mut gold: ResMut<VillageGold>,
     structure_query: Query<&StructureType>,
     mut game: Game,
+    mut command_log: ResMut<CommandLog>,
 ) {
     let Some(TilePressedEvent(tile)) = events.read().last() else {
         return;
@@ -303,13 +488,12 @@ pub fn spawn_in_progress_building(
         return;
     };
 
-    if game.is_occupied(*tile) {
+    let footprint = structure_type.footprint(*tile, rotation.0);
+    if footprint.tiles().any(|tile| game.is_occupied(tile)) {
         return;
     }
 
-    let sites = game.find_tiles_that_can_be_built_on();
-
-    if !sites.contains(tile) {
+    if !game.can_build(footprint, structure_type) {
         return;
     }
 
@@ -332,8 +516,34 @@ pub fn spawn_in_progress_building(
     gold.0 -= cost.gold;
     working_population.0 += cost.workers;
 
-    let object_translation = tile_coord_translation(tile.x() as f32, tile.y() as f32, 2.0);
-    let id = commands
+    let id = spawn_building_site(
+        &mut commands,
+        &tile_set,
+        footprint,
+        structure_type,
+        cost.turns,
+        cost.workers,
+    );
+
+    game.insert(footprint, id);
+    command_log.record(GameCommand::Build {
+        structure: structure_type,
+        tile: *tile,
+        rotated: rotation.0,
+    });
+}
+
+/// Spawn an unfinished structure, the caller is responsible for placing it on the map.
+pub fn spawn_building_site(
+    commands: &mut Commands,
+    tile_set: &TileSet,
+    footprint: Footprint,
+    structure_type: StructureType,
+    remaining_turns: u32,
+    workers: u32,
+) -> Entity {
+    let object_translation = footprint_translation(footprint, 2.0);
+    commands
         .spawn((
             SpriteBundle {
                 sprite: Sprite {
@@ -347,8 +557,8 @@ pub fn spawn_in_progress_building(
             },
             StateScoped(Screen::Playing),
             structure_type,
-            RemainingConstructionTurns(cost.turns),
-            ConstructionWorkers(cost.workers),
+            RemainingConstructionTurns(remaining_turns),
+            ConstructionWorkers(workers),
             BuildingSite,
             Structure,
         ))
@@ -356,7 +566,7 @@ pub fn spawn_in_progress_building(
             builder.spawn((
                 Text2dBundle {
                     text: Text::from_section(
-                        cost.turns.to_string(),
+                        remaining_turns.to_string(),
                         TextStyle {
                             font_size: 100.,
                             ..Default::default()
@@ -380,9 +590,7 @@ pub fn spawn_in_progress_building(
                 ..Default::default()
             },));
         })
-        .id();
-
-    game.insert(*tile, id);
+        .id()
 }
 
 pub fn update_building_progress(
@@ -404,58 +612,76 @@ pub fn update_building_progress(
             if b.0 == 0 {
                 working_population.0 = working_population.0.saturating_sub(w.0);
                 commands.entity(e).despawn_recursive();
-                let Some(tile) = village_map.actors.locate(e) else {
+                let Some(footprint) = village_map.actors.locate(e) else {
                     continue;
                 };
-                let object_translation =
-                    tile_coord_translation(tile.x() as f32, tile.y() as f32, 2.);
-                let mut object_entity = commands.spawn((
-                    SpriteBundle {
-                        sprite: Sprite {
-                            anchor: TILE_ANCHOR,
-                            ..Default::default()
-                        },
-                        texture: tile_set.get(s.tile_texture()),
-                        transform: Transform::from_translation(object_translation),
-                        ..default()
-                    },
-                    tile,
-                    PickableTile,
-                    StateScoped(Screen::Playing),
-                    StructureBundle::default(),
-                    SpawnAnimation::new(object_translation),
-                    s.clone(),
-                ));
-                println!("building structure type = {s:?}");
-                println!("entity = {:?}", object_entity.id());
-                match s {
-                    StructureType::Tavern => {
-                        println!("Insert Tavern");
-                        object_entity.insert(Tavern);
-                    }
-                    StructureType::SmallHouse => {
-                        object_entity.insert(House);
+                if *s == StructureType::Moat {
+                    village_map.actors.remove_entity(e);
+                    for tile in footprint.tiles() {
+                        village_map.set_terrain(tile, Terrain::Water);
                     }
-                    StructureType::House => {
-                        object_entity.insert(House);
-                    }
-                    StructureType::StrongHouse => {
-                        object_entity.insert(House);
-                    }
-                    StructureType::ArcherTower => {
-                        object_entity.insert(ArcherTower);
-                    }
-                    StructureType::Blacksmith => {
-                        object_entity.insert(Blacksmith);
-                    }
-                };
-
-                village_map.actors.set(tile, object_entity.id());
+                    continue;
+                }
+                let id = spawn_structure(&mut commands, &tile_set, footprint, *s);
+                village_map.actors.place(footprint, id);
             }
         }
     }
 }
 
+/// Spawn a finished structure, the caller is responsible for placing it on the map.
+pub fn spawn_structure(
+    commands: &mut Commands,
+    tile_set: &TileSet,
+    footprint: Footprint,
+    structure_type: StructureType,
+) -> Entity {
+    let object_translation = footprint_translation(footprint, 2.);
+    let mut object_entity = commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                anchor: TILE_ANCHOR,
+                ..Default::default()
+            },
+            texture: tile_set.get(structure_type.tile_texture()),
+            transform: Transform::from_translation(object_translation),
+            ..default()
+        },
+        footprint.origin,
+        PickableTile,
+        StateScoped(Screen::Playing),
+        StructureBundle {
+            health: Health::new(structure_type.health()),
+            population_capacity: PopulationCapacity(structure_type.population_capacity()),
+            ..default()
+        },
+        SpawnAnimation::new(object_translation),
+        structure_type,
+    ));
+    match structure_type {
+        StructureType::Tavern => {
+            object_entity.insert(Tavern);
+        }
+        StructureType::SmallHouse => {
+            object_entity.insert(House);
+        }
+        StructureType::House => {
+            object_entity.insert(House);
+        }
+        StructureType::StrongHouse => {
+            object_entity.insert(House);
+        }
+        StructureType::ArcherTower => {
+            object_entity.insert(ArcherTower);
+        }
+        StructureType::Blacksmith => {
+            object_entity.insert(Blacksmith);
+        }
+        StructureType::Wall | StructureType::Gate | StructureType::Moat => {}
+    };
+    object_entity.id()
+}
+
 pub fn update_building_progress_labels(
     mut building_query: Query<(&mut Text, &Parent), With<BuildingProgressLabel>>,
     remaining: Query<&RemainingConstructionTurns>,
@@ -1,10 +1,17 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::screen::{playing::GameState, Screen};
 
 use super::{
-    actors_list::PlayerActorList, events::EndDayTurn, map::VillageMap,
-    resources::VillageEmployment, update_resource_label, update_resource_label_system, WatchRes,
+    actors::enemy::FinalBoss,
+    actors_list::PlayerActorList,
+    events::EndDayTurn,
+    map::VillageMap,
+    replay::{CommandLog, GameCommand},
+    resources::VillageEmployment,
+    tower::{is_deployed, Garrison},
+    update_resource_label, update_resource_label_system, WatchRes,
 };
 
 /// Number of turns in a day.
@@ -13,6 +20,10 @@ pub const TURN_PER_DAY: u32 = 10;
 pub const DAY_PER_SEASON: u32 = 2; // TODO: Determine a balanced number, set to low for testing.
 /// Number of days in a cycle which contains all 3 seasons.
 pub const DAY_PER_CYCLE: u32 = DAY_PER_SEASON * 3;
+/// Every fifth night is a full moon.
+pub const DAY_PER_FULL_MOON: u32 = 5;
+/// The last day of the first winter ends in an eclipse instead of a night.
+pub const ECLIPSE_DAY: u32 = DAY_PER_CYCLE - 1;
 
 const M: f32 = 0.4;
 pub const CLEAR_BACKGROUND: Color = Color::srgb(0.7 * M, 0.75 * M, 0.8 * M);
@@ -28,6 +39,8 @@ impl Plugin for CyclePlugin {
             .init_resource::<Season>()
             .init_resource::<DayCycle>()
             .init_resource::<Turn>()
+            .init_resource::<DaysUntilFullMoon>()
+            .init_resource::<MoonPhase>()
             .init_resource::<VillageEmployment>()
             .add_event::<NextSeason>()
             .add_event::<EndTurn>()
@@ -58,6 +71,7 @@ impl Plugin for CyclePlugin {
 fn update_background(
     time_of_day: Res<State<TimeOfDay>>,
     season: Res<Season>,
+    moon_phase: Res<MoonPhase>,
     mut clear_color: ResMut<ClearColor>,
 ) {
     let color = match *season {
@@ -67,6 +81,7 @@ fn update_background(
     };
     clear_color.0 = match time_of_day.get() {
         TimeOfDay::Day => color,
+        TimeOfDay::Night if *moon_phase == MoonPhase::Eclipse => color.mix(&Color::BLACK, 0.8),
         TimeOfDay::Night => color.mix(&Color::BLACK, 0.5),
     };
 }
@@ -75,15 +90,35 @@ fn reset_background(mut clear_color: ResMut<ClearColor>) {
     clear_color.0 = CLEAR_BACKGROUND;
 }
 
-fn reset_cycle(mut season: ResMut<Season>, mut turn: ResMut<Turn>) {
+pub fn reset_cycle(
+    mut season: ResMut<Season>,
+    mut turn: ResMut<Turn>,
+    mut days_until_full_moon: ResMut<DaysUntilFullMoon>,
+    mut moon_phase: ResMut<MoonPhase>,
+) {
     *season = Season::default();
     turn.0 = 0;
+    *days_until_full_moon = DaysUntilFullMoon::default();
+    *moon_phase = MoonPhase::default();
 }
 
-fn end_turn(mut end_turn_evt: EventReader<EndTurn>, mut turn: ResMut<Turn>) {
+fn end_turn(
+    mut end_turn_evt: EventReader<EndTurn>,
+    mut turn: ResMut<Turn>,
+    moon_phase: Res<MoonPhase>,
+    q_final_bosses: Query<(), With<FinalBoss>>,
+    mut command_log: ResMut<CommandLog>,
+) {
     if end_turn_evt.is_empty() == false {
         end_turn_evt.clear();
-        turn.0 += 1;
+        // The eclipse night does not end, it stays on its last turn until the final boss is dead.
+        let eclipse_continues = *moon_phase == MoonPhase::Eclipse
+            && turn.0 % TURN_PER_DAY == TURN_PER_DAY - 1
+            && q_final_bosses.is_empty() == false;
+        if eclipse_continues == false {
+            turn.0 += 1;
+        }
+        command_log.record(GameCommand::EndTurn);
     }
 }
 
@@ -92,16 +127,19 @@ fn end_deployment(
     mut gamestate: ResMut<NextState<GameState>>,
     player_unit_list: Res<PlayerActorList>,
     village_map: Res<VillageMap>,
+    q_garrisons: Query<&Garrison>,
+    mut command_log: ResMut<CommandLog>,
 ) {
     if !end_deployment_evt.is_empty() {
         end_deployment_evt.clear();
         for entity in player_unit_list.0.iter() {
-            if village_map.actors.locate(*entity).is_none() {
+            if !is_deployed(*entity, &village_map, &q_garrisons) {
                 println!("Undeployed still");
                 return;
             }
         }
         gamestate.set(GameState::BattleTurn);
+        command_log.record(GameCommand::Fight);
     }
 }
 
@@ -110,6 +148,8 @@ fn update_cycle(
     mut day_cycle: ResMut<DayCycle>,
     mut next_tod: ResMut<NextState<TimeOfDay>>,
     mut season: ResMut<Season>,
+    mut days_until_full_moon: ResMut<DaysUntilFullMoon>,
+    mut moon_phase: ResMut<MoonPhase>,
     mut game_state: ResMut<NextState<GameState>>,
 ) {
     // Season
@@ -121,6 +161,14 @@ fn update_cycle(
         num => unreachable!("Season range is [0, 3) but given {num} instead!"),
     });
 
+    // Moon
+    days_until_full_moon.set_if_neq(DaysUntilFullMoon::from_day(day));
+    moon_phase.set_if_neq(match day {
+        ECLIPSE_DAY => MoonPhase::Eclipse,
+        _ if days_until_full_moon.0 == 0 => MoonPhase::Full,
+        _ => MoonPhase::Waxing,
+    });
+
     // Day cycle
     *day_cycle = DayCycle::from(*season);
 
@@ -141,12 +189,17 @@ fn turn_until_label(
     mut q_texts: Query<&mut Text, With<WatchRes<Turn>>>,
     turn: Res<Turn>,
     day_cycle: Res<DayCycle>,
+    moon_phase: Res<MoonPhase>,
 ) {
     let Ok(mut text) = q_texts.get_single_mut() else {
         return;
     };
 
     let turn_in_day = turn.0 % TURN_PER_DAY;
+    if *moon_phase == MoonPhase::Eclipse && turn_in_day >= day_cycle.day {
+        text.sections[0].value = "Eclipse, defeat the final boss".to_string();
+        return;
+    }
     let (turn_left, target_day) = match turn_in_day >= day_cycle.day {
         true => (TURN_PER_DAY - turn_in_day, "day"),
         false => (day_cycle.day - turn_in_day, "night"),
@@ -166,7 +219,7 @@ pub struct EndTurn;
 #[derive(Event, Copy, Clone, PartialEq, Default)]
 pub struct EndDeployment;
 
-#[derive(Resource, Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[derive(Resource, Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum Season {
     #[default]
     Summer,
@@ -224,11 +277,40 @@ impl From<Season> for DayCycle {
     }
 }
 
+/// Days left until the next full moon, 0 on the day of the full moon.
 #[derive(Resource, Debug, Copy, Clone, PartialEq)]
 pub struct DaysUntilFullMoon(pub u32);
 
+impl DaysUntilFullMoon {
+    pub fn from_day(day: u32) -> Self {
+        Self(DAY_PER_FULL_MOON - 1 - day % DAY_PER_FULL_MOON)
+    }
+}
+
 impl Default for DaysUntilFullMoon {
     fn default() -> Self {
-        Self(3)
+        Self::from_day(0)
+    }
+}
+
+/// Moon of the current day, bosses attack on a full moon and during the eclipse.
+#[derive(Resource, Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum MoonPhase {
+    #[default]
+    Waxing,
+    Full,
+    /// The night lasts until the final boss is defeated.
+    Eclipse,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_until_full_moon() {
+        assert_eq!(DaysUntilFullMoon::from_day(0), DaysUntilFullMoon(4));
+        assert_eq!(DaysUntilFullMoon::from_day(4), DaysUntilFullMoon(0));
+        assert_eq!(DaysUntilFullMoon::from_day(5), DaysUntilFullMoon(4));
     }
 }
@@ -4,12 +4,15 @@ use crate::screen::Screen;
 use super::actors_list::PlayerActorList;
 use super::assets::SoundtrackKey;
 use super::audio::soundtrack::PlaySoundtrack;
+use super::level::LevelZones;
 use super::map::VillageMap;
 use super::picking::TilePressedEvent;
+use super::replay::{CommandLog, GameCommand};
 use super::selection::SelectedActor;
 use super::selection::SelectedTiles;
 use super::tile_set::tile_coord_translation;
 use super::tile_set::TileSet;
+use super::tower::{is_deployed, Garrison};
 use bevy::color::palettes::css::LIME;
 use bevy::prelude::*;
 
@@ -17,25 +20,18 @@ pub fn deployment_setup(
     player_unit_list: Res<PlayerActorList>,
     mut selected_unit: ResMut<SelectedActor>,
     mut village_map: ResMut<VillageMap>,
+    level_zones: Res<LevelZones>,
     mut commands: Commands,
 ) {
     commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Battle));
     selected_unit.entity = player_unit_list.0.first().copied();
-    let size = village_map.size;
-    let r = IRect::from_corners(
-        IVec2::ZERO,
-        IVec2 {
-            x: size.x() as i32,
-            y: size.y() as i32,
-        },
-    )
-    .inflate(-3);
-    for x in r.min.x..r.max.x {
-        for y in r.min.y..r.max.y {
-            let value = Tile(x, y);
-            village_map.deployment_zone.insert(value);
-        }
-    }
+    let bounds = village_map.bounds();
+    village_map.deployment_zone.extend(
+        level_zones
+            .deployment
+            .into_iter()
+            .filter(|tile| bounds.contains(*tile)),
+    );
 }
 
 pub fn deployment_zone_visualization(
@@ -51,9 +47,10 @@ pub fn deployment_zone_visualization(
 pub fn is_deployment_ready(
     player_unit_list: Res<PlayerActorList>,
     village_map: Res<VillageMap>,
+    q_garrisons: Query<&Garrison>,
 ) -> bool {
     for entity in player_unit_list.0.iter() {
-        if village_map.actors.locate(*entity).is_none() {
+        if !is_deployed(*entity, &village_map, &q_garrisons) {
             return false;
         }
     }
@@ -66,7 +63,9 @@ pub fn deploy_unit(
     mut selected_unit: ResMut<SelectedActor>,
     player_unit_list: Res<PlayerActorList>,
     tile_set: Res<TileSet>,
+    q_garrisons: Query<&Garrison>,
     mut commands: Commands,
+    mut command_log: ResMut<CommandLog>,
 ) {
     let Some(entity_to_deploy) = selected_unit.entity else {
         return;
@@ -91,11 +90,17 @@ pub fn deploy_unit(
                     StateScoped(Screen::Playing),
                 ));
                 village_map.actors.set(*target_tile, entity_to_deploy);
+                if let Some(hero) = player_unit_list.index_of(entity_to_deploy) {
+                    command_log.record(GameCommand::Deploy {
+                        hero,
+                        tile: *target_tile,
+                    });
+                }
                 println!("Placing {} at {:?}", entity_to_deploy, target_tile);
                 if let Some(next_unit) = player_unit_list
                     .0
                     .iter()
-                    .find(|entity| village_map.actors.locate(**entity).is_none())
+                    .find(|entity| !is_deployed(**entity, &village_map, &q_garrisons))
                 {
                     println!("deployed: {entity_to_deploy:?}, next unit: {next_unit:?}");
                     selected_unit.set(*next_unit);
@@ -11,7 +11,9 @@ use super::cycle::Turn;
 use super::resources::VillageEmployment;
 use super::resources::VillageGold;
 use super::resources::VillagePopulation;
+use super::rng::GameRng;
 use super::update_resource_label;
+use super::victory::RunStats;
 
 pub struct EconomyPlugin;
 
@@ -40,18 +42,19 @@ pub fn update_income(
     mut gold: ResMut<VillageGold>,
     income_query: Query<&Income, With<Structure>>,
     cap_query: Query<&PopulationCapacity, With<Structure>>,
+    mut game_rng: ResMut<GameRng>,
+    mut run_stats: ResMut<RunStats>,
 ) {
     let mut total_population_capacity = 0;
     for population_capacity in cap_query.iter() {
         total_population_capacity += population_capacity.0;
     }
 
-    gold.0 += population.0.min(total_population_capacity);
+    let earned = population.0.min(total_population_capacity)
+        + income_query.iter().map(|income| income.0).sum::<u32>();
+    gold.0 += earned;
+    run_stats.gold_earned += earned;
 
-    for income in income_query.iter() {
-        gold.0 += income.0;
-    }
-    let mut rng = rand::thread_rng();
-    population.0 += 5 + rng.gen_range(0..10);
+    population.0 += 5 + game_rng.gen_range(0..10);
     population.0 = population.0.min(total_population_capacity);
 }
@@ -8,13 +8,13 @@ use super::actors::stats::Movement;
 use super::actors::EnemyActor;
 use super::actors::PlayerActor;
 use super::actors::Structure;
+use super::construction::StructureType;
 use super::level::Terrain;
-use super::map::VillageMap;
+use super::map::{Footprint, VillageMap};
 use crate::path_finding::tiles::Tiled;
 use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::utils::HashSet;
-use bimap::Overwritten;
 
 pub trait TileRef: Sized + Copy {
     fn get(self, game: &Game) -> Option<(Tile, Entity)> {
@@ -63,6 +63,7 @@ pub struct Game<'w, 's> {
     player_actors: Query<'w, 's, Entity, With<PlayerActor>>,
     enemy_actors: Query<'w, 's, Entity, With<EnemyActor>>,
     structures: Query<'w, 's, Entity, With<Structure>>,
+    structure_types: Query<'w, 's, &'static StructureType>,
 }
 
 impl Game<'_, '_> {
@@ -74,8 +75,12 @@ impl Game<'_, '_> {
         r.get_tile(self).and_then(|tile| self.map.actors.get(tile))
     }
 
+    /// Origin of the actor's footprint.
     pub fn find_actor(&self, entity: Entity) -> Option<Tile> {
-        self.map.actors.locate(entity)
+        self.map
+            .actors
+            .locate(entity)
+            .map(|footprint| footprint.origin)
     }
 
     pub fn tiles(&self) -> impl Iterator<Item = Tile> {
@@ -113,51 +118,130 @@ impl Game<'_, '_> {
             .and_then(move |tile| (self.map.actors.remove(tile).map(|entity| (tile, entity))))
     }
 
+    /// Every tile covered by a structure.
     pub fn structures(&self) -> impl Iterator<Item = (Tile, Entity)> + '_ {
-        self.structures.iter().filter_map(|entity| self.get(entity))
+        self.map
+            .actors
+            .iter()
+            .filter(|(_, entity)| self.structures.contains(*entity))
     }
 
-    pub fn insert(&mut self, tile: Tile, entity: Entity) -> InsertedActor {
+    pub fn insert(&mut self, footprint: Footprint, entity: Entity) -> InsertedActor {
         assert!(
-            self.contains(tile),
-            "{tile:?} outside of map bounds {:?}",
+            footprint.tiles().all(|tile| self.contains(tile)),
+            "{footprint:?} outside of map bounds {:?}",
             self.map.bounds()
         );
-        match self.map.actors.set(tile, entity) {
-            Overwritten::Left(_, entity) => InsertedActor::ReplacedActor(entity),
-            Overwritten::Right(tile, _) => InsertedActor::MovedFrom(tile),
-            _ => InsertedActor::Placed,
+        let moved_from = self.map.actors.locate(entity);
+        match (self.map.actors.place(footprint, entity).first(), moved_from) {
+            (Some(replaced), _) => InsertedActor::ReplacedActor(*replaced),
+            (None, Some(footprint)) => InsertedActor::MovedFrom(footprint),
+            (None, None) => InsertedActor::Placed,
         }
     }
 
+    /// Gates count as open ground for the reachability invariant.
+    pub fn is_passable_structure(&self, entity: Entity) -> bool {
+        self.structure_types
+            .get(entity)
+            .is_ok_and(|structure_type| structure_type.is_passable())
+    }
+
+    /// Walls, gates and moats do not extend the area that can be built on.
+    pub fn is_defensive_structure(&self, entity: Entity) -> bool {
+        self.structure_types
+            .get(entity)
+            .is_ok_and(|structure_type| structure_type.is_defensive())
+    }
+
     pub fn all_structures_reachable(&self) -> bool {
+        self.all_structures_reachable_with(None)
+    }
+
+    /// Same as [`Game::all_structures_reachable`] with another blocking structure on `new_structure`.
+    ///
+    /// A structure covering several tiles is reachable if any of its tiles is.
+    fn all_structures_reachable_with(&self, new_structure: Option<Footprint>) -> bool {
+        let is_new = |tile: Tile| new_structure.is_some_and(|footprint| footprint.contains(tile));
         let Some(start) = self.perimeter().find(|tile| {
             self.terrain(*tile)
                 .map(|terrain| terrain.is_walkable())
                 .unwrap_or(false)
+                && !is_new(*tile)
         }) else {
             return false;
         };
 
-        let structures: HashSet<Tile> = self.structures().map(|(tile, _)| tile).collect();
+        let blocking: HashSet<Tile> = self
+            .structures()
+            .filter(|(_, entity)| !self.is_passable_structure(*entity))
+            .map(|(tile, _)| tile)
+            .chain(new_structure.into_iter().flat_map(Footprint::tiles))
+            .collect();
         let navigator = |tile| {
-            let structures = &structures;
+            let blocking = &blocking;
             self.edge_adjacent(tile)
-                .filter(move |_| !structures.contains(&tile))
+                .filter(move |_| !blocking.contains(&tile))
         };
-        find_all(start, navigator).is_superset(&structures)
+        let reachable = find_all(start, navigator);
+
+        let reached: HashSet<Entity> = self
+            .structures()
+            .filter(|(tile, _)| reachable.contains(tile))
+            .map(|(_, entity)| entity)
+            .collect();
+        self.structures()
+            .all(|(_, entity)| reached.contains(&entity))
+            && new_structure
+                .is_none_or(|footprint| footprint.tiles().any(|tile| reachable.contains(&tile)))
+    }
+
+    /// Every tile of the footprint is one of [`Game::find_tiles_that_can_be_built_on`] and it fits
+    /// the rules of the structure type.
+    /// - structures other than gates must not cut another structure off from the map border
+    /// - moats have to be dug next to water
+    /// - nothing is built over a trap
+    pub fn can_build(&self, footprint: Footprint, structure_type: StructureType) -> bool {
+        let sites = self.find_tiles_that_can_be_built_on();
+        if !footprint
+            .tiles()
+            .all(|tile| sites.contains(&tile) && !self.map.traps.contains_key(&tile))
+        {
+            return false;
+        }
+        if structure_type == StructureType::Moat
+            && !footprint.tiles().any(|tile| {
+                self.edge_adjacent(tile)
+                    .any(|adjacent| self.terrain(adjacent) == Some(Terrain::Water))
+            })
+        {
+            return false;
+        }
+        structure_type.is_passable() || self.all_structures_reachable_with(Some(footprint))
     }
 
     /// A tile can be built on if
     /// - it is walkable
     /// - it is not on the map perimeter
     /// - from the tile there exists a walkable path to every perimeter tile on the map
-    /// - it is within two tiles distance (by edge steps) of another structure
+    /// - it is within two tiles distance (by edge steps) of another non-defensive structure
     ///     (losing all buildings is defeat)
+    ///
+    /// Paths may lead through gates.
     pub fn find_tiles_that_can_be_built_on(&self) -> HashSet<Tile> {
         let structures: HashSet<Tile> = self.structures().map(|(tile, _)| tile).collect();
+        let buildings: HashSet<Tile> = self
+            .structures()
+            .filter(|(_, entity)| !self.is_defensive_structure(*entity))
+            .map(|(tile, _)| tile)
+            .collect();
+        let gates: HashSet<Tile> = self
+            .structures()
+            .filter(|(_, entity)| self.is_passable_structure(*entity))
+            .map(|(tile, _)| tile)
+            .collect();
 
-        let distance_map = distance_map(structures.iter().copied(), |t| {
+        let distance_map = distance_map(buildings.iter().copied(), |t| {
             t.edge_adjacent()
                 .into_iter()
                 .filter(|tile| self.contains(*tile))
@@ -181,21 +265,22 @@ impl Game<'_, '_> {
             })
             .collect();
 
-        // pick any structure, shouldn't matter which
-        let Some(start) = structures.iter().next().copied() else {
+        // pick any building, shouldn't matter which
+        let Some(start) = buildings.iter().next().copied() else {
             // no structures, empty return
             return HashSet::default();
         };
 
-        // navigator only enters candidates
+        // navigator only enters candidates and gates
         let navigator = |tile: Tile| {
             tile.edge_adjacent()
                 .into_iter()
-                .filter(|adj_tile| candidate_tiles.contains(adj_tile))
+                .filter(|adj_tile| candidate_tiles.contains(adj_tile) || gates.contains(adj_tile))
         };
 
         let mut reachable = find_all(start, navigator);
         reachable.remove(&start);
+        reachable.retain(|tile| !gates.contains(tile));
         reachable
     }
 
@@ -212,9 +297,9 @@ impl Game<'_, '_> {
 pub enum InsertedActor {
     /// The tile was empty and the actor did not already exist in the world map.
     Placed,
-    /// The actor already existed in the world map. Returns its previous tile.
+    /// The actor already existed in the world map. Returns its previous footprint.
     #[allow(unused)]
-    MovedFrom(Tile),
+    MovedFrom(Footprint),
     /// The actor replaced an existing tile. Returns the replaced actor.
     #[allow(unused)]
     ReplacedActor(Entity),
@@ -2,6 +2,8 @@ use bevy::prelude::*;
 
 use crate::path_finding::tiles::TileDir;
 
+use super::actors::status::{StatusEffect, StatusKind};
+
 pub const ITEM_TEMPLATES: &[Item] = &[
     Item {
         name: "axe",
@@ -10,8 +12,12 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         item_count: 2,
         directions: &TileDir::EDGES,
         range: 2,
+        ranged: false,
         cost: 10,
         air: false,
+        armour: 0,
+        status: None,
+        push: 0,
     },
     Item {
         name: "dagger",
@@ -20,8 +26,12 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         item_count: 1,
         directions: &TileDir::EDGES,
         range: 1,
+        ranged: false,
         cost: 40,
         air: false,
+        armour: 0,
+        status: None,
+        push: 0,
     },
     Item {
         name: "sword",
@@ -30,8 +40,12 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         item_count: 1,
         directions: &TileDir::ALL,
         range: 2,
+        ranged: false,
         cost: 30,
         air: false,
+        armour: 0,
+        status: None,
+        push: 0,
     },
     Item {
         name: "whip",
@@ -40,8 +54,12 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         item_count: 1,
         directions: &TileDir::ALL,
         range: 3,
+        ranged: true,
         cost: 20,
         air: true,
+        armour: 0,
+        status: None,
+        push: 0,
     },
     Item {
         name: "bow",
@@ -50,8 +68,12 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         item_count: 2,
         directions: &TileDir::ALL,
         range: 3,
+        ranged: true,
         cost: 30,
         air: true,
+        armour: 0,
+        status: None,
+        push: 0,
     },
     Item {
         name: "health_potion",
@@ -60,11 +82,65 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         item_count: 1,
         directions: &TileDir::ALL,
         range: 2,
+        ranged: false,
         cost: 20,
         air: false,
+        armour: 0,
+        status: None,
+        push: 0,
+    },
+    Item {
+        name: "mace",
+        description: "Mace, close range weapon that knocks its target back a tile and stuns it for a turn. (Land only)",
+        health_effect: -1,
+        item_count: 1,
+        directions: &TileDir::EDGES,
+        range: 1,
+        ranged: false,
+        cost: 30,
+        air: false,
+        armour: 0,
+        status: Some(StatusEffect::new(StatusKind::Webbed, 2)),
+        push: 1,
+    },
+    Item {
+        name: "bandage",
+        description: "Bandage, braces an ally to block the next attack against them.",
+        health_effect: 0,
+        item_count: 2,
+        directions: &TileDir::ALL,
+        range: 1,
+        ranged: false,
+        cost: 15,
+        air: false,
+        armour: 0,
+        status: Some(StatusEffect::new(StatusKind::Block, 2)),
+        push: 0,
+    },
+    Item {
+        name: "strength_potion",
+        description: "Strength potion, hardens an ally's skin giving 1 armour.",
+        health_effect: 0,
+        item_count: 1,
+        directions: &TileDir::ALL,
+        range: 2,
+        ranged: false,
+        cost: 25,
+        air: false,
+        armour: 1,
+        status: None,
+        push: 0,
     },
 ];
 
+/// Look up an item template by name.
+pub fn find_item_template(name: &str) -> Option<Item> {
+    ITEM_TEMPLATES
+        .iter()
+        .find(|item| item.name == name)
+        .copied()
+}
+
 /// Maximum number of the items a character can
 #[derive(Component, Debug)]
 pub struct MaxInventorySize(pub u32);
@@ -76,7 +152,7 @@ impl Default for MaxInventorySize {
 }
 
 /// List of an entity's equipped items
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Inventory {
     pub selected_item: Option<usize>,
     item_slots: Vec<Option<Item>>,
@@ -92,6 +168,17 @@ impl Default for Inventory {
 }
 
 impl Inventory {
+    pub fn from_slots(item_slots: Vec<Option<Item>>) -> Self {
+        Self {
+            selected_item: None,
+            item_slots,
+        }
+    }
+
+    pub fn slots(&self) -> impl Iterator<Item = Option<Item>> + '_ {
+        self.item_slots.iter().copied()
+    }
+
     pub fn slot_count(&self) -> usize {
         self.item_slots.len()
     }
@@ -161,7 +248,15 @@ pub struct Item {
     pub item_count: u32,
     pub directions: &'static [TileDir],
     pub range: u32,
+    /// Shots need a clear line of sight, other items reach past actors in the way.
+    pub ranged: bool,
     /// Cost of the item in gold coins
     pub cost: u32,
     pub air: bool,
+    /// Armour points given to the target.
+    pub armour: u32,
+    /// Status effect inflicted on the target.
+    pub status: Option<StatusEffect>,
+    /// Tiles the target is knocked back, away from the user.
+    pub push: u32,
 }
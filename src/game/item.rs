@@ -1,5 +1,4 @@
-use bevy::{color::palettes::css, prelude::*};
-use bevy_trauma_shake::TraumaCommands;
+use bevy::{color::palettes::css, ecs::system::SystemParam, prelude::*, utils::HashSet};
 
 use crate::{
     game::{
@@ -9,16 +8,28 @@ use crate::{
         },
         tile_set::{tile_coord_translation, TILE_ANCHOR},
     },
-    path_finding::{find_all_within_distance_unweighted, tiles::Tile},
+    path_finding::{
+        find_all_within_distance_unweighted, line_of_sight::has_line_of_sight, tiles::Tile,
+    },
     screen::{playing::GameState, Screen},
     ui::icon_set::IconSet,
 };
 
 use super::{
-    actors::{stats::Health, ActorTurnState, EnemyActor},
+    actors::{
+        damage::{DamageEvent, DamageSystems, DamageType, HealEvent},
+        push::{push_actors, push_direction, PushEvent},
+        stats::{Armour, Health},
+        status::{inflict_status, InflictStatus},
+        ActorTurnState, EnemyActor, IsAirborne,
+    },
+    actors_list::PlayerActorList,
+    components::Abilities,
     inventory::{Inventory, Item},
     map::VillageMap,
+    replay::{CommandLog, GameCommand},
     selection::{self, SelectedActor, SelectedTiles, SelectionEvent},
+    undo::HeroActed,
 };
 
 pub struct ItemPlugin;
@@ -29,7 +40,11 @@ impl Plugin for ItemPlugin {
             Update,
             (
                 show_attack_range,
-                apply_item_effect.after(selection::set_selected_unit),
+                apply_item_effect
+                    .after(selection::set_selected_unit)
+                    .before(DamageSystems::Collect)
+                    .before(push_actors)
+                    .before(inflict_status),
                 deselect_inventory_on_click,
             )
                 .chain()
@@ -44,28 +59,55 @@ pub struct InventorySelection {
     pub tile: Tile,
 }
 
+/// Tiles the item can be used on from `tile`.
+///
+/// [`Item::ranged`] items need a clear line of sight and only items that reach the air can hit
+/// airborne actors.
+pub fn item_target_tiles(
+    item: &Item,
+    tile: Tile,
+    village_map: &VillageMap,
+    is_airborne: impl Fn(Entity) -> bool,
+) -> HashSet<Tile> {
+    find_all_within_distance_unweighted(tile, item.range, |t| {
+        item.directions.iter().copied().map(move |d| t.step(d))
+    })
+    .into_iter()
+    .filter(|target| {
+        (!item.ranged || has_line_of_sight(tile, *target, |t| village_map.actors.is_occupied(t)))
+            && (item.air
+                || village_map
+                    .actors
+                    .get(*target)
+                    .is_none_or(|entity| !is_airborne(entity)))
+    })
+    .collect()
+}
+
 fn show_attack_range(
-    q_inventories: Query<(Entity, &Inventory, &ActorTurnState), Changed<Inventory>>,
+    q_inventories: Query<(Entity, &Inventory, &ActorTurnState, &Abilities), Changed<Inventory>>,
+    q_airborne: Query<(), With<IsAirborne>>,
     mut selection_tiles: ResMut<SelectedTiles>,
     village_map: Res<VillageMap>,
     mut inventory_selection: ResMut<InventorySelection>,
 ) {
-    for (entity, inventory, turn_state) in q_inventories.iter() {
-        if turn_state.used_action {
+    for (entity, inventory, turn_state, abilities) in q_inventories.iter() {
+        if !turn_state.can_act(abilities) {
             continue;
         }
 
         if let Some(item) = inventory.selected_item.and_then(|i| inventory.get(i)) {
-            let Some(tile) = village_map.actors.locate(entity) else {
+            let Some(tile) = village_map
+                .actors
+                .locate(entity)
+                .map(|footprint| footprint.origin)
+            else {
                 continue;
             };
 
-            let possible_action_tiles =
-                find_all_within_distance_unweighted(tile, item.range, |t| {
-                    item.directions.iter().copied().map(move |d| t.step(d))
-                });
-
-            selection_tiles.tiles = possible_action_tiles;
+            selection_tiles.tiles = item_target_tiles(&item, tile, &village_map, |entity| {
+                q_airborne.contains(entity)
+            });
             selection_tiles.color = css::ORANGE.into();
 
             *inventory_selection = InventorySelection {
@@ -86,17 +128,41 @@ fn deselect_inventory_on_click(
     }
 }
 
-fn apply_item_effect(
+/// Read-only lookups of [`apply_item_effect`].
+#[derive(SystemParam)]
+pub struct ItemTargeting<'w, 's> {
+    q_enemy_units: Query<'w, 's, (), With<EnemyActor>>,
+    q_airborne: Query<'w, 's, Has<IsAirborne>>,
+    village_map: Res<'w, VillageMap>,
+    icon_set: Res<'w, IconSet>,
+    selected_unit: Res<'w, SelectedActor>,
+    inventory_selection: Res<'w, InventorySelection>,
+    player_actor_list: Res<'w, PlayerActorList>,
+}
+
+pub fn apply_item_effect(
     mut commands: Commands,
-    mut q_inventories: Query<(&mut Inventory, &mut ActorTurnState)>,
-    mut q_healths: Query<&mut Health>,
-    q_enemy_units: Query<(), With<EnemyActor>>,
-    mut village_map: ResMut<VillageMap>,
-    icon_set: Res<IconSet>,
-    selected_unit: Res<SelectedActor>,
-    inventory_selection: Res<InventorySelection>,
+    mut q_inventories: Query<(&mut Inventory, &mut ActorTurnState, &Abilities)>,
+    mut q_healths: Query<Option<&mut Armour>, With<Health>>,
+    targeting: ItemTargeting,
     mut selection_events: EventReader<SelectionEvent>,
+    mut command_log: ResMut<CommandLog>,
+    mut hero_acted_evt: EventWriter<HeroActed>,
+    mut inflict_status_evt: EventWriter<InflictStatus>,
+    mut damage_evt: EventWriter<DamageEvent>,
+    mut heal_evt: EventWriter<HealEvent>,
+    mut push_evt: EventWriter<PushEvent>,
 ) {
+    let ItemTargeting {
+        q_enemy_units,
+        q_airborne,
+        village_map,
+        icon_set,
+        selected_unit,
+        inventory_selection,
+        player_actor_list,
+    } = targeting;
+
     if selection_events.is_empty() {
         return;
     } else {
@@ -109,43 +175,48 @@ fn apply_item_effect(
     let Some(target_entity) = selected_unit.entity else {
         return;
     };
-    let Some(target_tile) = village_map.actors.locate(target_entity) else {
+    let Some(target_footprint) = village_map.actors.locate(target_entity) else {
         return;
     };
-    let Ok((mut inventory, mut turn_state)) = q_inventories.get_mut(origin_entity) else {
+    let Ok((mut inventory, mut turn_state, abilities)) = q_inventories.get_mut(origin_entity)
+    else {
         return;
     };
 
-    if turn_state.used_action {
+    if !turn_state.can_act(abilities) {
         return;
     }
 
+    let harmful_status = item.status.map(|status| status.kind.is_harmful());
+    let harmful = item.health_effect < 0 || harmful_status == Some(true);
+    let helpful = item.health_effect > 0 || 0 < item.armour || harmful_status == Some(false);
+
     // Cannot apply negative effect on player units
-    if q_enemy_units.contains(target_entity) == false && item.health_effect < 0 {
+    if q_enemy_units.contains(target_entity) == false && harmful {
         return;
     }
 
     // Cannot apply positive effect on enemy units
-    if q_enemy_units.contains(target_entity) && item.health_effect > 0 {
+    if q_enemy_units.contains(target_entity) && helpful {
         return;
     }
 
     println!("Using item: {}", item.name);
 
     let possible_action_tiles =
-        find_all_within_distance_unweighted(inventory_selection.tile, item.range, |t| {
-            item.directions.iter().copied().map(move |d| t.step(d))
+        item_target_tiles(&item, inventory_selection.tile, &village_map, |entity| {
+            q_airborne.get(entity).unwrap_or(false)
         });
 
-    if possible_action_tiles.contains(&target_tile) {
-        if let Ok(mut health) = q_healths.get_mut(target_entity) {
-            if item.health_effect > 0 {
-                health.value += item.health_effect as u32;
-            } else {
-                health.value = health
-                    .value
-                    .saturating_sub(item.health_effect.unsigned_abs());
+    // Structures covering several tiles are hit on the first of their tiles in reach.
+    let target_tile = target_footprint
+        .tiles()
+        .find(|tile| possible_action_tiles.contains(tile))
+        .unwrap_or(target_footprint.origin);
 
+    if possible_action_tiles.contains(&target_tile) {
+        if let Ok(armour) = q_healths.get_mut(target_entity) {
+            if harmful {
                 let translation =
                     tile_coord_translation(target_tile.x() as f32, target_tile.y() as f32, 3.0);
                 commands.spawn(ClawMarkBundle {
@@ -160,10 +231,40 @@ fn apply_item_effect(
                     despawn_anim: DespawnAnimation::new(translation)
                         .with_extra_progress(CLAW_ANIM_DURATAION),
                 });
-                commands.add_trauma(0.5);
-
-                if health.value == 0 {
-                    village_map.actors.remove_entity(target_entity);
+                damage_evt.send(
+                    DamageEvent::new(
+                        Some(origin_entity),
+                        target_entity,
+                        item.health_effect.min(0).unsigned_abs(),
+                        DamageType::Physical,
+                    )
+                    .with_status(item.status),
+                );
+                let direction = push_direction(inventory_selection.tile, target_tile);
+                if let (true, Some(direction)) = (0 < item.push, direction) {
+                    push_evt.send(PushEvent {
+                        source: Some(origin_entity),
+                        target: target_entity,
+                        direction,
+                        distance: item.push,
+                    });
+                }
+            } else {
+                if let Some(status) = item.status {
+                    inflict_status_evt.send(InflictStatus {
+                        target: target_entity,
+                        effect: status,
+                    });
+                }
+                if 0 < item.health_effect {
+                    heal_evt.send(HealEvent {
+                        source: Some(origin_entity),
+                        target: target_entity,
+                        amount: item.health_effect as u32,
+                    });
+                }
+                if let Some(mut armour) = armour {
+                    armour.0 += item.armour;
                 }
             }
 
@@ -176,7 +277,41 @@ fn apply_item_effect(
                 inventory.set(index, item);
             }
 
-            turn_state.used_action = true;
+            turn_state.acted();
+            hero_acted_evt.send(HeroActed);
+            command_log.record(match player_actor_list.index_of(origin_entity) {
+                Some(hero) => GameCommand::UseItem {
+                    hero,
+                    slot: index,
+                    target: target_tile,
+                },
+                None => GameCommand::TowerShot {
+                    tower: inventory_selection.tile,
+                    target: target_tile,
+                },
+            });
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::inventory::find_item_template;
+    use crate::path_finding::tiles::TileDim;
+
+    #[test]
+    fn test_only_ranged_items_need_line_of_sight() {
+        let mut village_map = VillageMap::new(TileDim(5, 1));
+        village_map.actors.set(Tile(1, 0), Entity::from_raw(1));
+        village_map.actors.set(Tile(2, 0), Entity::from_raw(2));
+        let target_tiles = |name| {
+            let item = find_item_template(name).unwrap();
+            item_target_tiles(&item, Tile(0, 0), &village_map, |_| false)
+        };
+        // The potion reaches the hero standing behind another hero.
+        assert!(target_tiles("health_potion").contains(&Tile(2, 0)));
+        assert!(!target_tiles("bow").contains(&Tile(2, 0)));
+        assert!(target_tiles("bow").contains(&Tile(1, 0)));
+    }
+}
@@ -2,33 +2,111 @@
 
 use bevy::color::palettes::css::YELLOW;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::game::actors::spawn::SpawnAnimation;
-use crate::game::actors::StructureBundle;
-use crate::path_finding::tiles::{Tile, TileDim};
+use crate::path_finding::tiles::{Tile, TileDim, TileRect};
 use crate::{screen::Screen, VillageCamera};
 
-use super::actors::EnemyActor;
-use super::{picking::PickableTile, selection::SelectionMap};
+use super::construction::{spawn_structure, StructureType};
+use super::resources::{VillageGold, VillagePopulation};
+use super::save::PendingSave;
+use super::selection::SelectionMap;
+use super::victory::VictoryConditions;
 
-use self::level_asset::{LevelAsset, LevelAssetPlugin, Levels};
+use self::generator::{generate_level, random_level_seed, GeneratorSettings};
+use self::level_asset::{LevelAsset, LevelAssetPlugin, LevelLoad, Levels, SpawnZone};
 
 use super::{
     map::VillageMap,
     tile_set::{tile_coord_translation, TileSet, TILE_ANCHOR, TILE_HALF_HEIGHT},
 };
 
+pub mod generator;
 pub mod level_asset;
+pub mod tiled;
 
 pub struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(LevelAssetPlugin)
-            .add_systems(OnEnter(Screen::Playing), load_level);
+            .init_resource::<SelectedLevel>()
+            .init_resource::<UnlockedLevels>()
+            .add_systems(
+                OnEnter(Screen::Playing),
+                (generate_selected_level, load_level).chain(),
+            );
     }
 }
 
+/// Name of the level to play, the first level is played if none is selected.
+///
+/// Names made by [`random_level_name`](generator::random_level_name) play a generated village.
+#[derive(Resource, Default, Debug, Clone, PartialEq, Eq)]
+pub struct SelectedLevel(pub Option<String>);
+
+/// Number of [`Levels`] the player can choose from, in unlock order.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnlockedLevels(pub usize);
+
+impl Default for UnlockedLevels {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl UnlockedLevels {
+    /// Unlock the level that follows the won one.
+    pub fn unlock_after(&mut self, levels: &Levels, selected_level: &SelectedLevel) {
+        let Some(won) = levels.selected(selected_level) else {
+            return;
+        };
+        // Generated villages are not part of the unlock order.
+        if won.seed.is_some() {
+            return;
+        }
+        if let Some(index) = levels.0.iter().position(|level| level.name == won.name) {
+            self.0 = self.0.max(index + 2);
+        }
+    }
+}
+
+/// Zones of the level being played, see [`LevelAsset`].
+#[derive(Resource, Debug, Clone)]
+pub struct LevelZones {
+    /// Heroes can only be deployed inside this rect.
+    pub deployment: TileRect,
+    /// Enemies spawn in a random tile of a random zone.
+    pub spawn: Vec<SpawnZone>,
+}
+
+/// Generate the selected level if it is a random village that does not exist yet.
+pub fn generate_selected_level(
+    mut levels: ResMut<Levels>,
+    mut level_assets: ResMut<Assets<LevelAsset>>,
+    selected_level: Res<SelectedLevel>,
+) {
+    let Some(name) = &selected_level.0 else {
+        return;
+    };
+    let Some(seed) = random_level_seed(name) else {
+        return;
+    };
+    if levels.get(name).is_some() {
+        return;
+    }
+
+    info!("Generating random village with seed {seed}");
+    let level_asset = generate_level(&GeneratorSettings::random(seed), seed);
+    levels.0.push(LevelLoad {
+        name: name.clone(),
+        display_name: "Random village".to_string(),
+        difficulty: 1,
+        handle: level_assets.add(level_asset),
+        seed: Some(seed),
+    });
+}
+
 /// Marker component for a sprite that shows a line around the edges of a tile.
 #[derive(Component)]
 pub struct TileBorder;
@@ -40,59 +118,61 @@ pub struct TileThickBorder;
 pub fn load_level(
     mut commands: Commands,
     mut village_camera_query: Query<&mut Transform, With<VillageCamera>>,
-    enemies_query: Query<(), With<EnemyActor>>,
-    mut levels: ResMut<Levels>,
+    levels: Res<Levels>,
+    selected_level: Res<SelectedLevel>,
     level_assets: Res<Assets<LevelAsset>>,
     tile_set: Res<TileSet>,
+    pending_save: Option<Res<PendingSave>>,
+    mut gold: ResMut<VillageGold>,
+    mut population: ResMut<VillagePopulation>,
 ) {
-    // Choose a random level
-    let level_index = rand::random::<usize>() % levels.0.len();
-    let level = &mut levels.0[level_index];
+    let Some(level) = levels.selected(&selected_level) else {
+        error!("Unable to find level: {:?}", selected_level.0);
+        return;
+    };
 
     let Some(level_asset) = level_assets.get(&level.handle) else {
         error!("Unable to load level: {}", level.name);
         return;
     };
 
-    let mut selection_map = SelectionMap::default();
-    let mut village_map = VillageMap::new(TileDim::splat(level_asset.size as i32));
+    gold.0 = level_asset.starting_gold;
+    population.0 = level_asset.starting_population;
+    commands.insert_resource(LevelZones {
+        deployment: level_asset.deployment_zone(),
+        spawn: level_asset.spawn_zones(),
+    });
+    commands.insert_resource(VictoryConditions(level_asset.victory.clone()));
 
-    let camera_translation = Vec3::new(
-        0.0,
-        -TILE_HALF_HEIGHT * level_asset.size as f32 + TILE_HALF_HEIGHT,
-        0.0,
-    );
+    // A saved village keeps its own terrain and structures, only the level's tiles are reused.
+    let saved_game = pending_save.as_ref().and_then(|save| save.0.as_ref());
+    let size = saved_game.map_or(level_asset.dim(), |save| save.size);
+
+    let mut selection_map = SelectionMap::default();
+    let mut village_map = VillageMap::new(size);
 
     for mut transform in village_camera_query.iter_mut() {
-        transform.translation = camera_translation;
+        transform.translation = village_camera_translation(size);
     }
 
-    for y in 0..level_asset.size {
-        for x in 0..level_asset.size {
-            let index = x + y * level_asset.size;
+    for yi in 0..size.y() {
+        for xi in 0..size.x() {
+            let (xf, yf) = (xi as f32, yi as f32);
 
-            let ground_tile_name = &level_asset.tiles[0][index];
-            let object_tile_name = &level_asset.tiles[1][index];
+            let edge_translation = tile_coord_translation(xf, yf, 1.0);
 
-            let (xf, yf) = (x as f32, y as f32);
+            let object_tile_name = if let Some(save) = saved_game {
+                let terrain = save.terrain_at(Tile(xi, yi)).unwrap_or_default();
+                village_map.set_terrain(Tile(xi, yi), terrain);
+                None
+            } else {
+                // Unknown tiles are rejected by the level loader.
+                let terrain = level_asset.terrain_at(Tile(xi, yi)).unwrap_or_default();
+                village_map.set_terrain(Tile(xi, yi), terrain);
 
-            let edge_translation = tile_coord_translation(xf, yf, 1.0);
-            let object_translation = tile_coord_translation(xf, yf, 2.0);
-
-            let (xi, yi) = (x as i32, y as i32);
-
-            let terrain = match ground_tile_name.as_str() {
-                "grassblock" => Terrain::Grass,
-                "gravelblock" => Terrain::Gravel,
-                "waterblock" => Terrain::Water,
-                _ => {
-                    warn!("Spawning unknown tile: {}", ground_tile_name);
-                    Terrain::Gravel
-                }
+                level_asset.structure_at(Tile(xi, yi))
             };
 
-            village_map.set_terrain(Tile(xi, yi), terrain);
-
             // Border
             let id = commands
                 .spawn((
@@ -140,34 +220,34 @@ pub fn load_level(
                 .id();
             selection_map.thick_borders.insert(Tile(xi, yi), id);
 
-            if object_tile_name != "empty" {
-                let object_entity = commands.spawn((
-                    SpriteBundle {
-                        sprite: Sprite {
-                            anchor: TILE_ANCHOR,
-                            ..Default::default()
-                        },
-                        texture: tile_set.get(object_tile_name),
-                        ..default()
-                    },
-                    PickableTile,
-                    StateScoped(Screen::Playing),
-                    // IMPORTANT: There must only be structure in the map asset
-                    StructureBundle::default(),
-                    SpawnAnimation::new(object_translation),
-                ));
-
-                village_map.actors.set(Tile(xi, yi), object_entity.id());
+            // Level validation only lets structures into the object layer.
+            if let Some(structure_type) =
+                object_tile_name.and_then(StructureType::from_tile_texture)
+            {
+                // Validation also keeps their footprints apart and on the map.
+                let footprint = structure_type.footprint(Tile(xi, yi), false);
+                let id = spawn_structure(&mut commands, &tile_set, footprint, structure_type);
+                village_map.actors.place(footprint, id);
             }
         }
     }
 
-    village_map.generate_heat_map(|e| enemies_query.contains(e));
     commands.insert_resource(village_map);
     commands.insert_resource(selection_map)
 }
 
-#[derive(Component, Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Camera position that shows a whole map of this size.
+pub fn village_camera_translation(size: TileDim) -> Vec3 {
+    Vec3::new(
+        0.0,
+        -TILE_HALF_HEIGHT * size.y() as f32 + TILE_HALF_HEIGHT,
+        0.0,
+    )
+}
+
+#[derive(
+    Component, Reflect, Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
 pub enum Terrain {
     #[default]
     /// Tile is grassland.
@@ -179,6 +259,25 @@ pub enum Terrain {
 }
 
 impl Terrain {
+    /// Name of the ground layer tile in the tile set.
+    pub fn tile_name(self) -> &'static str {
+        match self {
+            Terrain::Grass => "grassblock",
+            Terrain::Gravel => "gravelblock",
+            Terrain::Water => "waterblock",
+        }
+    }
+
+    /// Terrain of a ground layer tile in the tile set.
+    pub fn from_tile_name(name: &str) -> Option<Self> {
+        match name {
+            "grassblock" => Some(Terrain::Grass),
+            "gravelblock" => Some(Terrain::Gravel),
+            "waterblock" => Some(Terrain::Water),
+            _ => None,
+        }
+    }
+
     pub fn is_walkable(self) -> bool {
         match self {
             Terrain::Grass => true,
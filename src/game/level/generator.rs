@@ -0,0 +1,297 @@
+//! Seeded generator for random villages.
+//!
+//! The same [`GeneratorSettings`] and seed always give the same [`LevelAsset`].
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::game::construction::StructureType;
+use crate::path_finding::tiles::{Tile, TileDim};
+
+use super::level_asset::LevelAsset;
+use super::Terrain;
+
+/// Generated levels are named with this prefix followed by their seed.
+pub const RANDOM_LEVEL_PREFIX: &str = "random_";
+
+/// Maximum number of tiles tried for every house.
+const HOUSE_TRIALS: usize = 50;
+
+pub fn random_level_name(seed: u64) -> String {
+    format!("{RANDOM_LEVEL_PREFIX}{seed}")
+}
+
+/// Seed of a level named by [`random_level_name`].
+pub fn random_level_seed(name: &str) -> Option<u64> {
+    name.strip_prefix(RANDOM_LEVEL_PREFIX)?.parse().ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaterShape {
+    None,
+    /// A river crossing the map from one side to the opposite side.
+    River,
+    /// Round lakes away from the village center.
+    Lakes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeneratorSettings {
+    pub size: TileDim,
+    /// Share of the map covered by water, at most [`GeneratorSettings::MAX_WATER_RATIO`].
+    pub water_ratio: f32,
+    pub water_shape: WaterShape,
+    /// Number of gravel roads crossing the map.
+    pub roads: u32,
+    pub houses: u32,
+}
+
+impl GeneratorSettings {
+    pub const MAX_WATER_RATIO: f32 = 0.4;
+
+    /// Pick settings for a random village, the seed decides the map shape.
+    pub fn random(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        Self {
+            size: TileDim(rng.gen_range(9..=13), rng.gen_range(9..=13)),
+            water_ratio: rng.gen_range(0.0..0.2),
+            water_shape: match rng.gen_range(0..3) {
+                0 => WaterShape::None,
+                1 => WaterShape::River,
+                _ => WaterShape::Lakes,
+            },
+            roads: rng.gen_range(1..=2),
+            houses: rng.gen_range(3..=5),
+        }
+    }
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            size: TileDim(10, 10),
+            water_ratio: 0.1,
+            water_shape: WaterShape::River,
+            roads: 1,
+            houses: 4,
+        }
+    }
+}
+
+/// Generate a level where every structure can be reached from the map border.
+///
+/// At least one house is always placed, fewer than `settings.houses` are placed
+/// only if the map has no room left for them.
+pub fn generate_level(settings: &GeneratorSettings, seed: u64) -> LevelAsset {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let size = TileDim(settings.size.x().max(5), settings.size.y().max(5));
+    let mut level = LevelAsset::new(random_level_name(seed), size);
+
+    let area = (size.x() * size.y()) as f32;
+    let water_tiles = (area
+        * settings
+            .water_ratio
+            .clamp(0.0, GeneratorSettings::MAX_WATER_RATIO)) as usize;
+    match settings.water_shape {
+        WaterShape::None => {}
+        WaterShape::River => add_river(&mut level, &mut rng, water_tiles),
+        WaterShape::Lakes => add_lakes(&mut level, &mut rng, water_tiles),
+    }
+
+    for _ in 0..settings.roads {
+        add_road(&mut level, &mut rng);
+    }
+
+    add_houses(&mut level, &mut rng, settings.houses.max(1));
+
+    // Make room for a house in the center with a road leading to it.
+    if level
+        .bounds()
+        .into_iter()
+        .all(|tile| level.structure_at(tile).is_none())
+    {
+        let center = Tile(size.x() / 2, size.y() / 2);
+        for x in 0..center.x() {
+            level.set_terrain(Tile(x, center.y()), Terrain::Gravel);
+        }
+        level.set_terrain(center, Terrain::Grass);
+        level.set_structure(center, Some(StructureType::House.tile_texture()));
+    }
+
+    level
+}
+
+fn count_water(level: &LevelAsset) -> usize {
+    level
+        .bounds()
+        .into_iter()
+        .filter(|tile| level.terrain_at(*tile) == Some(Terrain::Water))
+        .count()
+}
+
+/// A meandering river that is widened until it covers enough tiles.
+fn add_river(level: &mut LevelAsset, rng: &mut StdRng, water_tiles: usize) {
+    if water_tiles == 0 {
+        return;
+    }
+    let size = level.dim();
+    let vertical = rng.gen_bool(0.5);
+    let (length, across) = match vertical {
+        true => (size.y(), size.x()),
+        false => (size.x(), size.y()),
+    };
+    let width = (water_tiles as i32 / length).clamp(1, (across / 3).max(1));
+
+    // Keep the river off the village center.
+    let mut position = match rng.gen_bool(0.5) {
+        true => rng.gen_range(1..=(across / 4).max(1)),
+        false => rng.gen_range((across - across / 4 - width).max(1)..across - width),
+    };
+    for step in 0..length {
+        for offset in 0..width {
+            let tile = match vertical {
+                true => Tile(position + offset, step),
+                false => Tile(step, position + offset),
+            };
+            level.set_terrain(tile, Terrain::Water);
+        }
+        position = (position + rng.gen_range(-1..=1)).clamp(0, across - width);
+    }
+}
+
+fn add_lakes(level: &mut LevelAsset, rng: &mut StdRng, water_tiles: usize) {
+    let size = level.dim();
+    let center = Vec2::new(size.x() as f32, size.y() as f32) / 2.;
+    let mut remaining = water_tiles;
+    for _ in 0..4 {
+        if remaining == 0 {
+            break;
+        }
+        let lake = Tile(rng.gen_range(0..size.x()), rng.gen_range(0..size.y()));
+        // Lakes near the village center are moved to the border.
+        let lake = if lake.to_ivec2().as_vec2().distance(center) < 2.5 {
+            Tile(lake.x(), 0)
+        } else {
+            lake
+        };
+        let before = count_water(level);
+        let radius = (remaining as f32 / std::f32::consts::PI).sqrt();
+        for tile in level.bounds() {
+            if tile
+                .to_ivec2()
+                .as_vec2()
+                .distance(lake.to_ivec2().as_vec2())
+                <= radius
+            {
+                level.set_terrain(tile, Terrain::Water);
+            }
+        }
+        remaining = remaining.saturating_sub(count_water(level) - before);
+    }
+}
+
+/// A straight gravel road through the village center, fording any water.
+fn add_road(level: &mut LevelAsset, rng: &mut StdRng) {
+    let size = level.dim();
+    if rng.gen_bool(0.5) {
+        let x = size.x() / 2 + rng.gen_range(-1..=1);
+        for y in 0..size.y() {
+            level.set_terrain(Tile(x, y), Terrain::Gravel);
+        }
+    } else {
+        let y = size.y() / 2 + rng.gen_range(-1..=1);
+        for x in 0..size.x() {
+            level.set_terrain(Tile(x, y), Terrain::Gravel);
+        }
+    }
+}
+
+/// Place houses close to each other around the center, keeping them all reachable.
+fn add_houses(level: &mut LevelAsset, rng: &mut StdRng, houses: u32) {
+    let size = level.dim();
+    let center = Tile(size.x() / 2, size.y() / 2);
+    let house = StructureType::House.tile_texture();
+    let mut placed: Vec<Tile> = Vec::new();
+
+    for _ in 0..houses {
+        for _ in 0..HOUSE_TRIALS {
+            let origin = placed.last().copied().unwrap_or(center);
+            let tile = Tile(
+                origin.x() + rng.gen_range(-2..=2),
+                origin.y() + rng.gen_range(-2..=2),
+            );
+            let on_border = tile.x() <= 0
+                || tile.y() <= 0
+                || size.x() - 1 <= tile.x()
+                || size.y() - 1 <= tile.y();
+            if on_border
+                || level.structure_at(tile).is_some()
+                || level
+                    .terrain_at(tile)
+                    .is_none_or(|terrain| terrain != Terrain::Grass)
+            {
+                continue;
+            }
+
+            level.set_structure(tile, Some(house));
+            if level.validate().is_ok() {
+                placed.push(tile);
+                break;
+            }
+            level.set_structure(tile, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_level() {
+        let settings = GeneratorSettings::default();
+        let a = generate_level(&settings, 7);
+        let b = generate_level(&settings, 7);
+        assert_eq!(a.tiles, b.tiles);
+        assert_eq!(a.name, "random_7");
+    }
+
+    #[test]
+    fn test_random_level_name_round_trip() {
+        assert_eq!(random_level_seed(&random_level_name(42)), Some(42));
+        assert_eq!(random_level_seed("debug_level"), None);
+    }
+
+    #[test]
+    fn test_generated_levels_are_valid() {
+        for seed in 0..200 {
+            let level = generate_level(&GeneratorSettings::random(seed), seed);
+            assert!(
+                level.validate().is_ok(),
+                "seed {seed}: {:?}",
+                level.validate()
+            );
+            assert!(
+                level
+                    .bounds()
+                    .into_iter()
+                    .any(|tile| level.structure_at(tile).is_some()),
+                "seed {seed} has no houses"
+            );
+        }
+    }
+
+    #[test]
+    fn test_water_ratio() {
+        let settings = GeneratorSettings {
+            water_ratio: 0.3,
+            water_shape: WaterShape::Lakes,
+            roads: 0,
+            ..default()
+        };
+        let level = generate_level(&settings, 3);
+        let water = count_water(&level) as f32 / 100.;
+        assert!((0.15..=0.45).contains(&water), "water ratio {water}");
+    }
+}
@@ -1,42 +1,491 @@
 use bevy::{
     asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
     prelude::*,
+    utils::HashSet,
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::game::actors::enemy::ENEMY_SPAWN_RANGE;
+use crate::game::constants::{INITIAL_GOLD, INITIAL_POPULATION};
+use crate::game::construction::StructureType;
+use crate::game::inventory::find_item_template;
+use crate::game::map::Footprint;
+use crate::game::victory::VictoryCondition;
+use crate::path_finding::find_all;
+use crate::path_finding::tiles::{Tile, TileDim, TileRect};
+
+use super::tiled::TiledLevelLoader;
+use super::{SelectedLevel, Terrain};
+
 pub struct LevelAssetPlugin;
 
 impl Plugin for LevelAssetPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<LevelAsset>()
+            .init_asset::<LevelManifest>()
             .init_asset_loader::<LevelAssetLoader>()
+            .init_asset_loader::<LevelManifestLoader>()
+            .init_asset_loader::<TiledLevelLoader>()
             .init_resource::<Levels>()
-            .add_systems(PreStartup, load_levels);
+            .add_systems(PreStartup, load_level_manifest)
+            .add_systems(PreUpdate, load_levels);
     }
 }
 
-/// Load levels from json file.
-fn load_levels(asset_sever: Res<AssetServer>, mut levels: ResMut<Levels>) {
-    const LEVELS: &[&str] = &["debug_level"];
+/// Path of the [`LevelManifest`] listing every level of the game.
+pub const LEVEL_MANIFEST_PATH: &str = "levels/index.json";
 
-    for &level in LEVELS {
-        info!("Loading level: {}", level);
+#[derive(Resource)]
+pub struct LevelManifestHandle(pub Handle<LevelManifest>);
 
-        levels.0.push(LevelLoad::new(
-            String::from(level),
-            asset_sever.load(format!("levels/{}.json", level)),
-        ));
+fn load_level_manifest(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(LevelManifestHandle(asset_server.load(LEVEL_MANIFEST_PATH)));
+}
+
+/// Load the levels listed in the manifest, again whenever the manifest changes.
+fn load_levels(
+    mut manifest_events: EventReader<AssetEvent<LevelManifest>>,
+    asset_server: Res<AssetServer>,
+    manifests: Res<Assets<LevelManifest>>,
+    manifest_handle: Res<LevelManifestHandle>,
+    mut levels: ResMut<Levels>,
+) {
+    let manifest_changed = manifest_events.read().any(|event| {
+        matches!(
+            event,
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }
+                if *id == manifest_handle.0.id()
+        )
+    });
+    if manifest_changed == false {
+        return;
+    }
+    let Some(manifest) = manifests.get(&manifest_handle.0) else {
+        return;
+    };
+
+    let mut entries = manifest.levels.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.unlock_order);
+
+    levels.0.clear();
+    for entry in entries {
+        info!("Loading level: {}", entry.name);
+
+        levels.0.push(LevelLoad {
+            name: entry.name.clone(),
+            display_name: entry.display_name.clone(),
+            difficulty: entry.difficulty,
+            handle: asset_server.load(format!("levels/{}", entry.file_name())),
+            seed: None,
+        });
     }
 }
 
-#[derive(Asset, TypePath, Serialize, Deserialize)]
+/// List of levels, see [`LEVEL_MANIFEST_PATH`].
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug)]
+pub struct LevelManifest {
+    pub levels: Vec<LevelManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelManifestEntry {
+    /// Unique name of the level, used by `--level` and saves.
+    pub name: String,
+    /// Name shown on the level select screen.
+    pub display_name: String,
+    pub difficulty: u32,
+    /// Levels unlock one after another in ascending order.
+    pub unlock_order: u32,
+    /// File inside the `levels` folder, `{name}.json` if not set.
+    /// Tiled maps (`.tmj`) are imported by [`TiledLevelLoader`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+impl LevelManifest {
+    /// List the level saved as `{name}.json`, new levels unlock after all others.
+    pub fn add_level(&mut self, name: &str) {
+        if let Some(entry) = self.levels.iter_mut().find(|entry| entry.name == name) {
+            // The saved json replaces the file the level was made from.
+            entry.file = None;
+            return;
+        }
+        let unlock_order = self
+            .levels
+            .iter()
+            .map(|entry| entry.unlock_order + 1)
+            .max()
+            .unwrap_or_default();
+        self.levels.push(LevelManifestEntry {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            difficulty: 1,
+            unlock_order,
+            file: None,
+        });
+    }
+}
+
+impl LevelManifestEntry {
+    pub fn file_name(&self) -> String {
+        self.file
+            .clone()
+            .unwrap_or_else(|| format!("{}.json", self.name))
+    }
+}
+
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Clone)]
 pub struct LevelAsset {
     pub name: String,
-    pub size: usize,
+    pub size: LevelSize,
+    /// Ground and object layers, tile names in row-major order.
     pub tiles: [Vec<String>; 2],
+    /// Enemies spawn in one of these zones, at the map border if empty.
+    #[serde(default)]
+    pub spawn_zones: Vec<SpawnZone>,
+    /// Heroes are deployed here, the map inset by [`DEPLOYMENT_INSET`] if not set.
+    #[serde(default)]
+    pub deployment_zone: Option<LevelRect>,
+    #[serde(default = "default_starting_gold")]
+    pub starting_gold: u32,
+    #[serde(default = "default_starting_population")]
+    pub starting_population: u32,
+    /// [`INITIAL_PLAYER_UNITS`](crate::game::actors::player::INITIAL_PLAYER_UNITS) default heroes are recruited if not set.
+    #[serde(default)]
+    pub starting_heroes: Option<Vec<StartingHero>>,
+    /// Meeting any of these wins the level, defeating the final boss if not set.
+    #[serde(default = "default_victory")]
+    pub victory: Vec<VictoryCondition>,
+}
+
+/// Distance of the default deployment zone from the map border.
+pub const DEPLOYMENT_INSET: i32 = 3;
+
+fn default_starting_gold() -> u32 {
+    INITIAL_GOLD
+}
+
+fn default_starting_population() -> u32 {
+    INITIAL_POPULATION
+}
+
+fn default_victory() -> Vec<VictoryCondition> {
+    vec![VictoryCondition::DefeatFinalBoss]
+}
+
+/// Either a single number for square maps or `{ "width": 12, "height": 8 }`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum LevelSize {
+    Square(u32),
+    Rectangle { width: u32, height: u32 },
+}
+
+impl LevelSize {
+    pub fn dim(self) -> TileDim {
+        match self {
+            LevelSize::Square(size) => TileDim::splat(size as i32),
+            LevelSize::Rectangle { width, height } => TileDim(width as i32, height as i32),
+        }
+    }
+}
+
+/// Inclusive rectangle of tiles.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelRect {
+    pub min: Tile,
+    pub max: Tile,
+}
+
+impl LevelRect {
+    pub fn tile_rect(self) -> TileRect {
+        TileRect(self.min, self.max)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SpawnZone {
+    pub name: String,
+    #[serde(flatten)]
+    pub area: LevelRect,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StartingHero {
+    /// Picked from the available names if not set.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default = "default_hero_stat")]
+    pub health: u32,
+    #[serde(default = "default_hero_stat")]
+    pub movement: u32,
+    /// Item template names, the default inventory is used if not set.
+    #[serde(default)]
+    pub items: Option<Vec<String>>,
+}
+
+fn default_hero_stat() -> u32 {
+    3
+}
+
+impl LevelAsset {
+    /// Grass level without structures, zones or starting heroes.
+    pub fn new(name: impl Into<String>, size: TileDim) -> Self {
+        let count = (size.x() * size.y()) as usize;
+        Self {
+            name: name.into(),
+            size: LevelSize::Rectangle {
+                width: size.x() as u32,
+                height: size.y() as u32,
+            },
+            tiles: [
+                vec![Terrain::Grass.tile_name().to_string(); count],
+                vec![EMPTY_TILE.to_string(); count],
+            ],
+            spawn_zones: Vec::new(),
+            deployment_zone: None,
+            starting_gold: INITIAL_GOLD,
+            starting_population: INITIAL_POPULATION,
+            starting_heroes: None,
+            victory: default_victory(),
+        }
+    }
+
+    pub fn dim(&self) -> TileDim {
+        self.size.dim()
+    }
+
+    pub fn bounds(&self) -> TileRect {
+        let dim = self.dim();
+        TileRect(Tile::ZERO, Tile(dim.x() - 1, dim.y() - 1))
+    }
+
+    fn index(&self, tile: Tile) -> Option<usize> {
+        self.bounds()
+            .contains(tile)
+            .then(|| (tile.x() + tile.y() * self.dim().x()) as usize)
+    }
+
+    pub fn terrain_at(&self, tile: Tile) -> Option<Terrain> {
+        self.index(tile)
+            .and_then(|index| self.tiles[0].get(index))
+            .and_then(|name| Terrain::from_tile_name(name))
+    }
+
+    /// Tile name of the structure standing on the tile.
+    pub fn structure_at(&self, tile: Tile) -> Option<&str> {
+        self.index(tile)
+            .and_then(|index| self.tiles[1].get(index))
+            .map(String::as_str)
+            .filter(|name| *name != EMPTY_TILE)
+    }
+
+    /// Tiles covered by each structure of the object layer, from the tile it is placed on.
+    pub fn structure_footprints(&self) -> impl Iterator<Item = (Footprint, StructureType)> + '_ {
+        self.bounds().into_iter().filter_map(|tile| {
+            self.structure_at(tile)
+                .and_then(StructureType::from_tile_texture)
+                .map(|structure_type| (structure_type.footprint(tile, false), structure_type))
+        })
+    }
+
+    pub fn set_terrain(&mut self, tile: Tile, terrain: Terrain) {
+        if let Some(index) = self.index(tile) {
+            self.tiles[0][index] = terrain.tile_name().to_string();
+        }
+    }
+
+    /// Place a structure by its tile name, or clear the tile.
+    pub fn set_structure(&mut self, tile: Tile, structure: Option<&str>) {
+        if let Some(index) = self.index(tile) {
+            self.tiles[1][index] = structure.unwrap_or(EMPTY_TILE).to_string();
+        }
+    }
+
+    /// Grow or shrink the level from its bottom right corner.
+    ///
+    /// New tiles are empty grass, zones that no longer fit are dropped.
+    pub fn resize(&mut self, size: TileDim) {
+        let mut resized = LevelAsset::new(self.name.clone(), size);
+        for tile in resized.bounds() {
+            if let Some(index) = self.index(tile) {
+                let resized_index = resized.index(tile).unwrap();
+                resized.tiles[0][resized_index].clone_from(&self.tiles[0][index]);
+                resized.tiles[1][resized_index].clone_from(&self.tiles[1][index]);
+            }
+        }
+
+        let bounds = resized.bounds();
+        let fits = |rect: &LevelRect| bounds.contains(rect.min) && bounds.contains(rect.max);
+        self.spawn_zones.retain(|zone| fits(&zone.area));
+        self.deployment_zone = self.deployment_zone.filter(fits);
+        self.size = resized.size;
+        self.tiles = resized.tiles;
+    }
+
+    pub fn deployment_zone(&self) -> TileRect {
+        match self.deployment_zone {
+            Some(zone) => zone.tile_rect(),
+            None => {
+                let max = self.bounds().max();
+                TileRect(
+                    Tile(DEPLOYMENT_INSET, DEPLOYMENT_INSET),
+                    Tile(max.x() - DEPLOYMENT_INSET, max.y() - DEPLOYMENT_INSET),
+                )
+            }
+        }
+    }
+
+    /// Named spawn zones, or the four map borders [`ENEMY_SPAWN_RANGE`] tiles deep.
+    pub fn spawn_zones(&self) -> Vec<SpawnZone> {
+        if self.spawn_zones.is_empty() == false {
+            return self.spawn_zones.clone();
+        }
+
+        let max = self.bounds().max();
+        let depth = ENEMY_SPAWN_RANGE as i32 - 1;
+        [
+            ("north", Tile(0, 0), Tile(max.x(), depth)),
+            ("east", Tile(max.x() - depth, 0), Tile(max.x(), max.y())),
+            ("south", Tile(0, max.y() - depth), Tile(max.x(), max.y())),
+            ("west", Tile(0, 0), Tile(depth, max.y())),
+        ]
+        .into_iter()
+        .map(|(name, min, max)| SpawnZone {
+            name: name.to_string(),
+            area: LevelRect { min, max },
+        })
+        .collect()
+    }
+
+    /// Check everything [`load_level`](super::load_level) relies on.
+    pub fn validate(&self) -> Result<(), LevelAssetLoaderError> {
+        let dim = self.dim();
+        if dim.x() <= 0 || dim.y() <= 0 {
+            return Err(LevelAssetLoaderError::EmptyLevel);
+        }
+
+        let expected = (dim.x() * dim.y()) as usize;
+        for (layer, tiles) in self.tiles.iter().enumerate() {
+            if tiles.len() != expected {
+                return Err(LevelAssetLoaderError::TileCount {
+                    layer,
+                    expected,
+                    found: tiles.len(),
+                });
+            }
+        }
+
+        for tile in self.bounds() {
+            let index = self.index(tile).unwrap();
+            let ground = &self.tiles[0][index];
+            if Terrain::from_tile_name(ground).is_none() {
+                return Err(LevelAssetLoaderError::UnknownTile {
+                    layer: 0,
+                    tile,
+                    name: ground.clone(),
+                });
+            }
+            let object = &self.tiles[1][index];
+            if object != EMPTY_TILE && StructureType::from_tile_texture(object).is_none() {
+                return Err(LevelAssetLoaderError::UnknownTile {
+                    layer: 1,
+                    tile,
+                    name: object.clone(),
+                });
+            }
+        }
+
+        let bounds = self.bounds();
+        let mut covered = HashSet::new();
+        for (footprint, _) in self.structure_footprints() {
+            if !footprint
+                .tiles()
+                .all(|tile| bounds.contains(tile) && covered.insert(tile))
+            {
+                return Err(LevelAssetLoaderError::OverlappingStructure(
+                    footprint.origin,
+                ));
+            }
+        }
+
+        let zones = self
+            .spawn_zones
+            .iter()
+            .map(|zone| (zone.name.as_str(), zone.area))
+            .chain(self.deployment_zone.map(|zone| ("deployment", zone)));
+        for (name, zone) in zones {
+            if !bounds.contains(zone.min) || !bounds.contains(zone.max) {
+                return Err(LevelAssetLoaderError::ZoneOutOfBounds(name.to_string()));
+            }
+        }
+
+        for hero in self.starting_heroes.iter().flatten() {
+            for item in hero.items.iter().flatten() {
+                if find_item_template(item).is_none() {
+                    return Err(LevelAssetLoaderError::UnknownItem(item.clone()));
+                }
+            }
+        }
+
+        if let Some(tile) = self.find_unreachable_structure() {
+            return Err(LevelAssetLoaderError::UnreachableStructure(tile));
+        }
+
+        Ok(())
+    }
+
+    /// A structure is reachable if enemies can walk next to it from the map border.
+    fn find_unreachable_structure(&self) -> Option<Tile> {
+        let bounds = self.bounds();
+        let covered: HashSet<Tile> = self
+            .structure_footprints()
+            .flat_map(|(footprint, _)| footprint.tiles())
+            .collect();
+        let is_open = |tile: Tile| {
+            !covered.contains(&tile)
+                && self
+                    .terrain_at(tile)
+                    .is_some_and(|terrain| terrain.is_walkable())
+        };
+
+        let mut reached = HashSet::new();
+        let is_border = |tile: Tile| {
+            tile.edge_adjacent()
+                .iter()
+                .any(|adjacent| !bounds.contains(*adjacent))
+        };
+        for start in bounds
+            .into_iter()
+            .filter(|tile| is_border(*tile) && is_open(*tile))
+        {
+            if reached.contains(&start) {
+                continue;
+            }
+            reached.extend(find_all(start, |tile| {
+                tile.edge_adjacent()
+                    .into_iter()
+                    .filter(|tile| bounds.contains(*tile) && is_open(*tile))
+            }));
+        }
+
+        self.structure_footprints()
+            .map(|(footprint, _)| footprint)
+            .find(|footprint| {
+                !footprint.tiles().any(|tile| {
+                    tile.edge_adjacent()
+                        .iter()
+                        .any(|adjacent| reached.contains(adjacent))
+                })
+            })
+            .map(|footprint| footprint.origin)
+    }
 }
 
+/// Object layer name of a tile without a structure.
+pub const EMPTY_TILE: &str = "empty";
+
 #[derive(Default)]
 pub struct LevelAssetLoader;
 
@@ -55,12 +504,13 @@ impl AssetLoader for LevelAssetLoader {
         reader.read_to_end(&mut bytes).await?;
 
         let tile_map_asset = serde_json::from_slice::<LevelAsset>(&bytes)?;
+        tile_map_asset.validate()?;
 
         Ok(tile_map_asset)
     }
 
     fn extensions(&self) -> &[&str] {
-        &[".json"]
+        &["json"]
     }
 }
 
@@ -71,20 +521,249 @@ pub enum LevelAssetLoaderError {
     Io(#[from] std::io::Error),
     #[error("Could not deserialize using serde: {0}")]
     Serde(#[from] serde_json::Error),
+    #[error("Level has no tiles")]
+    EmptyLevel,
+    #[error("Layer {layer} has {found} tiles, expected {expected}")]
+    TileCount {
+        layer: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Unknown tile {name:?} on layer {layer} at {tile:?}")]
+    UnknownTile {
+        layer: usize,
+        tile: Tile,
+        name: String,
+    },
+    #[error("Zone {0:?} is outside of the map")]
+    ZoneOutOfBounds(String),
+    #[error("Unknown starting item: {0}")]
+    UnknownItem(String),
+    #[error("Structure at {0:?} cannot be reached from the map border")]
+    UnreachableStructure(Tile),
+    #[error("Structure at {0:?} overlaps another structure or leaves the map")]
+    OverlappingStructure(Tile),
 }
 
-/// Stores [`LevelAsset`] as well as their parent [`Entity`] if it is already spawned.
+#[derive(Default)]
+pub struct LevelManifestLoader;
+
+impl AssetLoader for LevelManifestLoader {
+    type Asset = LevelManifest;
+    type Settings = ();
+    type Error = LevelAssetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        Ok(serde_json::from_slice::<LevelManifest>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json"]
+    }
+}
+
+/// Stores [`LevelAsset`] together with its entry in the [`LevelManifest`].
 #[derive(Debug)]
 pub struct LevelLoad {
     pub name: String,
+    pub display_name: String,
+    pub difficulty: u32,
     pub handle: Handle<LevelAsset>,
+    /// Seed of a generated level, these are not part of the manifest.
+    pub seed: Option<u64>,
 }
 
-impl LevelLoad {
-    pub fn new(name: String, handle: Handle<LevelAsset>) -> Self {
-        Self { name, handle }
+/// Levels in unlock order.
+#[derive(Resource, Default, Debug)]
+pub struct Levels(pub Vec<LevelLoad>);
+
+impl Levels {
+    pub fn get(&self, name: &str) -> Option<&LevelLoad> {
+        self.0.iter().find(|level| level.name == name)
+    }
+
+    /// The level named by [`SelectedLevel`], or the first level if none is selected.
+    pub fn selected(&self, selected_level: &SelectedLevel) -> Option<&LevelLoad> {
+        match &selected_level.0 {
+            Some(name) => self.get(name),
+            None => self.0.first(),
+        }
+    }
+
+    pub fn all_loaded(&self, asset_server: &AssetServer) -> bool {
+        !self.0.is_empty()
+            && self
+                .0
+                .iter()
+                .filter(|level| level.seed.is_none())
+                .all(|level| asset_server.is_loaded_with_dependencies(&level.handle))
     }
 }
 
-#[derive(Resource, Default, Debug)]
-pub struct Levels(pub Vec<LevelLoad>);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3x3 level of grass with a house in the middle.
+    fn test_level() -> LevelAsset {
+        let mut objects = vec![EMPTY_TILE.to_string(); 9];
+        objects[4] = "house1".to_string();
+        LevelAsset {
+            name: "test".to_string(),
+            size: LevelSize::Square(3),
+            tiles: [vec!["grassblock".to_string(); 9], objects],
+            spawn_zones: Vec::new(),
+            deployment_zone: None,
+            starting_gold: INITIAL_GOLD,
+            starting_population: INITIAL_POPULATION,
+            starting_heroes: None,
+            victory: default_victory(),
+        }
+    }
+
+    #[test]
+    fn test_valid_level() {
+        test_level().validate().unwrap();
+    }
+
+    #[test]
+    fn test_optional_fields_default() {
+        let json = r#"{ "name": "old", "size": 1, "tiles": [["grassblock"], ["empty"]] }"#;
+        let level = serde_json::from_str::<LevelAsset>(json).unwrap();
+        assert_eq!(level.dim(), TileDim(1, 1));
+        assert_eq!(level.starting_gold, INITIAL_GOLD);
+        assert!(level.starting_heroes.is_none());
+        assert_eq!(level.victory, vec![VictoryCondition::DefeatFinalBoss]);
+        assert_eq!(level.spawn_zones().len(), 4);
+    }
+
+    #[test]
+    fn test_manifest_add_level() {
+        let json = r#"{ "levels": [
+            { "name": "first", "display_name": "First", "difficulty": 1, "unlock_order": 0 },
+            { "name": "map", "display_name": "Map", "difficulty": 2, "unlock_order": 1, "file": "map.tmj" }
+        ] }"#;
+        let mut manifest = serde_json::from_str::<LevelManifest>(json).unwrap();
+
+        manifest.add_level("map");
+        assert_eq!(manifest.levels.len(), 2);
+        assert_eq!(manifest.levels[1].file_name(), "map.json");
+
+        manifest.add_level("new_level");
+        let entry = manifest.levels.last().unwrap();
+        assert_eq!(entry.name, "new_level");
+        assert_eq!(entry.unlock_order, 2);
+    }
+
+    #[test]
+    fn test_rectangular_size() {
+        let json = r#"{ "width": 4, "height": 2 }"#;
+        let size = serde_json::from_str::<LevelSize>(json).unwrap();
+        assert_eq!(size.dim(), TileDim(4, 2));
+    }
+
+    #[test]
+    fn test_wrong_tile_count() {
+        let mut level = test_level();
+        level.tiles[1].pop();
+        assert!(matches!(
+            level.validate(),
+            Err(LevelAssetLoaderError::TileCount {
+                layer: 1,
+                expected: 9,
+                found: 8
+            })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_tile() {
+        let mut level = test_level();
+        level.tiles[0][5] = "lavablock".to_string();
+        assert!(matches!(
+            level.validate(),
+            Err(LevelAssetLoaderError::UnknownTile {
+                layer: 0,
+                tile: Tile(2, 1),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_unreachable_structure() {
+        let mut level = test_level();
+        for index in [1, 3, 5, 7] {
+            level.tiles[0][index] = "waterblock".to_string();
+        }
+        assert!(matches!(
+            level.validate(),
+            Err(LevelAssetLoaderError::UnreachableStructure(Tile(1, 1)))
+        ));
+    }
+
+    #[test]
+    fn test_overlapping_structure() {
+        let mut level = test_level();
+        level.tiles[1][4] = "tavern".to_string();
+        level.tiles[1][5] = "house1".to_string();
+        assert!(matches!(
+            level.validate(),
+            Err(LevelAssetLoaderError::OverlappingStructure(Tile(2, 1)))
+        ));
+
+        // A tavern on the last column would stick out of the map.
+        level.tiles[1][4] = EMPTY_TILE.to_string();
+        level.tiles[1][5] = "tavern".to_string();
+        assert!(matches!(
+            level.validate(),
+            Err(LevelAssetLoaderError::OverlappingStructure(Tile(2, 1)))
+        ));
+    }
+
+    #[test]
+    fn test_resize_keeps_tiles_and_fitting_zones() {
+        let mut level = test_level();
+        level.deployment_zone = Some(LevelRect {
+            min: Tile(0, 0),
+            max: Tile(2, 2),
+        });
+        level.spawn_zones.push(SpawnZone {
+            name: "corner".to_string(),
+            area: LevelRect {
+                min: Tile(0, 0),
+                max: Tile(0, 0),
+            },
+        });
+
+        level.resize(TileDim(4, 2));
+
+        assert_eq!(level.dim(), TileDim(4, 2));
+        assert_eq!(level.tiles[0].len(), 8);
+        assert_eq!(level.structure_at(Tile(1, 1)), Some("house1"));
+        assert_eq!(level.terrain_at(Tile(3, 1)), Some(Terrain::Grass));
+        assert_eq!(level.deployment_zone, None);
+        assert_eq!(level.spawn_zones.len(), 1);
+    }
+
+    #[test]
+    fn test_zone_out_of_bounds() {
+        let mut level = test_level();
+        level.deployment_zone = Some(LevelRect {
+            min: Tile(0, 0),
+            max: Tile(3, 3),
+        });
+        assert!(matches!(
+            level.validate(),
+            Err(LevelAssetLoaderError::ZoneOutOfBounds(_))
+        ));
+    }
+}
@@ -0,0 +1,333 @@
+//! Import levels made in the [Tiled](https://www.mapeditor.org/) map editor.
+//!
+//! Only json maps (`.tmj`) with embedded "collection of images" tilesets are supported,
+//! tile names are the file names of the tile images without extension (`grassblock`, `house1`...).
+//!
+//! - The first tile layer (or the one named `ground`) is the ground layer.
+//! - The second tile layer (or the one named `objects`) is the object layer.
+//! - Rectangles of type `spawn` in object layers are enemy spawn zones,
+//!   the rectangle of type `deployment` is the deployment zone.
+//! - Map properties `starting_gold` and `starting_population` override the defaults.
+
+use std::path::Path;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::game::constants::{INITIAL_GOLD, INITIAL_POPULATION};
+use crate::game::victory::VictoryCondition;
+use crate::path_finding::tiles::Tile;
+
+use super::level_asset::{
+    LevelAsset, LevelAssetLoaderError, LevelRect, LevelSize, SpawnZone, EMPTY_TILE,
+};
+
+/// Tiled stores flipping in the highest bits of a global tile id.
+const TILED_GID_MASK: u32 = 0x0fff_ffff;
+
+#[derive(Deserialize, Debug)]
+struct TiledMap {
+    width: u32,
+    height: u32,
+    tilewidth: f32,
+    tileheight: f32,
+    #[serde(default)]
+    orientation: String,
+    layers: Vec<TiledLayer>,
+    #[serde(default)]
+    tilesets: Vec<TiledTileset>,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum TiledLayer {
+    TileLayer {
+        name: String,
+        data: Vec<u32>,
+    },
+    ObjectGroup {
+        objects: Vec<TiledObject>,
+    },
+    /// Image and group layers are ignored.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug)]
+struct TiledObject {
+    #[serde(default)]
+    name: String,
+    /// Called class since Tiled 1.9.
+    #[serde(default, rename = "type", alias = "class")]
+    kind: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+}
+
+#[derive(Deserialize, Debug)]
+struct TiledTileset {
+    firstgid: u32,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    tiles: Vec<TiledTile>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TiledTile {
+    id: u32,
+    #[serde(default)]
+    image: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TiledProperty {
+    name: String,
+    value: serde_json::Value,
+}
+
+impl TiledMap {
+    fn tile_name(&self, gid: u32) -> Result<String, TiledLevelLoaderError> {
+        let gid = gid & TILED_GID_MASK;
+        if gid == 0 {
+            return Ok(EMPTY_TILE.to_string());
+        }
+
+        let tileset = self
+            .tilesets
+            .iter()
+            .filter(|tileset| tileset.firstgid <= gid)
+            .max_by_key(|tileset| tileset.firstgid)
+            .ok_or(TiledLevelLoaderError::UnknownGid(gid))?;
+        if let Some(source) = &tileset.source {
+            return Err(TiledLevelLoaderError::ExternalTileset(source.clone()));
+        }
+
+        tileset
+            .tiles
+            .iter()
+            .find(|tile| tile.id == gid - tileset.firstgid)
+            .and_then(|tile| tile.image.as_deref())
+            .and_then(|image| Path::new(image).file_stem())
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or(TiledLevelLoaderError::UnknownGid(gid))
+    }
+
+    fn tile_layer(&self, name: &str, index: usize) -> Option<&Vec<u32>> {
+        let tile_layers = || {
+            self.layers.iter().filter_map(|layer| match layer {
+                TiledLayer::TileLayer { name, data } => Some((name, data)),
+                _ => None,
+            })
+        };
+        tile_layers()
+            .find(|(layer_name, _)| *layer_name == name)
+            .or_else(|| tile_layers().nth(index))
+            .map(|(_, data)| data)
+    }
+
+    /// Tiles covered by a rectangle object.
+    fn object_rect(&self, object: &TiledObject) -> LevelRect {
+        // Isometric maps measure both axes of objects in tile heights.
+        let (tile_width, tile_height) = match self.orientation.as_str() {
+            "isometric" => (self.tileheight, self.tileheight),
+            _ => (self.tilewidth, self.tileheight),
+        };
+        let min = Vec2::new(object.x / tile_width, object.y / tile_height);
+        let max = Vec2::new(
+            (object.x + object.width) / tile_width,
+            (object.y + object.height) / tile_height,
+        );
+        LevelRect {
+            min: Tile(min.x.floor() as i32, min.y.floor() as i32),
+            max: Tile(
+                (max.x.ceil() as i32 - 1).max(min.x.floor() as i32),
+                (max.y.ceil() as i32 - 1).max(min.y.floor() as i32),
+            ),
+        }
+    }
+
+    fn property_u32(&self, name: &str) -> Option<u32> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+            .and_then(|property| property.value.as_u64())
+            .map(|value| value as u32)
+    }
+
+    fn to_level_asset(&self, name: String) -> Result<LevelAsset, TiledLevelLoaderError> {
+        let mut tiles: [Vec<String>; 2] = Default::default();
+        for (index, layer_name) in ["ground", "objects"].into_iter().enumerate() {
+            let data = self
+                .tile_layer(layer_name, index)
+                .ok_or(TiledLevelLoaderError::MissingLayer(layer_name))?;
+            tiles[index] = data
+                .iter()
+                .map(|gid| self.tile_name(*gid))
+                .collect::<Result<_, _>>()?;
+        }
+
+        let mut spawn_zones = Vec::new();
+        let mut deployment_zone = None;
+        let objects = self.layers.iter().flat_map(|layer| match layer {
+            TiledLayer::ObjectGroup { objects } => objects.as_slice(),
+            _ => &[],
+        });
+        for object in objects {
+            match object.kind.as_str() {
+                "spawn" => spawn_zones.push(SpawnZone {
+                    name: object.name.clone(),
+                    area: self.object_rect(object),
+                }),
+                "deployment" => deployment_zone = Some(self.object_rect(object)),
+                _ => {}
+            }
+        }
+
+        let level = LevelAsset {
+            name,
+            size: LevelSize::Rectangle {
+                width: self.width,
+                height: self.height,
+            },
+            tiles,
+            spawn_zones,
+            deployment_zone,
+            starting_gold: self.property_u32("starting_gold").unwrap_or(INITIAL_GOLD),
+            starting_population: self
+                .property_u32("starting_population")
+                .unwrap_or(INITIAL_POPULATION),
+            starting_heroes: None,
+            victory: vec![VictoryCondition::DefeatFinalBoss],
+        };
+        level.validate()?;
+        Ok(level)
+    }
+}
+
+#[derive(Default)]
+pub struct TiledLevelLoader;
+
+impl AssetLoader for TiledLevelLoader {
+    type Asset = LevelAsset;
+    type Settings = ();
+    type Error = TiledLevelLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let tiled_map = serde_json::from_slice::<TiledMap>(&bytes)?;
+        let name = load_context
+            .path()
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        tiled_map.to_level_asset(name)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmj"]
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum TiledLevelLoaderError {
+    #[error("Could not load tiled map: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not deserialize tiled map: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("Tiled map has no {0} tile layer")]
+    MissingLayer(&'static str),
+    #[error("Tile id {0} is not in an embedded image collection tileset")]
+    UnknownGid(u32),
+    #[error("External tilesets are not supported, embed {0} in the map")]
+    ExternalTileset(String),
+    #[error(transparent)]
+    Level(#[from] LevelAssetLoaderError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::level::Terrain;
+
+    const TEST_MAP: &str = r#"{
+        "width": 3, "height": 2, "tilewidth": 32, "tileheight": 16,
+        "orientation": "orthogonal",
+        "layers": [
+            { "type": "tilelayer", "name": "ground", "data": [1, 1, 1, 2, 1, 1] },
+            { "type": "tilelayer", "name": "objects", "data": [0, 0, 0, 0, 3, 0] },
+            { "type": "objectgroup", "name": "zones", "objects": [
+                { "name": "west", "type": "spawn", "x": 0, "y": 0, "width": 32, "height": 32 },
+                { "name": "", "class": "deployment", "x": 32, "y": 0, "width": 64, "height": 16 }
+            ] }
+        ],
+        "tilesets": [{ "firstgid": 1, "tiles": [
+            { "id": 0, "image": "../tiles/grassblock.png" },
+            { "id": 1, "image": "../tiles/waterblock.png" },
+            { "id": 2, "image": "../tiles/house1.png" }
+        ] }],
+        "properties": [{ "name": "starting_gold", "type": "int", "value": 300 }]
+    }"#;
+
+    fn test_map() -> TiledMap {
+        serde_json::from_str(TEST_MAP).unwrap()
+    }
+
+    #[test]
+    fn test_tile_layers() {
+        let level = test_map().to_level_asset("test".to_string()).unwrap();
+        assert_eq!(level.terrain_at(Tile(0, 1)), Some(Terrain::Water));
+        assert_eq!(level.structure_at(Tile(1, 1)), Some("house1"));
+        assert_eq!(level.structure_at(Tile(0, 0)), None);
+        assert_eq!(level.starting_gold, 300);
+    }
+
+    #[test]
+    fn test_object_zones() {
+        let level = test_map().to_level_asset("test".to_string()).unwrap();
+        assert_eq!(level.spawn_zones.len(), 1);
+        assert_eq!(
+            level.spawn_zones[0].area,
+            LevelRect {
+                min: Tile(0, 0),
+                max: Tile(0, 1)
+            }
+        );
+        assert_eq!(
+            level.deployment_zone,
+            Some(LevelRect {
+                min: Tile(1, 0),
+                max: Tile(2, 0)
+            })
+        );
+    }
+
+    #[test]
+    fn test_flipped_tiles() {
+        let map = test_map();
+        assert_eq!(map.tile_name(0x8000_0003).unwrap(), "house1");
+        assert!(matches!(
+            map.tile_name(9),
+            Err(TiledLevelLoaderError::UnknownGid(9))
+        ));
+    }
+}
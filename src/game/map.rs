@@ -1,14 +1,14 @@
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use bevy::utils::HashSet;
-use bimap::{BiHashMap, Overwritten};
 use pathfinding::directed::astar::astar;
-use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
 
 use crate::path_finding::find_all_within_distance_unweighted;
 use crate::path_finding::tiles::Tiled;
 use crate::path_finding::tiles::{Tile, TileDim, TileDir, TileRect};
 
+use super::components::{Abilities, Ability};
 use super::level::Terrain;
 
 // On screen 0,0 is top middle tile,
@@ -31,12 +31,13 @@ use super::level::Terrain;
 //     NORTH, NORTHEAST, EAST, SOUTHEAST, SOUTH, SOUTHWEST, WEST, NORTHWEST,
 // ];
 
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Clone)]
 pub struct VillageMap {
     pub size: TileDim,
-    pub heat_map: Vec<u32>,
     pub terrain: HashMap<Tile, Terrain>,
     pub actors: TileMap,
+    /// Traps lie under the actors, a trapped tile can still be entered.
+    pub traps: HashMap<Tile, Entity>,
     pub deployment_zone: HashSet<Tile>,
 }
 
@@ -44,9 +45,9 @@ impl VillageMap {
     pub fn new(size: TileDim) -> VillageMap {
         VillageMap {
             size,
-            heat_map: Vec::new(),
             terrain: Default::default(),
             actors: TileMap::new(size),
+            traps: HashMap::default(),
             deployment_zone: HashSet::default(),
         }
     }
@@ -72,36 +73,26 @@ impl VillageMap {
     }
 
     /// Create a path from start to target while avoiding obstacles.
+    ///
+    /// The path can pass through tiles the actor is not allowed to stop on,
+    /// see [`Abilities`].
     pub fn pathfind(
         &self,
         start: &Tile,
         target: &Tile,
         directions: &[TileDir],
-        is_airborne: bool,
+        abilities: &Abilities,
     ) -> Option<(Vec<Tile>, i32)> {
+        if start != target && !self.can_stop_on(*target, abilities) {
+            return None;
+        }
+
         astar(
             start,
             // successors
             |tile_coord: &Tile| {
-                let tile_coord = *tile_coord;
-                directions.iter().filter_map(move |dir| {
-                    let final_coord = tile_coord.step(*dir);
-
-                    if self.is_out_of_bounds(final_coord) {
-                        return None;
-                    }
-
-                    // There is an obstacle blocking it
-                    if self.actors.get(final_coord).is_some() {
-                        return None;
-                    }
-
-                    // Check eligibility of moving on top of water tile
-                    match self.get_terrain(final_coord) {
-                        Some(Terrain::Water) if is_airborne == false => return None,
-                        _ => return Some((final_coord, 1)),
-                    }
-                })
+                self.passable_steps(*tile_coord, directions, abilities)
+                    .map(|tile| (tile, 1))
             },
             // heuristic
             |tile_coord: &Tile| tile_coord.distance_squared(*target),
@@ -111,142 +102,69 @@ impl VillageMap {
     }
 
     /// Flood into tiles within the range taking into consideration
-    /// on terrain, obstacles, and directions.
+    /// on terrain, obstacles, directions and abilities.
+    ///
+    /// Only tiles the actor can end its move on are returned, along with the start tile.
     pub fn flood(
         &self,
         start: Tile,
         max_distance: u32,
         directions: &[TileDir],
-        is_airborne: bool,
+        abilities: &Abilities,
     ) -> HashSet<Tile> {
-        find_all_within_distance_unweighted(start, max_distance, |tile_coord| {
-            directions.iter().filter_map(move |dir| {
-                let final_coord = tile_coord.step(*dir);
-                if self.is_out_of_bounds(final_coord) {
-                    return None;
-                }
-
-                // There is an obstacle blocking it
-                if self.actors.is_occupied(final_coord) {
-                    return None;
-                }
-
-                // Check eligibility of moving on top of water tile
-                match self.get_terrain(final_coord) {
-                    Some(Terrain::Water) if !is_airborne => return None,
-                    _ => return Some(final_coord),
-                }
-            })
-        })
-    }
-
-    /// Sort tiles based on distance.
-    pub fn sort_tiles_by_distance(tiles: &mut [Tile], target_tile: Tile) {
-        tiles.sort_by_key(|t| Tile::distance_squared(*t, target_tile));
-    }
-
-    /// Sort tiles based on heat map.
-    pub fn sort_tiles_by_heat(&self, tiles: &mut [Tile]) {
-        tiles.sort_by_key(|t| {
-            let index = t.x() + t.y() * self.size.x() as i32;
-            self.heat_map[index as usize]
+        let mut tiles = find_all_within_distance_unweighted(start, max_distance, |tile_coord| {
+            self.passable_steps(tile_coord, directions, abilities)
         });
-    }
-
-    /// Get best tile based on heat map.
-    pub fn get_best_tile(
-        &self,
-        start: Tile,
-        max_distance: u32,
-        directions: &[TileDir],
-        is_airborne: bool,
-    ) -> Option<Tile> {
-        let mut tiles = self
-            .flood(start, max_distance, directions, is_airborne)
-            .iter()
-            .cloned()
-            .collect::<Vec<_>>();
-
-        Self::sort_tiles_by_distance(&mut tiles, start);
-        self.sort_tiles_by_heat(&mut tiles);
-        tiles.first().copied()
-    }
-
-    /// Get worst tile based on heat map.
-    pub fn get_worst_tile(
-        &self,
-        start: Tile,
-        max_distance: u32,
-        directions: &[TileDir],
-        is_airborne: bool,
-    ) -> Option<Tile> {
-        let mut tiles = self
-            .flood(start, max_distance, directions, is_airborne)
-            .iter()
-            .cloned()
-            .collect::<Vec<_>>();
-
-        Self::sort_tiles_by_distance(&mut tiles, start);
-        self.sort_tiles_by_heat(&mut tiles);
-        tiles.last().copied()
-    }
+        tiles.retain(|tile| *tile == start || self.can_stop_on(*tile, abilities));
+        tiles
+    }
+
+    /// Neighbouring tiles an actor can move through.
+    fn passable_steps<'a>(
+        &'a self,
+        tile_coord: Tile,
+        directions: &'a [TileDir],
+        abilities: &'a Abilities,
+    ) -> impl Iterator<Item = Tile> + 'a {
+        // Flying actors can always move diagonally
+        let directions = match abilities.has(Ability::Flying) {
+            true => &TileDir::ALL[..],
+            false => directions,
+        };
+
+        directions.iter().filter_map(move |dir| {
+            let final_coord = tile_coord.step(*dir);
+            if self.is_out_of_bounds(final_coord) {
+                return None;
+            }
 
-    /// Generate heat map based on [`Self::object`].
-    ///
-    /// # Example
-    ///
-    /// 4, 3, 2, 3, 4, 5, 6, 7, 8, 9,
-    /// 3, 2, 1, 2, 3, 4, 5, 6, 7, 8,
-    /// 2, 1, 0, 1, 2, 3, 4, 5, 6, 7,
-    /// 2, 1, 1, 2, 2, 3, 4, 5, 6, 7,
-    /// 1, 0, 1, 2, 1, 2, 3, 4, 5, 6,
-    /// 2, 1, 2, 1, 0, 1, 2, 3, 4, 5,
-    /// 3, 2, 3, 2, 1, 2, 3, 4, 5, 6,
-    /// 4, 3, 4, 3, 2, 3, 4, 5, 6, 7,
-    /// 5, 4, 5, 4, 3, 4, 5, 6, 7, 8,
-    /// 6, 5, 6, 5, 4, 5, 6, 7, 8, 9,
-    pub fn generate_heat_map(&mut self, is_enemy: impl Fn(Entity) -> bool) {
-        // Mark max as unvisted
-        self.heat_map = vec![u32::MAX; (self.size.x() * self.size.y()) as usize];
-        let mut stack = VecDeque::new();
-
-        for (tile_coord, entity) in self.actors.map.iter() {
-            if is_enemy(*entity) {
-                continue;
+            // There is an obstacle blocking it
+            if self.actors.is_occupied(final_coord) && !abilities.passes_actors() {
+                return None;
             }
 
-            let index = (tile_coord.x() + tile_coord.y() * self.size.x() as i32) as usize;
-            self.heat_map[index] = 0;
+            // Check eligibility of moving through a water tile
+            match self.get_terrain(final_coord) {
+                Some(Terrain::Water) if !abilities.passes_water() => None,
+                _ => Some(final_coord),
+            }
+        })
+    }
 
-            stack.push_back(*tile_coord);
+    /// Can an actor end its move on this tile.
+    pub fn can_stop_on(&self, tile: Tile, abilities: &Abilities) -> bool {
+        if self.is_out_of_bounds(tile) || self.actors.is_occupied(tile) {
+            return false;
         }
-
-        if stack.is_empty() {
-            self.heat_map.fill(0);
-            return;
+        match self.get_terrain(tile) {
+            Some(Terrain::Water) => abilities.stays_on_water(),
+            _ => true,
         }
+    }
 
-        while let Some(tile_coord) = stack.pop_front() {
-            let index = (tile_coord.x() + tile_coord.y() * self.size.x() as i32) as usize;
-            let curr_heat = self.heat_map[index];
-
-            for offset in TileDir::EDGES.iter() {
-                let flood_coord = tile_coord.step(*offset);
-                if self.is_out_of_bounds(flood_coord) {
-                    continue;
-                }
-
-                let index = (flood_coord.x() + flood_coord.y() * self.size.x() as i32) as usize;
-
-                // Has been visited
-                if self.heat_map[index] != u32::MAX {
-                    continue;
-                }
-
-                self.heat_map[index] = curr_heat + 1;
-                stack.push_back(flood_coord);
-            }
-        }
+    /// Sort tiles based on distance.
+    pub fn sort_tiles_by_distance(tiles: &mut [Tile], target_tile: Tile) {
+        tiles.sort_by_key(|t| Tile::distance_squared(*t, target_tile));
     }
 
     pub fn iter_terrain(&self) -> impl Iterator<Item = (Tile, Terrain)> + '_ {
@@ -269,10 +187,54 @@ impl Tiled for VillageMap {
     }
 }
 
-#[derive(Debug, Default)]
+/// Tiles covered by an actor on the [`TileMap`].
+///
+/// Covers `size` tiles from `origin` towards larger x and y, most actors cover a single tile.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Footprint {
+    pub origin: Tile,
+    pub size: TileDim,
+}
+
+impl Footprint {
+    pub fn new(origin: Tile, size: TileDim) -> Self {
+        assert!(0 < size.x() && 0 < size.y());
+        Self { origin, size }
+    }
+
+    pub fn single(origin: Tile) -> Self {
+        Self::new(origin, TileDim::ONE)
+    }
+
+    /// Quarter turn around the origin, width and depth swap.
+    pub fn rotated(self) -> Self {
+        Self::new(self.origin, TileDim(self.size.y(), self.size.x()))
+    }
+
+    /// Far corner from the origin, the tile nearest to the camera.
+    pub fn max(self) -> Tile {
+        self.origin + self.size - TileDim::ONE
+    }
+
+    pub fn rect(self) -> TileRect {
+        TileRect(self.origin, self.max())
+    }
+
+    pub fn contains(self, tile: Tile) -> bool {
+        self.rect().contains(tile)
+    }
+
+    pub fn tiles(self) -> impl Iterator<Item = Tile> {
+        self.rect().into_iter()
+    }
+}
+
+/// Actors on the map, an actor can cover several tiles but a tile holds at most one actor.
+#[derive(Debug, Default, Clone)]
 pub struct TileMap {
     size: TileDim,
-    map: BiHashMap<Tile, Entity>,
+    map: HashMap<Tile, Entity>,
+    footprints: HashMap<Entity, Footprint>,
 }
 
 impl TileMap {
@@ -280,7 +242,8 @@ impl TileMap {
         assert!(0 < size.x() && 0 < size.y());
         TileMap {
             size,
-            map: BiHashMap::default(),
+            map: HashMap::default(),
+            footprints: HashMap::default(),
         }
     }
 
@@ -293,35 +256,54 @@ impl TileMap {
     }
 
     pub fn is_occupied(&self, position: Tile) -> bool {
-        self.map.get_by_left(&position).is_some()
+        self.map.contains_key(&position)
     }
 
     /// get entity at position
     pub fn get(&self, position: Tile) -> Option<Entity> {
-        self.map.get_by_left(&position).copied()
+        self.map.get(&position).copied()
     }
 
-    /// find entity's position in map
-    pub fn locate(&self, entity: Entity) -> Option<Tile> {
-        self.map.get_by_right(&entity).copied()
+    /// find the tiles the entity covers in map
+    pub fn locate(&self, entity: Entity) -> Option<Footprint> {
+        self.footprints.get(&entity).copied()
     }
 
     /// place entity at map position, will move entity if already in map.
-    /// will overwrite any existing entity at the position
-    pub fn set(&mut self, position: Tile, entity: Entity) -> Overwritten<Tile, Entity> {
-        self.map.insert(position, entity)
+    /// will take any existing entity at the position off the map and return it
+    pub fn set(&mut self, position: Tile, entity: Entity) -> Vec<Entity> {
+        self.place(Footprint::single(position), entity)
+    }
+
+    /// Place entity on every tile of the footprint, will move entity if already in map.
+    /// Entities overlapping the footprint are taken off the map and returned.
+    pub fn place(&mut self, footprint: Footprint, entity: Entity) -> Vec<Entity> {
+        self.remove_entity(entity);
+        let mut replaced = Vec::new();
+        for tile in footprint.tiles() {
+            if let Some(other) = self.remove(tile) {
+                replaced.push(other);
+            }
+            self.map.insert(tile, entity);
+        }
+        self.footprints.insert(entity, footprint);
+        replaced
     }
 
-    /// remove entity from map at position
+    /// remove the entity covering the position from map
     pub fn remove(&mut self, position: Tile) -> Option<Entity> {
-        self.map.remove_by_left(&position).map(|(_, entity)| entity)
+        let entity = self.get(position)?;
+        self.remove_entity(entity);
+        Some(entity)
     }
 
     /// remove entity from map
-    pub fn remove_entity(&mut self, entity: Entity) -> Option<Tile> {
-        self.map
-            .remove_by_right(&entity)
-            .map(|(position, _)| position)
+    pub fn remove_entity(&mut self, entity: Entity) -> Option<Footprint> {
+        let footprint = self.footprints.remove(&entity)?;
+        for tile in footprint.tiles() {
+            self.map.remove(&tile);
+        }
+        Some(footprint)
     }
 
     pub fn get_neighbouring_positions_rook(
@@ -346,9 +328,15 @@ impl TileMap {
             .filter(|target| self.bounds().contains(*target))
     }
 
+    /// Every occupied tile, actors covering several tiles are listed once per tile.
     pub fn iter(&self) -> impl Iterator<Item = (Tile, Entity)> + '_ {
         self.map.iter().map(|(t, e)| (*t, *e))
     }
+
+    /// Every actor on the map with the tiles it covers.
+    pub fn footprints(&self) -> impl Iterator<Item = (Entity, Footprint)> + '_ {
+        self.footprints.iter().map(|(e, f)| (*e, *f))
+    }
 }
 
 #[cfg(test)]
@@ -430,6 +418,39 @@ mod tests {
         assert_eq!(tile_map.get(tile), None);
     }
 
+    #[test]
+    fn test_tile_map_footprint() {
+        let mut tile_map = TileMap::new(TileDim(10, 10));
+        let entity = Entity::from_raw(1);
+        let footprint = Footprint::new(Tile(1, 1), TileDim(2, 1));
+        tile_map.place(footprint, entity);
+        assert_eq!(tile_map.get(Tile(1, 1)), Some(entity));
+        assert_eq!(tile_map.get(Tile(2, 1)), Some(entity));
+        assert_eq!(tile_map.get(Tile(1, 2)), None);
+        assert_eq!(tile_map.locate(entity), Some(footprint));
+
+        // Moving replaces the whole footprint.
+        tile_map.place(footprint.rotated(), entity);
+        assert_eq!(tile_map.get(Tile(2, 1)), None);
+        assert_eq!(tile_map.get(Tile(1, 2)), Some(entity));
+
+        // Removing any covered tile takes the whole actor off the map.
+        assert_eq!(tile_map.remove(Tile(1, 2)), Some(entity));
+        assert_eq!(tile_map.get(Tile(1, 1)), None);
+        assert_eq!(tile_map.locate(entity), None);
+    }
+
+    #[test]
+    fn test_tile_map_place_replaces_overlapping_actors() {
+        let mut tile_map = TileMap::new(TileDim(10, 10));
+        let (small, large) = (Entity::from_raw(1), Entity::from_raw(2));
+        tile_map.set(Tile(2, 1), small);
+        let replaced = tile_map.place(Footprint::new(Tile(1, 1), TileDim(2, 1)), large);
+        assert_eq!(replaced, vec![small]);
+        assert_eq!(tile_map.locate(small), None);
+        assert_eq!(tile_map.get(Tile(2, 1)), Some(large));
+    }
+
     #[test]
     fn test_tile_map_get_neighbouring_positions_rook() {
         let size = TileDim(10, 10);
@@ -473,7 +494,7 @@ mod tests {
         let target = Tile(3, 3);
         let directions = &TileDir::ALL;
 
-        let path = village_map.pathfind(&start, &target, directions, false);
+        let path = village_map.pathfind(&start, &target, directions, &Abilities::default());
         assert!(path.is_some());
         let (tiles, cost) = path.unwrap();
         assert_eq!(tiles.first().unwrap(), &start);
@@ -489,7 +510,7 @@ mod tests {
         let target = start.step(TileDir::South);
         let directions = &TileDir::ALL;
 
-        let path = village_map.pathfind(&start, &target, directions, false);
+        let path = village_map.pathfind(&start, &target, directions, &Abilities::default());
         assert!(path.is_some());
         let (tiles, cost) = path.unwrap();
         assert_eq!(tiles.first().unwrap(), &start);
@@ -505,7 +526,7 @@ mod tests {
         let target = Tile(3, 3);
         let directions = &TileDir::ALL;
 
-        let path = village_map.pathfind(&start, &target, directions, false);
+        let path = village_map.pathfind(&start, &target, directions, &Abilities::default());
         assert!(path.is_some());
         let (tiles, cost) = path.unwrap();
         assert_eq!(tiles.last().unwrap(), &target);
@@ -519,7 +540,7 @@ mod tests {
         let start = Tile(0, 0);
         let directions = &TileDir::ALL;
 
-        let flooded_tiles = village_map.flood(start, 3, directions, false);
+        let flooded_tiles = village_map.flood(start, 3, directions, &Abilities::default());
         assert!(flooded_tiles.len() == 1);
         assert!(flooded_tiles.contains(&Tile(0, 0)));
     }
@@ -531,7 +552,7 @@ mod tests {
         let start = Tile(0, 0);
         let directions = &TileDir::ALL;
 
-        let flooded_tiles = village_map.flood(start, 3, directions, false);
+        let flooded_tiles = village_map.flood(start, 3, directions, &Abilities::default());
 
         assert_eq!(flooded_tiles.len(), 2);
         assert!(flooded_tiles.contains(&Tile(0, 0)));
@@ -545,7 +566,7 @@ mod tests {
         let start = Tile(0, 0);
         let directions = &TileDir::ALL;
 
-        let flooded_tiles = village_map.flood(start, 3, directions, false);
+        let flooded_tiles = village_map.flood(start, 3, directions, &Abilities::default());
         assert_eq!(flooded_tiles.len(), 2);
         assert!(flooded_tiles.contains(&Tile(0, 0)));
         assert!(flooded_tiles.contains(&Tile(1, 0)));
@@ -558,7 +579,7 @@ mod tests {
         let start = Tile(0, 0);
         let directions = &TileDir::ALL;
 
-        let flooded_tiles = village_map.flood(start, 3, directions, false);
+        let flooded_tiles = village_map.flood(start, 3, directions, &Abilities::default());
         println!("flooded_tiles = {flooded_tiles:?}");
         assert_eq!(flooded_tiles.len(), 6);
     }
@@ -570,55 +591,71 @@ mod tests {
         let start = Tile(0, 0);
         let directions = &TileDir::ALL;
 
-        let flooded_tiles = village_map.flood(start, 3, directions, false);
+        let flooded_tiles = village_map.flood(start, 3, directions, &Abilities::default());
         assert!(flooded_tiles.contains(&Tile(3, 3)));
     }
 
-    #[test]
-    fn test_village_map_sort_tiles_by_distance() {
-        let target = Tile(0, 0);
-        let mut tiles = vec![Tile(1, 1), Tile(0, 2), Tile(2, 2), Tile(3, 3)];
-        VillageMap::sort_tiles_by_distance(&mut tiles, target);
-        assert_eq!(tiles, vec![Tile(1, 1), Tile(0, 2), Tile(2, 2), Tile(3, 3)]);
+    /// A 3x1 map with water in the middle.
+    fn river_map() -> VillageMap {
+        let mut village_map = VillageMap::new(TileDim(3, 1));
+        village_map.set_terrain(Tile(0, 0), Terrain::Grass);
+        village_map.set_terrain(Tile(1, 0), Terrain::Water);
+        village_map.set_terrain(Tile(2, 0), Terrain::Grass);
+        village_map
     }
 
     #[test]
-    fn test_village_map_sort_tiles_by_heat() {
-        let mut village_map = VillageMap::new(TileDim(10, 10));
-        village_map.heat_map = (0..100).collect();
-        let mut tiles = vec![Tile(1, 1), Tile(2, 2), Tile(3, 3), Tile(4, 4)];
-        village_map.sort_tiles_by_heat(&mut tiles);
-        assert_eq!(tiles, vec![Tile(1, 1), Tile(2, 2), Tile(3, 3), Tile(4, 4)]);
+    fn test_village_map_flood_water() {
+        let village_map = river_map();
+        let flood = |abilities: &[Ability]| {
+            village_map.flood(Tile(0, 0), 2, &TileDir::EDGES, &Abilities::new(abilities))
+        };
+
+        assert_eq!(flood(&[]).len(), 1);
+
+        let swimmer = flood(&[Ability::Swimmer]);
+        assert!(!swimmer.contains(&Tile(1, 0)));
+        assert!(swimmer.contains(&Tile(2, 0)));
+
+        let aquatic = flood(&[Ability::Aquatic]);
+        assert!(aquatic.contains(&Tile(1, 0)));
+        assert!(aquatic.contains(&Tile(2, 0)));
     }
 
     #[test]
-    fn test_village_map_get_worst_tile() {
-        let mut village_map = VillageMap::new(TileDim(10, 10));
-        village_map.heat_map = (0..100).collect();
-        let start = Tile(0, 0);
-        let directions = &TileDir::ALL;
-        let worst_tile = village_map.get_worst_tile(start, 3, directions, false);
-        assert_eq!(worst_tile, Some(Tile(3, 3)));
+    fn test_village_map_flood_intangible() {
+        let mut village_map = VillageMap::new(TileDim(3, 1));
+        village_map.actors.set(Tile(1, 0), Entity::from_raw(0));
+        let flood = |abilities: &[Ability]| {
+            village_map.flood(Tile(0, 0), 2, &TileDir::EDGES, &Abilities::new(abilities))
+        };
+
+        assert_eq!(flood(&[]).len(), 1);
+
+        let intangible = flood(&[Ability::Intangible]);
+        assert!(!intangible.contains(&Tile(1, 0)));
+        assert!(intangible.contains(&Tile(2, 0)));
     }
 
     #[test]
-    fn test_village_map_get_best_tile_trivial() {
-        let mut village_map = VillageMap::new(TileDim(1, 2));
-        village_map.heat_map = vec![1, 0];
-        let start = Tile(0, 0);
-        let directions = &TileDir::ALL;
-        let best_tile = village_map.get_best_tile(start, 3, directions, false);
-        assert_eq!(best_tile, Some(Tile(0, 1)));
+    fn test_village_map_pathfind_swimmer() {
+        let village_map = river_map();
+        let swimmer = Abilities::new(&[Ability::Swimmer]);
+        let (tiles, _) = village_map
+            .pathfind(&Tile(0, 0), &Tile(2, 0), &TileDir::EDGES, &swimmer)
+            .unwrap();
+        assert_eq!(tiles, vec![Tile(0, 0), Tile(1, 0), Tile(2, 0)]);
+        assert!(village_map
+            .pathfind(&Tile(0, 0), &Tile(1, 0), &TileDir::EDGES, &swimmer)
+            .is_none());
     }
 
     #[test]
-    fn test_village_map_generate_heat_map() {
-        let mut village_map = VillageMap::new(TileDim(3, 3));
-        let entity = Entity::from_raw(1);
-        village_map.actors.set(Tile(2, 2), entity);
-        village_map.generate_heat_map(|_| false);
-        assert_eq!(village_map.heat_map[0], 4);
-        assert_eq!(village_map.heat_map[8], 0);
+    fn test_village_map_sort_tiles_by_distance() {
+        let target = Tile(0, 0);
+        let mut tiles = vec![Tile(1, 1), Tile(0, 2), Tile(2, 2), Tile(3, 3)];
+        VillageMap::sort_tiles_by_distance(&mut tiles, target);
+        assert_eq!(tiles, vec![Tile(1, 1), Tile(0, 2), Tile(2, 2), Tile(3, 3)]);
     }
 
     #[test]
@@ -1,5 +1,6 @@
 use super::deployment::deploy_unit;
 use super::map::VillageMap;
+use super::replay::Replay;
 use super::selection::dispatch_object_pressed;
 use super::tile_set::TILE_HALF_HEIGHT;
 use super::tile_set::TILE_WIDTH;
@@ -22,13 +23,9 @@ impl Plugin for PickingPlugin {
             .add_systems(
                 Update,
                 (
-                    pointer_coords_to_world_camera_coords,
-                    world_camera_picked_point_to_tile_coords,
-                    pick_tile,
                     dispatch_pressed_tile,
                     dispatch_object_pressed,
                     deploy_unit.run_if(in_state(GameState::Deployment)),
-                    touch_tile,
                 )
                     .chain()
                     .run_if(in_state(Screen::Playing)),
@@ -36,6 +33,29 @@ impl Plugin for PickingPlugin {
     }
 }
 
+/// Picks the [`PickedTile`] from the cursor and touches on the primary window.
+///
+/// Headless apps leave this out and write [`PickedTile`] themselves.
+pub struct PointerPickingPlugin;
+
+impl Plugin for PointerPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                pointer_coords_to_world_camera_coords,
+                world_camera_picked_point_to_tile_coords,
+                pick_tile,
+                touch_tile,
+            )
+                .chain()
+                .before(dispatch_pressed_tile)
+                .run_if(in_state(Screen::Playing).or_else(in_state(Screen::Editor)))
+                .run_if(not(resource_exists::<Replay>)),
+        );
+    }
+}
+
 #[derive(Component)]
 pub struct PickableTile;
 
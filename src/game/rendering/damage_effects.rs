@@ -0,0 +1,105 @@
+//! Screen shake and floating numbers for damage and healing.
+
+use bevy::color::palettes::tailwind::{GREEN_400, RED_500, SLATE_300};
+use bevy::prelude::*;
+use bevy_trauma_shake::TraumaCommands;
+
+use crate::game::actors::damage::{DamageDealt, DamageSystems, Healed};
+use crate::screen::Screen;
+
+const FLOATING_NUMBER_DURATION: f32 = 1.0;
+const FLOATING_NUMBER_RISE: f32 = 120.;
+const FLOATING_NUMBER_OFFSET: f32 = 300.;
+
+pub struct DamageEffectsPlugin;
+
+impl Plugin for DamageEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (shake_on_damage, spawn_floating_numbers).after(DamageSystems::Apply),
+                animate_floating_numbers,
+            )
+                .run_if(in_state(Screen::Playing)),
+        );
+    }
+}
+
+/// Rises above the actor and fades out.
+#[derive(Component, Debug)]
+pub struct FloatingNumber {
+    start: Vec3,
+    timer: Timer,
+}
+
+fn shake_on_damage(mut commands: Commands, mut damage_dealt: EventReader<DamageDealt>) {
+    if damage_dealt.read().any(|dealt| 0 < dealt.amount) {
+        commands.add_trauma(0.5);
+    }
+}
+
+fn spawn_floating_numbers(
+    mut commands: Commands,
+    mut damage_dealt: EventReader<DamageDealt>,
+    mut healed: EventReader<Healed>,
+    q_transforms: Query<&Transform>,
+) {
+    let damage = damage_dealt.read().map(|dealt| {
+        let label = match (dealt.blocked, dealt.amount, dealt.absorbed) {
+            (true, ..) => ("Blocked".to_string(), SLATE_300),
+            (false, 0, 1..) => ("Absorbed".to_string(), SLATE_300),
+            (false, amount, _) => (format!("-{amount}"), RED_500),
+        };
+        (dealt.target, label)
+    });
+    let healing = healed
+        .read()
+        .filter(|healed| 0 < healed.amount)
+        .map(|healed| (healed.target, (format!("+{}", healed.amount), GREEN_400)));
+
+    for (target, (text, color)) in damage.chain(healing).collect::<Vec<_>>() {
+        let Ok(transform) = q_transforms.get(target) else {
+            continue;
+        };
+        let start = transform.translation + Vec3::new(0., FLOATING_NUMBER_OFFSET, 100.);
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    text,
+                    TextStyle {
+                        font_size: 80.,
+                        color: color.into(),
+                        ..default()
+                    },
+                ),
+                transform: Transform::from_translation(start),
+                ..default()
+            },
+            FloatingNumber {
+                start,
+                timer: Timer::from_seconds(FLOATING_NUMBER_DURATION, TimerMode::Once),
+            },
+            StateScoped(Screen::Playing),
+        ));
+    }
+}
+
+fn animate_floating_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut q_numbers: Query<(Entity, &mut FloatingNumber, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut number, mut transform, mut text) in q_numbers.iter_mut() {
+        number.timer.tick(time.delta());
+        if number.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let t = number.timer.fraction();
+        transform.translation = number.start + FLOATING_NUMBER_RISE * t * Vec3::Y;
+        for section in text.sections.iter_mut() {
+            section.style.color.set_alpha(1. - t);
+        }
+    }
+}
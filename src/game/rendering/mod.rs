@@ -1,17 +1,27 @@
+pub mod damage_effects;
+
+use super::actors::intent::EnemyIntent;
+use super::actors::stats::Armour;
 use super::actors::stats::Health;
-use super::components::ArcherTower;
+use super::actors::status::StatusEffects;
 use super::components::GroundTileLayer;
 use super::constants;
 use super::constants::CURSOR_COLOR;
+use super::constants::ENEMY_INTENT_COLOR;
+use super::constants::ENEMY_TARGET_COLOR;
 use super::game_params::Game;
+use super::level::Terrain;
 use super::map::VillageMap;
 use super::picking::PickableTile;
 use super::picking::PickedTile;
 use super::selection::SelectedActor;
 use super::selection::SelectedTiles;
+use super::tile_set::footprint_translation;
 use super::tile_set::tile_coord_translation;
 use super::tile_set::TileSet;
 use super::tile_set::TILE_ANCHOR;
+use super::tower::{Garrison, TOWER_RANGE};
+use crate::path_finding::line_of_sight::projectile_path;
 use crate::path_finding::tiles::Tile;
 use crate::path_finding::tiles::TileCorner;
 use crate::path_finding::tiles::TileEdge;
@@ -22,7 +32,9 @@ use crate::ui::icon_set::IconSet;
 use bevy::color::palettes::css::RED;
 use bevy::color::palettes::tailwind::BLUE_300;
 use bevy::color::palettes::tailwind::PURPLE_400;
+use bevy::color::palettes::tailwind::SLATE_300;
 use bevy::color::palettes::tailwind::YELLOW_300;
+use bevy::ecs::system::EntityCommands;
 use bevy::math::vec2;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
@@ -59,7 +71,9 @@ impl Plugin for MapRenderingPlugin {
             .add_systems(
                 PostUpdate,
                 (
-                    spawn_arrow_sprites.run_if(in_state(GameState::BattleTurn)),
+                    (spawn_arrow_sprites, draw_enemy_intents)
+                        .before(draw_terrain)
+                        .run_if(in_state(GameState::BattleTurn)),
                     draw_terrain,
                     spawn_selected_tiles
                         .run_if(|layers: Res<ShowLayers>| layers.show_selected_area),
@@ -189,7 +203,11 @@ fn spawn_tile_coord_labels(mut commands: Commands, map: Res<VillageMap>) {
     }
 }
 
-fn spawn_tile_cursor(mut commands: Commands, picked_tile: Res<PickedTile>, tile_set: Res<TileSet>) {
+pub fn spawn_tile_cursor(
+    mut commands: Commands,
+    picked_tile: Res<PickedTile>,
+    tile_set: Res<TileSet>,
+) {
     let image = tile_set.get("border");
     if let Some(tile) = picked_tile.0 {
         commands.spawn((
@@ -215,7 +233,7 @@ fn spawn_arrow_sprites(
     mut commands: Commands,
     selected: Res<SelectedActor>,
     village_map: Res<VillageMap>,
-    query: Query<&Tile, With<ArcherTower>>,
+    query: Query<&Tile, With<Garrison>>,
     asset_server: Res<AssetServer>,
     picked_tile: Res<PickedTile>,
     mut tile_tints: ResMut<TileTints>,
@@ -235,20 +253,14 @@ fn spawn_arrow_sprites(
         return;
     };
 
-    let Some(mut line_iterator) = tile.get_line_through(picked_tile) else {
-        return;
-    };
-
-    let (flip_x, flip_y) = match edge {
-        TileEdge::North => (true, false),
-        TileEdge::East => (false, false),
-        TileEdge::South => (false, true),
-        TileEdge::West => (true, true),
-    };
+    let (flip_x, flip_y) = arrow_flip(edge);
 
-    while let Some(cursor) = line_iterator
-        .next()
-        .filter(|&cursor| village_map.bounds().contains(cursor))
+    let path = projectile_path(tile, edge.direction(), TOWER_RANGE, |cursor| {
+        village_map.actors.is_occupied(cursor)
+    });
+    for cursor in path
+        .into_iter()
+        .take_while(|&cursor| village_map.bounds().contains(cursor))
     {
         tile_tints
             .0
@@ -290,6 +302,76 @@ fn spawn_arrow_sprites(
     }
 }
 
+/// Flip the arrow sprite to point across this edge.
+fn arrow_flip(edge: TileEdge) -> (bool, bool) {
+    match edge {
+        TileEdge::North => (true, false),
+        TileEdge::East => (false, false),
+        TileEdge::South => (false, true),
+        TileEdge::West => (true, true),
+    }
+}
+
+/// Show where every enemy moves and which tile it attacks once the player ends the turn.
+fn draw_enemy_intents(
+    mut commands: Commands,
+    q_intents: Query<&EnemyIntent>,
+    tile_set: Res<TileSet>,
+    asset_server: Res<AssetServer>,
+    mut tile_tints: ResMut<TileTints>,
+) {
+    let arrow_image = asset_server.load("tiles/arrow.png");
+    let border_image = tile_set.get("border");
+    for intent in q_intents.iter() {
+        for step in intent.path.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            tile_tints.0.insert(to, ENEMY_INTENT_COLOR.into());
+            // Diagonal steps only get their tile tinted
+            let Some(edge) = from.find_direction_edge(to) else {
+                continue;
+            };
+            let (flip_x, flip_y) = arrow_flip(edge);
+            // Half way between the two tiles
+            let middle = 0.5 * (from.to_ivec2() + to.to_ivec2()).as_vec2();
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: ENEMY_INTENT_COLOR.into(),
+                        flip_x,
+                        flip_y,
+                        ..default()
+                    },
+                    texture: arrow_image.clone(),
+                    transform: Transform {
+                        translation: tile_coord_translation(middle.x, middle.y, 1.2),
+                        scale: Vec3::new(2., 2., 1.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                TemporarySprite,
+            ));
+        }
+
+        if let Some(attack) = intent.attack {
+            tile_tints.0.insert(attack, ENEMY_TARGET_COLOR.into());
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        anchor: TILE_ANCHOR,
+                        color: ENEMY_TARGET_COLOR.into(),
+                        ..default()
+                    },
+                    texture: border_image.clone(),
+                    transform: Transform::from_translation(tile_to_camera(attack, 1.1)),
+                    ..default()
+                },
+                TemporarySprite,
+            ));
+        }
+    }
+}
+
 // fn tint_buildable(game: Game, mut tints: ResMut<TileTints>) {
 //     for tile in game.find_tiles_that_can_be_built_on() {
 //         tints.0.insert(tile, BLUE_300.into());
@@ -331,22 +413,7 @@ fn draw_terrain(
     let buildable = game.find_tiles_that_can_be_built_on();
 
     for (tile, terrain) in game.iter_terrain() {
-        commands.spawn((
-            SpriteBundle {
-                sprite: Sprite {
-                    color: tint(tile),
-                    anchor: TILE_ANCHOR,
-                    ..Default::default()
-                },
-                texture: tile_set.get_terrain(terrain),
-                transform: Transform::from_translation(tile_to_camera(tile, 0.)),
-                ..default()
-            },
-            tile,
-            PickableTile,
-            GroundTileLayer,
-            TemporarySprite,
-        ));
+        spawn_terrain_sprite(&mut commands, &tile_set, tile, terrain, tint(tile));
 
         if buildable.contains(&tile) && *state == GameState::BuildingTurn {
             commands.spawn((
@@ -367,26 +434,57 @@ fn draw_terrain(
     }
 }
 
+/// Spawn the ground sprite of a tile for this frame only.
+pub fn spawn_terrain_sprite<'a>(
+    commands: &'a mut Commands,
+    tile_set: &TileSet,
+    tile: Tile,
+    terrain: Terrain,
+    color: Color,
+) -> EntityCommands<'a> {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                anchor: TILE_ANCHOR,
+                ..Default::default()
+            },
+            texture: tile_set.get_terrain(terrain),
+            transform: Transform::from_translation(tile_to_camera(tile, 0.)),
+            ..default()
+        },
+        tile,
+        PickableTile,
+        GroundTileLayer,
+        TemporarySprite,
+    ))
+}
+
 const HEART_SIZE: Vec2 = Vec2::new(40.0, 40.0);
 const HEART_GAP: f32 = 10.0;
+const ARMOUR_PIP_SIZE: Vec2 = Vec2::new(26.0, 26.0);
+const STATUS_ICON_SIZE: Vec2 = Vec2::new(36.0, 36.0);
 
 pub fn draw_health(
-    query: Query<(Entity, &Health)>,
+    query: Query<(Entity, &Health, Option<&Armour>, Option<&StatusEffects>)>,
     icon_set: Res<IconSet>,
     mut commands: Commands,
     map: Res<VillageMap>,
 ) {
-    for (entity, health) in query.iter() {
-        let health_width = (HEART_SIZE.x + HEART_GAP) * health.max as f32 - HEART_GAP;
+    for (entity, health, armour, status_effects) in query.iter() {
+        let armour = armour.map_or(0, |armour| armour.0);
+        // Armour pips take a slot each after the hearts
+        let slots = health.max + armour;
+        let health_width = (HEART_SIZE.x + HEART_GAP) * slots as f32 - HEART_GAP;
         let x_offset = -0.5 * (health_width - HEART_SIZE.x);
         let inner_panel_size = vec2(health_width + 2. * HEART_GAP, HEART_SIZE.y + HEART_GAP);
         let outer_panel_size = inner_panel_size + 8.;
 
-        let Some(tile) = map.actors.locate(entity) else {
+        let Some(footprint) = map.actors.locate(entity) else {
             continue;
         };
 
-        let translation = tile_to_camera(tile, 10.) + 250. * Vec3::Y;
+        let translation = footprint_translation(footprint, 10.) + 250. * Vec3::Y;
 
         commands.spawn((
             SpriteBundle {
@@ -453,5 +551,52 @@ pub fn draw_health(
                 TemporarySprite,
             ));
         }
+
+        for index in health.max..slots {
+            let translation = translation
+                + x_offset * Vec3::X
+                + (HEART_SIZE.x + HEART_GAP) * index as f32 * Vec3::X
+                + Vec3::Z * 0.2;
+
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: SLATE_300.into(),
+                        custom_size: Some(ARMOUR_PIP_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(translation)
+                        .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+                    ..default()
+                },
+                TemporarySprite,
+            ));
+        }
+
+        // Status effects are shown to the right of the hearts
+        let status_x = 0.5 * outer_panel_size.x + HEART_GAP + 0.5 * STATUS_ICON_SIZE.x;
+        for (index, status_effect) in status_effects
+            .into_iter()
+            .flat_map(|s| s.iter())
+            .enumerate()
+        {
+            let translation = translation
+                + (status_x + (STATUS_ICON_SIZE.x + HEART_GAP) * index as f32) * Vec3::X
+                + Vec3::Z * 0.2;
+
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: status_effect.kind.color(),
+                        custom_size: Some(STATUS_ICON_SIZE),
+                        ..default()
+                    },
+                    texture: icon_set.get(status_effect.kind.icon()),
+                    transform: Transform::from_translation(translation),
+                    ..default()
+                },
+                TemporarySprite,
+            ));
+        }
     }
 }
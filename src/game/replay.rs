@@ -0,0 +1,398 @@
+//! Record every player decision of a game and play them back.
+//!
+//! A [`CommandLog`] together with its seed and level is enough to reproduce a whole game,
+//! the commands are fed back through the same systems that recorded them.
+//! Heroes are referred to by their index in [`PlayerActorList`] since entities differ between runs.
+
+use std::collections::VecDeque;
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::modals::tavern::TavernUpgrade;
+use crate::path_finding::tiles::Tile;
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+
+use super::actors::status::BlockAction;
+use super::actors_list::PlayerActorList;
+use super::construction::StructureType;
+use super::cycle::{EndDeployment, EndTurn, TimeOfDay};
+use super::events::EndDayTurn;
+use super::inventory::Inventory;
+use super::level::level_asset::Levels;
+use super::level::{generate_selected_level, SelectedLevel};
+use super::map::VillageMap;
+use super::picking::PickedTile;
+use super::resources::SelectedStructueType;
+use super::rng::{seed_game_rng, GameRng};
+use super::selection::SelectedActor;
+use super::trap::TrapType;
+use super::undo::UndoHeroAction;
+
+/// Updates between two replayed commands, enough for their state transitions to apply.
+pub const REPLAY_STEP_UPDATES: usize = 2;
+
+#[cfg(not(target_family = "wasm"))]
+const LAST_REPLAY_PATH: &str = "replays/last.json";
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandLog>()
+            .add_event::<ReplayButtonPress>()
+            .add_systems(
+                OnEnter(Screen::Playing),
+                start_command_log
+                    .after(seed_game_rng)
+                    .after(generate_selected_level),
+            )
+            .add_systems(
+                PreUpdate,
+                replay_commands
+                    .after(InputSystem)
+                    .after(bevy::ui::UiSystem::Focus)
+                    .run_if(in_state(Screen::Playing).and_then(resource_exists::<Replay>)),
+            );
+    }
+}
+
+/// Writes the [`CommandLog`] to `replays/last.json` at the start of each building turn
+/// and when the game ends.
+///
+/// Headless apps leave this out.
+pub struct ReplayFilePlugin;
+
+impl Plugin for ReplayFilePlugin {
+    fn build(&self, _app: &mut App) {
+        #[cfg(not(target_family = "wasm"))]
+        _app.add_systems(
+            OnEnter(GameState::BuildingTurn),
+            write_last_replay.run_if(in_state(Screen::Playing)),
+        )
+        .add_systems(OnExit(Screen::Playing), write_last_replay);
+    }
+}
+
+/// A single player decision.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum GameCommand {
+    Build {
+        structure: StructureType,
+        tile: Tile,
+        #[serde(default)]
+        rotated: bool,
+    },
+    PlaceTrap {
+        trap: TrapType,
+        tile: Tile,
+    },
+    Deploy {
+        hero: usize,
+        tile: Tile,
+    },
+    Move {
+        hero: usize,
+        tile: Tile,
+    },
+    /// The hero spends its action to block the next attack.
+    Block {
+        hero: usize,
+    },
+    /// Use the item in `slot` of the hero on the actor standing on `target`.
+    UseItem {
+        hero: usize,
+        slot: usize,
+        target: Tile,
+    },
+    /// Man the tower on `tile` with the hero, or toggle its villager garrison without one.
+    Garrison {
+        hero: Option<usize>,
+        tile: Tile,
+    },
+    /// Shoot the arrows of the manned tower on `tower` at the actor standing on `target`.
+    TowerShot {
+        tower: Tile,
+        target: Tile,
+    },
+    OpenMerchant,
+    /// Buy one of the items offered by the merchant.
+    Buy {
+        hero: usize,
+        offer: usize,
+    },
+    Sell {
+        hero: usize,
+        slot: usize,
+    },
+    OpenTavern,
+    Upgrade {
+        hero: usize,
+        upgrade: TavernUpgrade,
+    },
+    Recruit,
+    /// Leave the merchant or the tavern.
+    CloseModal,
+    EndTurn,
+    /// End the deployment and start the battle.
+    Fight,
+    /// Take back the last hero action of this turn.
+    Undo,
+}
+
+/// Every command of the current game in the order they were made.
+#[derive(Resource, Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct CommandLog {
+    pub seed: u64,
+    pub level: Option<String>,
+    pub commands: Vec<GameCommand>,
+}
+
+impl CommandLog {
+    pub fn record(&mut self, command: GameCommand) {
+        self.commands.push(command);
+    }
+
+    pub fn to_json(&self) -> Result<String, ReplayError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ReplayError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("Could not access replay file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not (de)serialize replay using serde: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+pub fn read_command_log(path: impl AsRef<std::path::Path>) -> Result<CommandLog, ReplayError> {
+    CommandLog::from_json(&std::fs::read_to_string(path)?)
+}
+
+/// Commands left to play back, removed once all of them are played.
+///
+/// Pointer picking is disabled while a replay is running.
+#[derive(Resource, Debug, Default)]
+pub struct Replay {
+    commands: VecDeque<GameCommand>,
+    /// Tile to click once the item picked by [`GameCommand::UseItem`] shows its range.
+    target: Option<Tile>,
+    wait: usize,
+}
+
+impl Replay {
+    pub fn new(log: &CommandLog) -> Self {
+        Self {
+            commands: log.commands.iter().copied().collect(),
+            ..default()
+        }
+    }
+}
+
+/// Press the modal button matching this command, sent while replaying.
+///
+/// The hero of the command is already selected.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayButtonPress(pub GameCommand);
+
+fn start_command_log(
+    mut command_log: ResMut<CommandLog>,
+    game_rng: Res<GameRng>,
+    levels: Res<Levels>,
+    selected_level: Res<SelectedLevel>,
+) {
+    *command_log = CommandLog {
+        seed: game_rng.seed(),
+        level: levels
+            .selected(&selected_level)
+            .map(|level| level.name.clone()),
+        commands: Vec::new(),
+    };
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn write_last_replay(command_log: Res<CommandLog>) {
+    let write = || -> Result<(), ReplayError> {
+        let path = std::path::Path::new(LAST_REPLAY_PATH);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, command_log.to_json()?)?;
+        Ok(())
+    };
+    if let Err(err) = write() {
+        error!("Unable to write replay: {err}");
+    }
+}
+
+/// Events sent by [`replay_commands`] in place of the buttons a player would press.
+#[derive(SystemParam)]
+pub struct ReplayEvents<'w> {
+    end_turn: EventWriter<'w, EndTurn>,
+    day_turn: EventWriter<'w, EndDayTurn>,
+    end_deployment: EventWriter<'w, EndDeployment>,
+    button_press: EventWriter<'w, ReplayButtonPress>,
+    undo: EventWriter<'w, UndoHeroAction>,
+    block: EventWriter<'w, BlockAction>,
+}
+
+/// Turn the next command into the same input a player would give.
+pub fn replay_commands(
+    mut commands: Commands,
+    mut replay: ResMut<Replay>,
+    game_state: Res<State<GameState>>,
+    time_of_day: Res<State<TimeOfDay>>,
+    player_actor_list: Res<PlayerActorList>,
+    village_map: Res<VillageMap>,
+    mut mouse_button: ResMut<ButtonInput<MouseButton>>,
+    mut picked_tile: ResMut<PickedTile>,
+    mut selected_structure_type: ResMut<SelectedStructueType>,
+    mut selected_actor: ResMut<SelectedActor>,
+    mut q_inventories: Query<&mut Inventory>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut events: ReplayEvents,
+) {
+    if *game_state.get() == GameState::EnemyTurn {
+        return;
+    }
+    if 0 < replay.wait {
+        replay.wait -= 1;
+        return;
+    }
+
+    let mut click = |tile: Tile| {
+        picked_tile.0 = Some(tile);
+        mouse_button.reset(MouseButton::Left);
+        mouse_button.press(MouseButton::Left);
+    };
+
+    if let Some(target) = replay.target.take() {
+        click(target);
+        replay.wait = REPLAY_STEP_UPDATES;
+        return;
+    }
+    let Some(command) = replay.commands.pop_front() else {
+        commands.remove_resource::<Replay>();
+        return;
+    };
+    replay.wait = REPLAY_STEP_UPDATES;
+
+    let hero = |hero: usize| player_actor_list.0.get(hero).copied();
+    match command {
+        GameCommand::Build {
+            structure, tile, ..
+        } => {
+            selected_structure_type.0 = Some(structure);
+            events.button_press.send(ReplayButtonPress(command));
+            click(tile);
+        }
+        GameCommand::PlaceTrap { tile, .. } => {
+            selected_structure_type.0 = None;
+            events.button_press.send(ReplayButtonPress(command));
+            replay.target = Some(tile);
+        }
+        GameCommand::Deploy { hero: index, tile } | GameCommand::Move { hero: index, tile } => {
+            if let Some(entity) = hero(index) {
+                selected_actor.set(entity);
+            }
+            click(tile);
+        }
+        GameCommand::UseItem {
+            hero: index,
+            slot,
+            target,
+        } => {
+            if let Some(entity) = hero(index) {
+                selected_actor.set(entity);
+                if let Ok(mut inventory) = q_inventories.get_mut(entity) {
+                    inventory.selected_item = Some(slot);
+                }
+            }
+            replay.target = Some(target);
+        }
+        GameCommand::Block { hero: index } => {
+            if let Some(entity) = hero(index) {
+                selected_actor.set(entity);
+            }
+            events.block.send(BlockAction);
+        }
+        GameCommand::Garrison { hero: index, tile } => {
+            selected_structure_type.0 = None;
+            if let Some(entity) = index.and_then(hero) {
+                selected_actor.set(entity);
+            }
+            click(tile);
+        }
+        GameCommand::TowerShot { tower, target } => {
+            if let Some(entity) = village_map.actors.get(tower) {
+                selected_actor.set(entity);
+                if let Ok(mut inventory) = q_inventories.get_mut(entity) {
+                    inventory.selected_item = Some(0);
+                }
+            }
+            replay.target = Some(target);
+        }
+        GameCommand::OpenMerchant => next_game_state.set(GameState::Merchant),
+        GameCommand::OpenTavern => next_game_state.set(GameState::Tavern),
+        GameCommand::Buy { hero: index, .. }
+        | GameCommand::Sell { hero: index, .. }
+        | GameCommand::Upgrade { hero: index, .. } => {
+            if let Some(entity) = hero(index) {
+                selected_actor.set(entity);
+            }
+            events.button_press.send(ReplayButtonPress(command));
+        }
+        GameCommand::Recruit | GameCommand::CloseModal => {
+            events.button_press.send(ReplayButtonPress(command));
+        }
+        GameCommand::EndTurn => {
+            events.end_turn.send(EndTurn);
+            if *time_of_day.get() == TimeOfDay::Day {
+                events.day_turn.send(EndDayTurn);
+            }
+        }
+        GameCommand::Fight => {
+            events.end_deployment.send(EndDeployment);
+        }
+        GameCommand::Undo => {
+            events.undo.send(UndoHeroAction);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_log_round_trip() {
+        let log = CommandLog {
+            seed: 3,
+            level: Some("riverside".to_string()),
+            commands: vec![
+                GameCommand::Build {
+                    structure: StructureType::House,
+                    tile: Tile(2, 3),
+                    rotated: false,
+                },
+                GameCommand::Upgrade {
+                    hero: 1,
+                    upgrade: TavernUpgrade::AddHealth,
+                },
+                GameCommand::EndTurn,
+            ],
+        };
+        assert_eq!(CommandLog::from_json(&log.to_json().unwrap()).unwrap(), log);
+    }
+}
@@ -44,6 +44,10 @@ impl std::fmt::Display for VillagePopulation {
 #[derive(Resource, Default)]
 pub struct SelectedStructueType(pub Option<StructureType>);
 
+/// Structures covering several tiles are built rotated a quarter turn.
+#[derive(Resource, Default)]
+pub struct SelectedStructureRotation(pub bool);
+
 #[derive(Resource, Default)]
 pub struct VillageEmployment(pub u32);
 
@@ -0,0 +1,89 @@
+//! Single source of randomness for gameplay, so a run can be replayed from its seed.
+//!
+//! Cosmetic randomness like picking a sound effect variant does not use [`GameRng`].
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+use crate::screen::Screen;
+
+pub struct GameRngPlugin;
+
+impl Plugin for GameRngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameSeed>()
+            .init_resource::<GameRng>()
+            .add_systems(OnEnter(Screen::Playing), seed_game_rng);
+    }
+}
+
+/// Seed of the next run, a new seed is picked for every run if none is set.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameSeed(pub Option<u64>);
+
+/// Seeded random number generator for everything that affects gameplay.
+#[derive(Resource, Debug, Clone)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Seed of the current run, include this in bug reports.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(0)
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+pub fn seed_game_rng(game_seed: Res<GameSeed>, mut game_rng: ResMut<GameRng>) {
+    let seed = game_seed.0.unwrap_or_else(rand::random);
+    info!("Game seed: {seed}");
+    *game_rng = GameRng::from_seed(seed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_same_seed_same_numbers() {
+        let mut a = GameRng::from_seed(5);
+        let mut b = GameRng::from_seed(5);
+        let a: Vec<u32> = (0..10).map(|_| a.gen_range(0..100)).collect();
+        let b: Vec<u32> = (0..10).map(|_| b.gen_range(0..100)).collect();
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,624 @@
+//! Persist a running village and restore it from the title screen.
+//!
+//! Native builds write a json file, web builds use the browser's local storage.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::path_finding::tiles::{Tile, TileDim};
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+use crate::ui::icon_set::IconSet;
+
+use super::actors::player::{add_starting_player_units, spawn_player_unit};
+use super::actors::stats::{ActorName, Armour, Health, Movement};
+use super::actors::{AvailableActorNames, Structure};
+use super::actors_list::PlayerActorList;
+use super::components::{ConstructionWorkers, RemainingConstructionTurns};
+use super::construction::{spawn_building_site, spawn_structure, BuildingSite, StructureType};
+use super::cycle::{reset_cycle, Season, Turn};
+use super::inventory::{find_item_template, Inventory, Item, MaxInventorySize};
+use super::level::{load_level, SelectedLevel, Terrain, UnlockedLevels};
+use super::map::{Footprint, VillageMap};
+use super::resources::{VillageEmployment, VillageGold, VillagePopulation};
+use super::tile_set::TileSet;
+use super::tower::{man_tower, Garrison};
+use super::trap::{spawn_trap, Trap, TrapType};
+use super::victory::{reset_run_stats, RunStats};
+
+/// Version of the save format, bump this whenever older saves would no longer read the same.
+///
+/// New fields with `#[serde(default)]` keep older saves readable and don't need a bump.
+pub const SAVE_VERSION: u32 = 2;
+
+#[cfg(not(target_family = "wasm"))]
+const SAVE_FILE_PATH: &str = "saves/village.json";
+#[cfg(target_family = "wasm")]
+const SAVE_STORAGE_KEY: &str = "bevy_jam_5/village";
+/// Unlocked levels are kept apart from the village, they outlast every run.
+#[cfg(not(target_family = "wasm"))]
+const PROGRESS_FILE_PATH: &str = "saves/progress.json";
+#[cfg(target_family = "wasm")]
+const PROGRESS_STORAGE_KEY: &str = "bevy_jam_5/progress";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingSave>().add_systems(
+            OnEnter(Screen::Playing),
+            restore_save
+                .after(load_level)
+                .after(add_starting_player_units)
+                .after(reset_cycle)
+                .after(reset_run_stats),
+        );
+    }
+}
+
+/// Writes the village to storage at the start of each building turn.
+///
+/// Headless apps leave this out.
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::BuildingTurn),
+            save_village
+                .pipe(autosave)
+                .run_if(in_state(Screen::Playing)),
+        );
+    }
+}
+
+/// Save that will be restored the next time [`Screen::Playing`] is entered.
+#[derive(Resource, Default)]
+pub struct PendingSave(pub Option<SaveGame>);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SaveGame {
+    pub version: u32,
+    /// Level the village was started on, its zones are reused when restoring.
+    #[serde(default)]
+    pub level: Option<String>,
+    pub size: TileDim,
+    /// Terrain of every tile on the map in row-major order.
+    pub terrain: Vec<Terrain>,
+    pub gold: u32,
+    pub population: u32,
+    pub employment: u32,
+    pub turn: u32,
+    pub season: Season,
+    /// Heroes in [`PlayerActorList`] order.
+    pub heroes: Vec<SavedHero>,
+    pub structures: Vec<SavedStructure>,
+    pub building_sites: Vec<SavedBuildingSite>,
+    #[serde(default)]
+    pub traps: Vec<SavedTrap>,
+    #[serde(default)]
+    pub run_stats: RunStats,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedHero {
+    pub name: String,
+    pub health: Health,
+    #[serde(default)]
+    pub armour: Armour,
+    pub movement: Movement,
+    pub max_inventory_size: u32,
+    pub inventory: Vec<Option<SavedItem>>,
+}
+
+/// Items are stored by template name so that rebalancing a template applies to old saves.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedItem {
+    pub name: String,
+    pub item_count: u32,
+}
+
+impl SavedItem {
+    pub fn from_item(item: &Item) -> Self {
+        Self {
+            name: item.name.to_string(),
+            item_count: item.item_count,
+        }
+    }
+
+    pub fn to_item(&self) -> Option<Item> {
+        find_item_template(&self.name).map(|item| Item {
+            item_count: self.item_count,
+            ..item
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedStructure {
+    pub tile: Tile,
+    pub structure_type: StructureType,
+    /// Tiles covered from `tile`, as the structure stands on the map.
+    pub size: TileDim,
+    pub health: Health,
+    /// Manned by villagers, heroes leave their towers before the village is saved.
+    #[serde(default)]
+    pub garrisoned: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedBuildingSite {
+    pub tile: Tile,
+    pub structure_type: StructureType,
+    pub size: TileDim,
+    pub remaining_turns: u32,
+    pub workers: u32,
+}
+
+/// Traps are armed again at dawn, so they are restored armed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedTrap {
+    pub tile: Tile,
+    pub trap_type: TrapType,
+}
+
+/// Only the version is read first so that saves from other versions give a useful error.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+impl SaveGame {
+    pub fn to_json(&self) -> Result<String, SaveError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SaveError> {
+        let header = serde_json::from_str::<SaveHeader>(json)?;
+        if header.version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(header.version));
+        }
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn terrain_at(&self, tile: Tile) -> Option<Terrain> {
+        if tile.x() < 0 || self.size.x() <= tile.x() || tile.y() < 0 {
+            return None;
+        }
+        let index = tile.x() + tile.y() * self.size.x();
+        self.terrain.get(index as usize).copied()
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("No save found")]
+    NotFound,
+    #[error("Could not access save file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not (de)serialize save using serde: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("Unsupported save version {0}, expected {SAVE_VERSION}")]
+    UnsupportedVersion(u32),
+    #[error("Local storage is unavailable")]
+    StorageUnavailable,
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn has_save() -> bool {
+    std::path::Path::new(SAVE_FILE_PATH).exists()
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn write_save(save: &SaveGame) -> Result<(), SaveError> {
+    let path = std::path::Path::new(SAVE_FILE_PATH);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, save.to_json()?)?;
+    Ok(())
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn read_save() -> Result<SaveGame, SaveError> {
+    match std::fs::read_to_string(SAVE_FILE_PATH) {
+        Ok(json) => SaveGame::from_json(&json),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(SaveError::NotFound),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn write_progress(unlocked_levels: &UnlockedLevels) -> Result<(), SaveError> {
+    let path = std::path::Path::new(PROGRESS_FILE_PATH);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string(unlocked_levels)?)?;
+    Ok(())
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn read_progress() -> Result<UnlockedLevels, SaveError> {
+    match std::fs::read_to_string(PROGRESS_FILE_PATH) {
+        Ok(json) => Ok(serde_json::from_str(&json)?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(SaveError::NotFound),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(target_family = "wasm")]
+fn local_storage() -> Result<web_sys::Storage, SaveError> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or(SaveError::StorageUnavailable)
+}
+
+#[cfg(target_family = "wasm")]
+pub fn has_save() -> bool {
+    local_storage()
+        .ok()
+        .and_then(|storage| storage.get_item(SAVE_STORAGE_KEY).ok().flatten())
+        .is_some()
+}
+
+#[cfg(target_family = "wasm")]
+pub fn write_save(save: &SaveGame) -> Result<(), SaveError> {
+    local_storage()?
+        .set_item(SAVE_STORAGE_KEY, &save.to_json()?)
+        .map_err(|_| SaveError::StorageUnavailable)
+}
+
+#[cfg(target_family = "wasm")]
+pub fn read_save() -> Result<SaveGame, SaveError> {
+    let json = local_storage()?
+        .get_item(SAVE_STORAGE_KEY)
+        .map_err(|_| SaveError::StorageUnavailable)?
+        .ok_or(SaveError::NotFound)?;
+    SaveGame::from_json(&json)
+}
+
+#[cfg(target_family = "wasm")]
+pub fn write_progress(unlocked_levels: &UnlockedLevels) -> Result<(), SaveError> {
+    local_storage()?
+        .set_item(
+            PROGRESS_STORAGE_KEY,
+            &serde_json::to_string(unlocked_levels)?,
+        )
+        .map_err(|_| SaveError::StorageUnavailable)
+}
+
+#[cfg(target_family = "wasm")]
+pub fn read_progress() -> Result<UnlockedLevels, SaveError> {
+    let json = local_storage()?
+        .get_item(PROGRESS_STORAGE_KEY)
+        .map_err(|_| SaveError::StorageUnavailable)?
+        .ok_or(SaveError::NotFound)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Snapshot of the running village, written to disk at the start of each building turn.
+pub fn save_village(
+    village_map: Res<VillageMap>,
+    selected_level: Res<SelectedLevel>,
+    gold: Res<VillageGold>,
+    population: Res<VillagePopulation>,
+    employment: Res<VillageEmployment>,
+    turn: Res<Turn>,
+    season: Res<Season>,
+    player_actor_list: Res<PlayerActorList>,
+    run_stats: Res<RunStats>,
+    q_heroes: Query<(
+        &ActorName,
+        &Health,
+        &Armour,
+        &Movement,
+        &Inventory,
+        &MaxInventorySize,
+    )>,
+    q_structures: Query<
+        (Entity, &StructureType, &Health, Option<&Garrison>),
+        (With<Structure>, Without<BuildingSite>),
+    >,
+    q_building_sites: Query<
+        (
+            Entity,
+            &StructureType,
+            &RemainingConstructionTurns,
+            &ConstructionWorkers,
+        ),
+        With<BuildingSite>,
+    >,
+    q_traps: Query<&Trap>,
+) -> SaveGame {
+    let heroes = player_actor_list
+        .0
+        .iter()
+        .filter_map(|entity| q_heroes.get(*entity).ok())
+        .map(
+            |(name, health, armour, movement, inventory, max_inventory_size)| SavedHero {
+                name: name.0.clone(),
+                health: *health,
+                armour: *armour,
+                movement: *movement,
+                max_inventory_size: max_inventory_size.0,
+                inventory: inventory
+                    .slots()
+                    .map(|slot| slot.as_ref().map(SavedItem::from_item))
+                    .collect(),
+            },
+        )
+        .collect();
+
+    let structures = q_structures
+        .iter()
+        .filter_map(|(entity, structure_type, health, garrison)| {
+            village_map
+                .actors
+                .locate(entity)
+                .map(|footprint| SavedStructure {
+                    tile: footprint.origin,
+                    structure_type: *structure_type,
+                    size: footprint.size,
+                    health: *health,
+                    garrisoned: garrison == Some(&Garrison::Villagers),
+                })
+        })
+        .collect();
+
+    let building_sites = q_building_sites
+        .iter()
+        .filter_map(|(entity, structure_type, remaining, workers)| {
+            village_map
+                .actors
+                .locate(entity)
+                .map(|footprint| SavedBuildingSite {
+                    tile: footprint.origin,
+                    structure_type: *structure_type,
+                    size: footprint.size,
+                    remaining_turns: remaining.0,
+                    workers: workers.0,
+                })
+        })
+        .collect();
+
+    let traps = village_map
+        .traps
+        .iter()
+        .filter_map(|(tile, entity)| {
+            q_traps.get(*entity).ok().map(|trap| SavedTrap {
+                tile: *tile,
+                trap_type: trap.trap_type,
+            })
+        })
+        .collect();
+
+    SaveGame {
+        version: SAVE_VERSION,
+        level: selected_level.0.clone(),
+        size: village_map.size(),
+        terrain: village_map
+            .bounds()
+            .into_iter()
+            .map(|tile| village_map.get_terrain(tile).unwrap_or_default())
+            .collect(),
+        gold: gold.0,
+        population: population.0,
+        employment: employment.0,
+        turn: turn.0,
+        season: *season,
+        heroes,
+        structures,
+        building_sites,
+        traps,
+        run_stats: run_stats.clone(),
+    }
+}
+
+/// Save the village at the start of each building turn.
+fn autosave(In(save): In<SaveGame>) {
+    match write_save(&save) {
+        Ok(()) => info!("Saved village on turn {}", save.turn),
+        Err(err) => error!("Unable to save village: {err}"),
+    }
+}
+
+/// Replace the freshly started village with the one from [`PendingSave`].
+///
+/// The terrain is already restored by [`load_level`].
+fn restore_save(
+    mut commands: Commands,
+    mut pending_save: ResMut<PendingSave>,
+    mut village_map: ResMut<VillageMap>,
+    mut player_actor_list: ResMut<PlayerActorList>,
+    mut available_names: ResMut<AvailableActorNames>,
+    mut gold: ResMut<VillageGold>,
+    mut population: ResMut<VillagePopulation>,
+    mut employment: ResMut<VillageEmployment>,
+    mut turn: ResMut<Turn>,
+    mut season: ResMut<Season>,
+    mut run_stats: ResMut<RunStats>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    tile_set: Res<TileSet>,
+    icon_set: Res<IconSet>,
+) {
+    let Some(save) = pending_save.0.take() else {
+        return;
+    };
+
+    for entity in player_actor_list.0.drain(..) {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for hero in save.heroes {
+        available_names.0.retain(|name| *name != hero.name);
+        let id = spawn_player_unit(&mut commands, hero.name.clone());
+        let item_slots = hero
+            .inventory
+            .iter()
+            .map(|slot| {
+                slot.as_ref().and_then(|saved_item| {
+                    let item = saved_item.to_item();
+                    if item.is_none() {
+                        warn!("Dropping unknown item from save: {}", saved_item.name);
+                    }
+                    item
+                })
+            })
+            .collect();
+        commands.entity(id).insert((
+            hero.health,
+            hero.armour,
+            hero.movement,
+            MaxInventorySize(hero.max_inventory_size),
+            Inventory::from_slots(item_slots),
+        ));
+        player_actor_list.0.push(id);
+    }
+
+    for structure in save.structures {
+        let footprint = Footprint::new(structure.tile, structure.size);
+        let id = spawn_structure(
+            &mut commands,
+            &tile_set,
+            footprint,
+            structure.structure_type,
+        );
+        commands.entity(id).insert(structure.health);
+        if structure.garrisoned {
+            man_tower(&mut commands, id, Garrison::Villagers);
+        }
+        village_map.actors.place(footprint, id);
+    }
+
+    for site in save.building_sites {
+        let footprint = Footprint::new(site.tile, site.size);
+        let id = spawn_building_site(
+            &mut commands,
+            &tile_set,
+            footprint,
+            site.structure_type,
+            site.remaining_turns,
+            site.workers,
+        );
+        village_map.actors.place(footprint, id);
+    }
+
+    for trap in save.traps {
+        let id = spawn_trap(&mut commands, &icon_set, trap.tile, trap.trap_type);
+        village_map.traps.insert(trap.tile, id);
+    }
+
+    gold.0 = save.gold;
+    population.0 = save.population;
+    employment.0 = save.employment;
+    turn.0 = save.turn;
+    *season = save.season;
+    *run_stats = save.run_stats;
+    next_game_state.set(GameState::BuildingTurn);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::inventory::ITEM_TEMPLATES;
+
+    fn test_save() -> SaveGame {
+        SaveGame {
+            version: SAVE_VERSION,
+            level: Some("debug_level".to_string()),
+            size: TileDim(2, 2),
+            terrain: vec![
+                Terrain::Grass,
+                Terrain::Water,
+                Terrain::Gravel,
+                Terrain::Grass,
+            ],
+            gold: 120,
+            population: 30,
+            employment: 10,
+            turn: 20,
+            season: Season::Autumn,
+            heroes: vec![SavedHero {
+                name: "Dorian Blackwood".to_string(),
+                health: Health { value: 1, max: 3 },
+                armour: Armour(1),
+                movement: Movement(4),
+                max_inventory_size: 3,
+                inventory: vec![Some(SavedItem::from_item(&ITEM_TEMPLATES[0])), None],
+            }],
+            structures: vec![SavedStructure {
+                tile: Tile(0, 0),
+                structure_type: StructureType::Tavern,
+                size: TileDim(1, 2),
+                health: Health::new(2),
+                garrisoned: false,
+            }],
+            building_sites: vec![SavedBuildingSite {
+                tile: Tile(1, 1),
+                structure_type: StructureType::House,
+                size: TileDim::ONE,
+                remaining_turns: 2,
+                workers: 10,
+            }],
+            traps: vec![SavedTrap {
+                tile: Tile(1, 0),
+                trap_type: TrapType::BearTrap,
+            }],
+            run_stats: RunStats {
+                nights_survived: 4,
+                enemies_killed: 7,
+                gold_earned: 90,
+                ..default()
+            },
+        }
+    }
+
+    #[test]
+    fn save_round_trip() {
+        let save = test_save();
+        let json = save.to_json().unwrap();
+        assert_eq!(SaveGame::from_json(&json).unwrap(), save);
+    }
+
+    #[test]
+    fn save_rejects_other_versions() {
+        let save = SaveGame {
+            version: SAVE_VERSION + 1,
+            ..test_save()
+        };
+        let json = save.to_json().unwrap();
+        assert!(matches!(
+            SaveGame::from_json(&json),
+            Err(SaveError::UnsupportedVersion(version)) if version == SAVE_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn save_terrain_lookup() {
+        let save = test_save();
+        assert_eq!(save.terrain_at(Tile(1, 0)), Some(Terrain::Water));
+        assert_eq!(save.terrain_at(Tile(0, 1)), Some(Terrain::Gravel));
+        assert_eq!(save.terrain_at(Tile(2, 0)), None);
+    }
+
+    #[test]
+    fn saved_item_restores_count() {
+        let item = SavedItem {
+            name: "bow".to_string(),
+            item_count: 1,
+        }
+        .to_item()
+        .unwrap();
+        assert_eq!(item.name, "bow");
+        assert_eq!(item.item_count, 1);
+        assert!(SavedItem {
+            name: "unknown".to_string(),
+            item_count: 1,
+        }
+        .to_item()
+        .is_none());
+    }
+}
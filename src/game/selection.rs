@@ -1,3 +1,4 @@
+use super::components::Abilities;
 use super::components::GroundTileLayer;
 use super::deployment::deploy_unit;
 
@@ -86,7 +87,7 @@ fn color_selected_tiles(
 }
 
 pub fn show_movement_range(
-    q_movements: Query<(&Movement, &ActorTurnState)>,
+    q_movements: Query<(&Movement, &ActorTurnState, &Abilities)>,
     q_enemies: Query<(), With<EnemyActor>>,
     selected_unit: Res<SelectedActor>,
     mut selected_tiles: ResMut<SelectedTiles>,
@@ -95,23 +96,26 @@ pub fn show_movement_range(
     let Some(entity) = selected_unit.entity else {
         return;
     };
-    let (Some(tile), Ok((movement, turn_state))) =
-        (village_map.actors.locate(entity), q_movements.get(entity))
-    else {
+    let (Some(tile), Ok((movement, turn_state, abilities))) = (
+        village_map
+            .actors
+            .locate(entity)
+            .map(|footprint| footprint.origin),
+        q_movements.get(entity),
+    ) else {
         return;
     };
 
-    if turn_state.used_move {
+    if !turn_state.can_move(abilities) {
         selected_tiles.tiles.clear();
         return;
     }
 
-    let tiles = village_map.flood(tile, movement.0, &TileDir::EDGES, false);
+    let tiles = village_map.flood(tile, movement.0, &TileDir::EDGES, abilities);
     selected_tiles.tiles = tiles;
     match q_enemies.contains(entity) {
         true => selected_tiles.color = css::INDIAN_RED.into(),
         false => {
-            // match turn_state.used_move
             selected_tiles.color = css::LIME.into();
         }
     }
@@ -3,6 +3,7 @@ use bevy::sprite::Anchor;
 use bevy::utils::HashMap;
 
 use super::level::Terrain;
+use super::map::Footprint;
 
 /// Width of a tile.
 pub const TILE_WIDTH: f32 = 256.0;
@@ -32,30 +33,31 @@ impl Plugin for TileSetPlugin {
     }
 }
 
+/// Names of every tile loaded into the [`TileSet`].
+pub const TILE_NAMES: &[&str] = &[
+    "edge",
+    "grassblock",
+    "gravelblock",
+    "waterblock",
+    "house1",
+    "blacksmith",
+    "human",
+    "werewolf",
+    "slime",
+    "bat",
+    "border_thick",
+    "border",
+    "tower",
+    "tavern",
+    "ne_corner",
+    "se_corner",
+    "block_blue",
+    "block_grey",
+    "block_orange",
+];
+
 fn load_tiles(asset_server: Res<AssetServer>, mut tile_set: ResMut<TileSet>) {
-    const TILES: &[&str] = &[
-        "edge",
-        "grassblock",
-        "gravelblock",
-        "waterblock",
-        "house1",
-        "blacksmith",
-        "human",
-        "werewolf",
-        "slime",
-        "bat",
-        "border_thick",
-        "border",
-        "tower",
-        "tavern",
-        "ne_corner",
-        "se_corner",
-        "block_blue",
-        "block_grey",
-        "block_orange",
-    ];
-
-    for &tile in TILES {
+    for &tile in TILE_NAMES {
         info!("Loading tile: {}", tile);
         tile_set.insert(tile, asset_server.load(format!("tiles/{}.png", tile)));
     }
@@ -71,6 +73,17 @@ pub fn tile_coord_translation(x: f32, y: f32, layer: f32) -> Vec3 {
     translation
 }
 
+/// Convert a footprint to world translation, centred between its tiles.
+///
+/// Depth is taken from the tile nearest to the camera so the whole actor is drawn in front of
+/// everything standing behind any of its tiles.
+pub fn footprint_translation(footprint: Footprint, layer: f32) -> Vec3 {
+    let (origin, max) = (footprint.origin, footprint.max());
+    let near = tile_coord_translation(max.x() as f32, max.y() as f32, layer);
+    let far = tile_coord_translation(origin.x() as f32, origin.y() as f32, layer);
+    far.lerp(near, 0.5).xy().extend(near.z)
+}
+
 #[derive(Resource, Default, Debug)]
 pub struct TileSet(HashMap<&'static str, Handle<Image>>);
 
@@ -92,11 +105,6 @@ impl TileSet {
     }
 
     pub fn get_terrain(&self, terrain: Terrain) -> Handle<Image> {
-        let image_name = match terrain {
-            Terrain::Grass => "grassblock",
-            Terrain::Gravel => "gravelblock",
-            Terrain::Water => "waterblock",
-        };
-        self.get(image_name)
+        self.get(terrain.tile_name())
     }
 }
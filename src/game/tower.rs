@@ -0,0 +1,243 @@
+//! Archer towers shoot during the battle turn once they are manned.
+//!
+//! Villagers man a tower during the day by clicking it, they are drawn from the free
+//! [`VillagePopulation`] and stay until clicked again. A hero can man an unmanned tower
+//! instead of being deployed, they leave it again at dawn.
+//!
+//! A manned tower is given an [`Inventory`] holding [`TOWER_ARROWS`] so it is aimed with
+//! the same item targeting as the heroes, once per turn.
+
+use bevy::prelude::*;
+
+use crate::path_finding::tiles::{Tile, TileDir};
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+
+use super::actors::damage::{ActorDied, DamageSystems};
+use super::actors::ActorTurnState;
+use super::actors_list::PlayerActorList;
+use super::components::{Abilities, ArcherTower};
+use super::inventory::{Inventory, Item};
+use super::map::VillageMap;
+use super::replay::{CommandLog, GameCommand};
+use super::resources::{SelectedStructueType, VillageEmployment, VillagePopulation};
+use super::selection::{
+    dispatch_object_pressed, set_selected_unit, ObjectPressedEvent, SelectedActor,
+};
+use super::tile_set::{tile_coord_translation, TileSet, TILE_ANCHOR};
+
+/// Villagers needed to man a tower.
+pub const TOWER_GARRISON_VILLAGERS: u32 = 5;
+/// Tiles a tower shoots along each of its lines.
+pub const TOWER_RANGE: u32 = 4;
+
+/// Shot by manned towers, restocked every battle turn.
+pub const TOWER_ARROWS: Item = Item {
+    name: "bow",
+    description: "Tower arrows, fly straight along the tower's lines. (Land & Air)",
+    health_effect: -1,
+    item_count: 1,
+    directions: &TileDir::EDGES,
+    range: TOWER_RANGE,
+    ranged: true,
+    cost: 0,
+    air: true,
+    armour: 0,
+    status: None,
+    push: 0,
+};
+
+pub struct TowerPlugin;
+
+impl Plugin for TowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                garrison_villagers
+                    .after(dispatch_object_pressed)
+                    .run_if(in_state(GameState::BuildingTurn)),
+                garrison_hero
+                    .after(dispatch_object_pressed)
+                    .before(set_selected_unit)
+                    .run_if(in_state(GameState::Deployment)),
+                abandon_fallen_towers.after(DamageSystems::Apply),
+            )
+                .run_if(in_state(Screen::Playing)),
+        )
+        .add_systems(OnEnter(GameState::BattleTurn), restock_tower_arrows)
+        .add_systems(
+            OnEnter(GameState::BuildingTurn),
+            release_heroes.run_if(in_state(Screen::Playing)),
+        );
+    }
+}
+
+/// Who mans an archer tower, towers without a garrison can't shoot.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Garrison {
+    Villagers,
+    Hero(Entity),
+}
+
+/// Heroes manning a tower count as deployed.
+pub fn is_deployed(
+    entity: Entity,
+    village_map: &VillageMap,
+    q_garrisons: &Query<&Garrison>,
+) -> bool {
+    village_map.actors.locate(entity).is_some()
+        || q_garrisons
+            .iter()
+            .any(|garrison| *garrison == Garrison::Hero(entity))
+}
+
+/// Give the tower its garrison and the arrows to shoot with.
+pub fn man_tower(commands: &mut Commands, tower: Entity, garrison: Garrison) {
+    commands.entity(tower).insert((
+        garrison,
+        Inventory::from_slots(vec![Some(TOWER_ARROWS)]),
+        ActorTurnState::default(),
+        Abilities::default(),
+    ));
+}
+
+fn leave_tower(commands: &mut Commands, tower: Entity) {
+    commands
+        .entity(tower)
+        .remove::<(Garrison, Inventory, ActorTurnState, Abilities)>();
+}
+
+fn garrison_villagers(
+    mut commands: Commands,
+    mut events: EventReader<ObjectPressedEvent>,
+    q_towers: Query<(Option<&Garrison>, &Tile), With<ArcherTower>>,
+    selected_structure_type: Res<SelectedStructueType>,
+    population: Res<VillagePopulation>,
+    mut employment: ResMut<VillageEmployment>,
+    mut command_log: ResMut<CommandLog>,
+) {
+    let Some(ObjectPressedEvent(tower)) = events.read().last().copied() else {
+        return;
+    };
+    // Clicks are for building while a structure is picked.
+    if selected_structure_type.0.is_some() {
+        return;
+    }
+    let Ok((garrison, tile)) = q_towers.get(tower) else {
+        return;
+    };
+
+    match garrison {
+        None if employment.0 + TOWER_GARRISON_VILLAGERS <= population.0 => {
+            employment.0 += TOWER_GARRISON_VILLAGERS;
+            man_tower(&mut commands, tower, Garrison::Villagers);
+        }
+        Some(Garrison::Villagers) => {
+            employment.0 = employment.0.saturating_sub(TOWER_GARRISON_VILLAGERS);
+            leave_tower(&mut commands, tower);
+        }
+        _ => return,
+    }
+    command_log.record(GameCommand::Garrison {
+        hero: None,
+        tile: *tile,
+    });
+}
+
+fn garrison_hero(
+    mut commands: Commands,
+    mut events: EventReader<ObjectPressedEvent>,
+    q_towers: Query<&Tile, (With<ArcherTower>, Without<Garrison>)>,
+    q_garrisons: Query<&Garrison>,
+    selected_unit: Res<SelectedActor>,
+    player_unit_list: Res<PlayerActorList>,
+    village_map: Res<VillageMap>,
+    tile_set: Res<TileSet>,
+    mut command_log: ResMut<CommandLog>,
+) {
+    let Some(ObjectPressedEvent(tower)) = events.read().last().copied() else {
+        return;
+    };
+    let Ok(tile) = q_towers.get(tower) else {
+        return;
+    };
+    let Some(hero) = selected_unit
+        .entity
+        .filter(|hero| player_unit_list.0.contains(hero))
+    else {
+        return;
+    };
+    if is_deployed(hero, &village_map, &q_garrisons) {
+        return;
+    }
+
+    // Hidden until the hero has to climb down from a fallen tower.
+    commands.entity(hero).insert((
+        SpriteBundle {
+            sprite: Sprite {
+                anchor: TILE_ANCHOR,
+                ..default()
+            },
+            transform: Transform::from_translation(tile_coord_translation(
+                tile.x() as f32,
+                tile.y() as f32,
+                2.0,
+            )),
+            texture: tile_set.get("human"),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ));
+    man_tower(&mut commands, tower, Garrison::Hero(hero));
+    if let Some(index) = player_unit_list.index_of(hero) {
+        command_log.record(GameCommand::Garrison {
+            hero: Some(index),
+            tile: *tile,
+        });
+    }
+}
+
+fn restock_tower_arrows(mut q_towers: Query<&mut Inventory, With<Garrison>>) {
+    for mut inventory in q_towers.iter_mut() {
+        inventory.set(0, TOWER_ARROWS);
+    }
+}
+
+/// Heroes leave their towers at dawn, villagers stay.
+fn release_heroes(mut commands: Commands, q_towers: Query<(Entity, &Garrison)>) {
+    for (tower, garrison) in q_towers.iter() {
+        if let Garrison::Hero(_) = garrison {
+            leave_tower(&mut commands, tower);
+        }
+    }
+}
+
+/// The garrison of a destroyed tower flees, a hero is left standing in the rubble.
+fn abandon_fallen_towers(
+    mut events: EventReader<ActorDied>,
+    q_towers: Query<(&Garrison, &Tile)>,
+    mut q_heroes: Query<(&mut Transform, &mut Visibility)>,
+    mut village_map: ResMut<VillageMap>,
+    mut employment: ResMut<VillageEmployment>,
+) {
+    for ActorDied { entity, .. } in events.read() {
+        let Ok((garrison, tile)) = q_towers.get(*entity) else {
+            continue;
+        };
+        match *garrison {
+            Garrison::Villagers => {
+                employment.0 = employment.0.saturating_sub(TOWER_GARRISON_VILLAGERS);
+            }
+            Garrison::Hero(hero) => {
+                village_map.actors.set(*tile, hero);
+                if let Ok((mut transform, mut visibility)) = q_heroes.get_mut(hero) {
+                    transform.translation =
+                        tile_coord_translation(tile.x() as f32, tile.y() as f32, 2.0);
+                    *visibility = Visibility::Inherited;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Traps are laid during the day and go off when an enemy walks onto them at night.
+//!
+//! Traps live on their own layer, [`VillageMap::traps`], so a trapped tile can still be
+//! entered. Enemy movement ([`EnemyMovement`]) sends [`TrapTriggered`] whenever an enemy's [`TilePath`] enters
+//! a trapped tile. Consumable traps are used up, the others are re-armed at dawn.
+//! Airborne enemies fly over traps.
+
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::path_finding::tiles::{Tile, TileDir};
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+use crate::ui::icon_set::IconSet;
+use crate::ui::prelude::InteractionPalette;
+
+use super::actors::damage::{DamageEvent, DamageSystems, DamageType};
+use super::actors::enemy::{EnemyMovement, TilePath};
+use super::actors::status::{StatusEffect, StatusKind};
+use super::actors::{EnemyActor, IsAirborne};
+use super::components::Blacksmith;
+use super::cycle::TimeOfDay;
+use super::map::VillageMap;
+use super::picking::TilePressedEvent;
+use super::replay::{replay_commands, CommandLog, GameCommand, Replay, ReplayButtonPress};
+use super::resources::{SelectedStructueType, VillageGold};
+use super::tile_set::tile_coord_translation;
+
+/// Size of a trap icon on its tile.
+const TRAP_ICON_SIZE: Vec2 = Vec2::splat(96.0);
+
+pub struct TrapPlugin;
+
+impl Plugin for TrapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedTrapType>()
+            .add_event::<TrapTriggered>()
+            .add_systems(
+                Update,
+                (
+                    deselect_trap_type.run_if(resource_changed::<SelectedStructueType>),
+                    place_trap.run_if(in_state(GameState::BuildingTurn)),
+                    trigger_traps
+                        .after(EnemyMovement)
+                        .before(DamageSystems::Collect),
+                    tint_disarmed_traps,
+                )
+                    .run_if(in_state(Screen::Playing)),
+            )
+            .add_systems(
+                PreUpdate,
+                press_replayed_trap_buttons
+                    .after(replay_commands)
+                    .run_if(in_state(Screen::Playing).and_then(resource_exists::<Replay>)),
+            )
+            .add_systems(
+                OnEnter(GameState::BuildingTurn),
+                rearm_traps.run_if(in_state(Screen::Playing)),
+            )
+            .add_systems(OnExit(TimeOfDay::Day), |mut s: ResMut<SelectedTrapType>| {
+                s.0 = None;
+            });
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum TrapType {
+    /// Snaps shut on an enemy and holds it in place.
+    BearTrap,
+    /// Blows up, hurting every enemy on and around its tile. Used up when it goes off.
+    Landmine,
+    /// A pressure plate that shoots a crossbow bolt at the enemy stepping on it.
+    Crossbow,
+}
+
+impl TrapType {
+    pub const ALL: [Self; 3] = [Self::BearTrap, Self::Landmine, Self::Crossbow];
+
+    pub fn name(&self) -> &str {
+        match self {
+            TrapType::BearTrap => "bear trap",
+            TrapType::Landmine => "landmine",
+            TrapType::Crossbow => "crossbow",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            TrapType::BearTrap => "claw_mark",
+            TrapType::Landmine => "fire_potion",
+            TrapType::Crossbow => "bow",
+        }
+    }
+
+    pub fn gold(&self) -> u32 {
+        match self {
+            TrapType::BearTrap => 10,
+            TrapType::Landmine => 25,
+            TrapType::Crossbow => 20,
+        }
+    }
+
+    pub fn damage(&self) -> u32 {
+        match self {
+            TrapType::BearTrap => 1,
+            TrapType::Landmine | TrapType::Crossbow => 2,
+        }
+    }
+
+    /// Removed once it went off instead of being re-armed.
+    pub fn is_consumable(&self) -> bool {
+        *self == TrapType::Landmine
+    }
+
+    /// Only a village with a finished blacksmith can lay these.
+    pub fn needs_blacksmith(&self) -> bool {
+        matches!(self, TrapType::Landmine | TrapType::Crossbow)
+    }
+}
+
+/// Trap picked in the building panel, placed by clicking a tile.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectedTrapType(pub Option<TrapType>);
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trap {
+    pub trap_type: TrapType,
+    /// Disarmed traps don't go off until they are re-armed.
+    pub armed: bool,
+}
+
+#[derive(Component)]
+pub struct TrapButton(pub TrapType);
+
+/// Sent when an enemy walks onto a trapped tile.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TrapTriggered {
+    pub trap: Entity,
+    pub target: Entity,
+}
+
+/// Traps go on empty walkable tiles without another trap.
+pub fn can_place_trap(village_map: &VillageMap, tile: Tile) -> bool {
+    !village_map.is_out_of_bounds(tile)
+        && village_map
+            .get_terrain(tile)
+            .is_some_and(|terrain| terrain.is_walkable())
+        && village_map.actors.get(tile).is_none()
+        && !village_map.traps.contains_key(&tile)
+}
+
+/// Spawn an armed trap, the caller is responsible for placing it on the map.
+pub fn spawn_trap(
+    commands: &mut Commands,
+    icon_set: &IconSet,
+    tile: Tile,
+    trap_type: TrapType,
+) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(TRAP_ICON_SIZE),
+                    ..default()
+                },
+                texture: icon_set.get(trap_type.icon()),
+                transform: Transform::from_translation(tile_coord_translation(
+                    tile.x() as f32,
+                    tile.y() as f32,
+                    1.5,
+                )),
+                ..default()
+            },
+            tile,
+            Trap {
+                trap_type,
+                armed: true,
+            },
+            StateScoped(Screen::Playing),
+        ))
+        .id()
+}
+
+pub fn trap_btn_interaction(
+    q_interactions: Query<(&Interaction, &TrapButton), Changed<Interaction>>,
+    mut selected_trap_type: ResMut<SelectedTrapType>,
+    mut selected_structure: ResMut<SelectedStructueType>,
+) {
+    for (interaction, button) in q_interactions.iter() {
+        if *interaction == Interaction::Pressed {
+            selected_trap_type.0 = Some(button.0);
+            selected_structure.0 = None;
+        }
+    }
+}
+
+pub fn update_trap_buttons(
+    selected_trap_type: Res<SelectedTrapType>,
+    mut q_buttons: Query<(&TrapButton, &mut InteractionPalette, &mut BackgroundColor)>,
+) {
+    for (button, mut palette, mut color) in q_buttons.iter_mut() {
+        if selected_trap_type.0 == Some(button.0) {
+            palette.none = css::RED.into();
+        } else {
+            palette.none = Color::BLACK;
+            color.0 = Color::BLACK;
+        }
+    }
+}
+
+/// Picking a structure puts the trap back.
+fn deselect_trap_type(
+    selected_structure_type: Res<SelectedStructueType>,
+    mut selected_trap_type: ResMut<SelectedTrapType>,
+) {
+    if selected_structure_type.0.is_some() && selected_trap_type.0.is_some() {
+        selected_trap_type.0 = None;
+    }
+}
+
+fn place_trap(
+    mut commands: Commands,
+    mut events: EventReader<TilePressedEvent>,
+    selected_trap_type: Res<SelectedTrapType>,
+    q_blacksmiths: Query<(), With<Blacksmith>>,
+    mut village_map: ResMut<VillageMap>,
+    mut gold: ResMut<VillageGold>,
+    icon_set: Res<IconSet>,
+    mut command_log: ResMut<CommandLog>,
+) {
+    let Some(TilePressedEvent(tile)) = events.read().last().copied() else {
+        return;
+    };
+    let Some(trap_type) = selected_trap_type.0 else {
+        return;
+    };
+    if trap_type.needs_blacksmith() && q_blacksmiths.is_empty() {
+        return;
+    }
+    if gold.0 < trap_type.gold() || !can_place_trap(&village_map, tile) {
+        return;
+    }
+
+    gold.0 -= trap_type.gold();
+    let id = spawn_trap(&mut commands, &icon_set, tile, trap_type);
+    village_map.traps.insert(tile, id);
+    command_log.record(GameCommand::PlaceTrap {
+        trap: trap_type,
+        tile,
+    });
+}
+
+fn trigger_traps(
+    mut commands: Commands,
+    mut events: EventReader<TrapTriggered>,
+    mut q_traps: Query<(&mut Trap, &Tile)>,
+    mut q_paths: Query<&mut TilePath>,
+    q_airborne: Query<(), With<IsAirborne>>,
+    q_enemies: Query<(), With<EnemyActor>>,
+    mut village_map: ResMut<VillageMap>,
+    mut damage_evt: EventWriter<DamageEvent>,
+) {
+    for TrapTriggered { trap, target } in events.read() {
+        let Ok((mut trap_state, tile)) = q_traps.get_mut(*trap) else {
+            continue;
+        };
+        if !trap_state.armed || q_airborne.contains(*target) {
+            continue;
+        }
+        let trap_type = trap_state.trap_type;
+        let hit = |target| DamageEvent::new(None, target, trap_type.damage(), DamageType::Physical);
+
+        match trap_type {
+            TrapType::BearTrap => {
+                damage_evt
+                    .send(hit(*target).with_status(Some(StatusEffect::new(StatusKind::Webbed, 2))));
+                // The enemy is already placed at the end of its path, it can only be held
+                // here while nobody else claimed the tile.
+                let is_free = village_map
+                    .actors
+                    .get(*tile)
+                    .is_none_or(|entity| entity == *target);
+                if let (true, Ok(mut path)) = (is_free, q_paths.get_mut(*target)) {
+                    let end = path.index + 1;
+                    path.path.truncate(end);
+                    path.attack = None;
+                    village_map.actors.set(*tile, *target);
+                }
+            }
+            TrapType::Landmine => {
+                let mut targets = vec![*target];
+                for around in TileDir::ALL
+                    .into_iter()
+                    .map(|direction| tile.step(direction))
+                    .chain([*tile])
+                {
+                    // Heroes and structures nearby are spared.
+                    if let Some(entity) = village_map
+                        .actors
+                        .get(around)
+                        .filter(|entity| q_enemies.contains(*entity) && !targets.contains(entity))
+                    {
+                        targets.push(entity);
+                    }
+                }
+                damage_evt.send_batch(targets.into_iter().map(hit));
+            }
+            TrapType::Crossbow => {
+                damage_evt.send(hit(*target));
+            }
+        }
+
+        if trap_type.is_consumable() {
+            village_map.traps.remove(tile);
+            commands.entity(*trap).despawn_recursive();
+        } else {
+            trap_state.armed = false;
+        }
+    }
+}
+
+fn rearm_traps(mut q_traps: Query<&mut Trap>) {
+    for mut trap in q_traps.iter_mut() {
+        trap.armed = true;
+    }
+}
+
+fn tint_disarmed_traps(mut q_traps: Query<(&Trap, &mut Sprite), Changed<Trap>>) {
+    for (trap, mut sprite) in q_traps.iter_mut() {
+        sprite.color = match trap.armed {
+            true => Color::WHITE,
+            false => Color::WHITE.with_alpha(0.4),
+        };
+    }
+}
+
+fn press_replayed_trap_buttons(
+    mut events: EventReader<ReplayButtonPress>,
+    mut selected_trap_type: ResMut<SelectedTrapType>,
+) {
+    for ReplayButtonPress(command) in events.read() {
+        if let GameCommand::PlaceTrap { trap, .. } = command {
+            selected_trap_type.0 = Some(*trap);
+        }
+    }
+}
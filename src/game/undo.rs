@@ -0,0 +1,203 @@
+//! Take back hero moves and item uses until the battle turn ends.
+//!
+//! After every hero action the state of all actors on the map is pushed on the [`UndoStack`],
+//! undoing pops it and restores the state below. Kills are final,
+//! once an enemy dies the actions before it can no longer be undone.
+
+use bevy::prelude::*;
+
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+
+use super::actors::damage::{ActorDied, DamageSystems};
+use super::actors::player::move_unit;
+use super::actors::stats::{Armour, Health, Movement};
+use super::actors::status::{inflict_status, StatusEffects};
+use super::actors::ActorTurnState;
+use super::inventory::Inventory;
+use super::item::{apply_item_effect, InventorySelection};
+use super::map::{Footprint, VillageMap};
+use super::replay::{CommandLog, GameCommand};
+use super::selection::SelectedTiles;
+use super::tile_set::tile_coord_translation;
+
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UndoStack>()
+            .add_event::<HeroActed>()
+            .add_event::<UndoHeroAction>()
+            .add_systems(OnEnter(GameState::BattleTurn), start_undo_stack)
+            .add_systems(
+                OnExit(GameState::BattleTurn),
+                |mut undo_stack: ResMut<UndoStack>| undo_stack.0.clear(),
+            )
+            .add_systems(
+                Update,
+                (
+                    push_undo_step
+                        .after(move_unit)
+                        .after(apply_item_effect)
+                        .after(DamageSystems::Apply)
+                        .after(inflict_status),
+                    undo_hero_action,
+                )
+                    .chain()
+                    .run_if(in_state(Screen::Playing).and_then(in_state(GameState::BattleTurn))),
+            );
+    }
+}
+
+/// Sent after a hero moved or used an item.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct HeroActed;
+
+/// Take back the last hero action of this turn.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct UndoHeroAction;
+
+type SnapshotData = (
+    Entity,
+    &'static mut Health,
+    Option<&'static mut Armour>,
+    Option<&'static mut Movement>,
+    Option<&'static mut StatusEffects>,
+    Option<&'static mut ActorTurnState>,
+    Option<&'static mut Inventory>,
+    &'static mut Transform,
+);
+
+#[derive(Debug, Clone)]
+struct ActorSnapshot {
+    entity: Entity,
+    footprint: Footprint,
+    health: Health,
+    armour: Option<Armour>,
+    movement: Option<Movement>,
+    status_effects: Option<StatusEffects>,
+    turn_state: Option<ActorTurnState>,
+    inventory: Option<Inventory>,
+}
+
+/// State of the map at the start of the turn followed by the state after each hero action.
+#[derive(Resource, Default, Debug)]
+pub struct UndoStack(Vec<Vec<ActorSnapshot>>);
+
+impl UndoStack {
+    pub fn can_undo(&self) -> bool {
+        1 < self.0.len()
+    }
+}
+
+fn take_snapshot(village_map: &VillageMap, q_actors: &Query<SnapshotData>) -> Vec<ActorSnapshot> {
+    q_actors
+        .iter()
+        .filter(|(_, health, ..)| !health.is_empty())
+        .filter_map(
+            |(entity, health, armour, movement, status_effects, turn_state, inventory, _)| {
+                Some(ActorSnapshot {
+                    entity,
+                    footprint: village_map.actors.locate(entity)?,
+                    health: *health,
+                    armour: armour.copied(),
+                    movement: movement.copied(),
+                    status_effects: status_effects.cloned(),
+                    turn_state: turn_state.cloned(),
+                    inventory: inventory.cloned(),
+                })
+            },
+        )
+        .collect()
+}
+
+fn start_undo_stack(
+    mut undo_stack: ResMut<UndoStack>,
+    village_map: Res<VillageMap>,
+    q_actors: Query<SnapshotData>,
+) {
+    undo_stack.0 = vec![take_snapshot(&village_map, &q_actors)];
+}
+
+fn push_undo_step(
+    mut events: EventReader<HeroActed>,
+    mut actor_died: EventReader<ActorDied>,
+    mut undo_stack: ResMut<UndoStack>,
+    village_map: Res<VillageMap>,
+    q_actors: Query<SnapshotData>,
+) {
+    let killed = actor_died.read().count() > 0;
+    if events.is_empty() {
+        return;
+    }
+    events.clear();
+
+    let snapshot = take_snapshot(&village_map, &q_actors);
+    if killed {
+        undo_stack.0.clear();
+    }
+    undo_stack.0.push(snapshot);
+}
+
+fn undo_hero_action(
+    mut events: EventReader<UndoHeroAction>,
+    mut undo_stack: ResMut<UndoStack>,
+    mut village_map: ResMut<VillageMap>,
+    mut q_actors: Query<SnapshotData>,
+    mut inventory_selection: ResMut<InventorySelection>,
+    mut selected_tiles: ResMut<SelectedTiles>,
+    mut command_log: ResMut<CommandLog>,
+) {
+    for _ in events.read() {
+        if !undo_stack.can_undo() {
+            continue;
+        }
+        undo_stack.0.pop();
+        let Some(step) = undo_stack.0.last() else {
+            continue;
+        };
+
+        for actor in step {
+            let Ok((
+                _,
+                mut health,
+                armour,
+                movement,
+                status_effects,
+                turn_state,
+                inventory,
+                mut transform,
+            )) = q_actors.get_mut(actor.entity)
+            else {
+                continue;
+            };
+            health.set_if_neq(actor.health);
+            if let (Some(mut armour), Some(saved)) = (armour, actor.armour) {
+                *armour = saved;
+            }
+            if let (Some(mut movement), Some(saved)) = (movement, actor.movement) {
+                *movement = saved;
+            }
+            if let (Some(mut status_effects), Some(saved)) = (status_effects, &actor.status_effects)
+            {
+                *status_effects = saved.clone();
+            }
+            if let (Some(mut turn_state), Some(saved)) = (turn_state, &actor.turn_state) {
+                *turn_state = saved.clone();
+            }
+            if let (Some(mut inventory), Some(saved)) = (inventory, &actor.inventory) {
+                *inventory = saved.clone();
+            }
+            if village_map.actors.locate(actor.entity) != Some(actor.footprint) {
+                village_map.actors.place(actor.footprint, actor.entity);
+                let tile = actor.footprint.origin;
+                transform.translation =
+                    tile_coord_translation(tile.x() as f32, tile.y() as f32, 2.);
+            }
+        }
+
+        inventory_selection.selection = None;
+        selected_tiles.tiles.clear();
+        command_log.record(GameCommand::Undo);
+    }
+}
@@ -0,0 +1,206 @@
+//! How a run ends.
+//!
+//! A level is won as soon as one of its [`VictoryConditions`] is met and lost once no structure is left.
+//! Winning a level unlocks the next one, see [`UnlockedLevels`].
+//! [`RunStats`] are tracked along the way and shown on the won screen.
+
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::screen::Screen;
+
+use super::actors::damage::{ActorDied, DamageSystems};
+use super::actors::enemy::FinalBoss;
+use super::actors::{EnemyActor, Structure};
+use super::construction::StructureType;
+use super::cycle::{Turn, DAY_PER_CYCLE, TURN_PER_DAY};
+use super::level::level_asset::Levels;
+use super::level::{SelectedLevel, UnlockedLevels};
+use super::resources::VillagePopulation;
+
+pub struct VictoryPlugin;
+
+impl Plugin for VictoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .init_resource::<VictoryConditions>()
+            .add_systems(OnEnter(Screen::Playing), reset_run_stats)
+            .add_systems(
+                Update,
+                (
+                    count_nights.run_if(resource_changed::<Turn>),
+                    count_kills.after(DamageSystems::Apply),
+                    check_victory,
+                )
+                    .chain()
+                    .run_if(in_state(Screen::Playing)),
+            )
+            .add_systems(
+                PostUpdate,
+                check_lost_status.run_if(in_state(Screen::Playing)),
+            );
+    }
+}
+
+/// A goal that wins the level once reached.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VictoryCondition {
+    /// Live through this many full cycles of seasons.
+    SurviveCycles { cycles: u32 },
+    /// Kill the boss attacking during the eclipse.
+    DefeatFinalBoss,
+    /// Grow the village to this many people.
+    Population { target: u32 },
+}
+
+impl VictoryCondition {
+    pub fn is_met(self, turn: &Turn, population: &VillagePopulation, stats: &RunStats) -> bool {
+        match self {
+            VictoryCondition::SurviveCycles { cycles } => {
+                cycles * DAY_PER_CYCLE * TURN_PER_DAY <= turn.0
+            }
+            VictoryCondition::DefeatFinalBoss => stats.final_boss_defeated,
+            VictoryCondition::Population { target } => target <= population.0,
+        }
+    }
+}
+
+impl fmt::Display for VictoryCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VictoryCondition::SurviveCycles { cycles: 1 } => write!(f, "Survive a full cycle"),
+            VictoryCondition::SurviveCycles { cycles } => write!(f, "Survive {cycles} cycles"),
+            VictoryCondition::DefeatFinalBoss => write!(f, "Defeat the final boss"),
+            VictoryCondition::Population { target } => {
+                write!(f, "Reach a population of {target}")
+            }
+        }
+    }
+}
+
+/// Conditions of the level being played, meeting any of them wins.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct VictoryConditions(pub Vec<VictoryCondition>);
+
+impl Default for VictoryConditions {
+    fn default() -> Self {
+        Self(vec![VictoryCondition::DefeatFinalBoss])
+    }
+}
+
+/// What happened during the current run, saved along with the village.
+#[derive(Resource, Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct RunStats {
+    pub nights_survived: u32,
+    pub enemies_killed: u32,
+    /// Gold from the village's daily income.
+    pub gold_earned: u32,
+    pub final_boss_defeated: bool,
+    /// The condition that won the run.
+    pub victory: Option<VictoryCondition>,
+}
+
+pub fn reset_run_stats(mut run_stats: ResMut<RunStats>) {
+    *run_stats = RunStats::default();
+}
+
+fn count_nights(turn: Res<Turn>, mut run_stats: ResMut<RunStats>) {
+    run_stats.nights_survived = turn.0 / TURN_PER_DAY;
+}
+
+fn count_kills(
+    mut commands: Commands,
+    mut actor_died: EventReader<ActorDied>,
+    q_enemies: Query<Has<FinalBoss>, With<EnemyActor>>,
+    mut run_stats: ResMut<RunStats>,
+) {
+    for died in actor_died.read() {
+        let Ok(is_final_boss) = q_enemies.get(died.entity) else {
+            continue;
+        };
+        run_stats.enemies_killed += 1;
+        if is_final_boss {
+            run_stats.final_boss_defeated = true;
+            // Lets the eclipse end.
+            commands.entity(died.entity).remove::<FinalBoss>();
+        }
+    }
+}
+
+fn check_victory(
+    victory_conditions: Res<VictoryConditions>,
+    turn: Res<Turn>,
+    population: Res<VillagePopulation>,
+    levels: Res<Levels>,
+    selected_level: Res<SelectedLevel>,
+    mut unlocked_levels: ResMut<UnlockedLevels>,
+    mut run_stats: ResMut<RunStats>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    if run_stats.victory.is_some() {
+        return;
+    }
+    let met = victory_conditions
+        .0
+        .iter()
+        .find(|condition| condition.is_met(&turn, &population, &run_stats));
+    if let Some(condition) = met {
+        run_stats.victory = Some(*condition);
+        unlocked_levels.unlock_after(&levels, &selected_level);
+        next_screen.set(Screen::Won);
+    }
+}
+
+/// Defensive structures alone do not keep the village alive.
+fn check_lost_status(
+    q_structures: Query<Option<&StructureType>, With<Structure>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    if q_structures
+        .iter()
+        .all(|structure_type| structure_type.is_some_and(StructureType::is_defensive))
+    {
+        next_screen.set(Screen::Lost);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_victory_conditions() {
+        let mut stats = RunStats::default();
+        let population = VillagePopulation(100);
+        let turn = Turn(DAY_PER_CYCLE * TURN_PER_DAY);
+
+        let survive = VictoryCondition::SurviveCycles { cycles: 1 };
+        assert!(survive.is_met(&turn, &population, &stats));
+        assert!(!survive.is_met(&Turn(turn.0 - 1), &population, &stats));
+
+        let population_target = VictoryCondition::Population { target: 150 };
+        assert!(!population_target.is_met(&turn, &population, &stats));
+        assert!(population_target.is_met(&turn, &VillagePopulation(150), &stats));
+
+        assert!(!VictoryCondition::DefeatFinalBoss.is_met(&turn, &population, &stats));
+        stats.final_boss_defeated = true;
+        assert!(VictoryCondition::DefeatFinalBoss.is_met(&turn, &population, &stats));
+    }
+
+    #[test]
+    fn test_victory_condition_json() {
+        let json =
+            r#"[{ "type": "survive_cycles", "cycles": 2 }, { "type": "defeat_final_boss" }]"#;
+        let conditions = serde_json::from_str::<Vec<VictoryCondition>>(json).unwrap();
+        assert_eq!(
+            conditions,
+            vec![
+                VictoryCondition::SurviveCycles { cycles: 2 },
+                VictoryCondition::DefeatFinalBoss,
+            ]
+        );
+    }
+}
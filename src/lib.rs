@@ -5,6 +5,7 @@ pub mod game;
 pub mod modals;
 pub mod path_finding;
 pub mod screen;
+pub mod simulation;
 pub mod ui;
 
 const BASE_APP_HEIGHT: f32 = 390.0;
@@ -18,9 +19,19 @@ use bevy::{
 };
 use bevy_trauma_shake::{Shake, TraumaPlugin};
 use debug::DebugPlugin;
+use game::level::generator::random_level_name;
+use game::level::SelectedLevel;
+use game::replay::{CommandLog, Replay};
+use game::rng::GameSeed;
 
 pub struct AppPlugin {
     pub show_debug: bool,
+    /// Level to start playing once loading is done, skipping the menus.
+    pub level: Option<String>,
+    /// Seed of the run, also plays a random village generated from it if no level is given.
+    pub seed: Option<u64>,
+    /// Play back a recorded game, its level and seed replace `level` and `seed`.
+    pub replay: Option<CommandLog>,
 }
 
 impl Plugin for AppPlugin {
@@ -65,6 +76,15 @@ impl Plugin for AppPlugin {
             app.add_plugins(DebugPlugin);
         }
 
+        let level = self.level.clone().or(self.seed.map(random_level_name));
+        app.insert_resource(SelectedLevel(level))
+            .insert_resource(GameSeed(self.seed));
+        if let Some(log) = &self.replay {
+            app.insert_resource(SelectedLevel(log.level.clone()))
+                .insert_resource(GameSeed(Some(log.seed)))
+                .insert_resource(Replay::new(log));
+        }
+
         load_internal_binary_asset!(
             app,
             TextStyle::default().font,
@@ -2,6 +2,7 @@
 #![cfg_attr(not(feature = "dev"), windows_subsystem = "windows")]
 
 use bevy::prelude::*;
+use bevy_jam_5::game::replay::read_command_log;
 use bevy_jam_5::AppPlugin;
 
 use clap::Parser;
@@ -11,14 +12,33 @@ use clap::Parser;
 struct Args {
     #[arg(short, long)]
     debug: bool,
+    /// Start playing this level right away, e.g. `debug_level`.
+    #[arg(short, long)]
+    level: Option<String>,
+    /// Seed for all gameplay randomness, plays a random village from it unless `--level` is given.
+    #[arg(short, long)]
+    seed: Option<u64>,
+    /// Play back a game recorded in this replay file, e.g. `replays/last.json`.
+    #[arg(short, long)]
+    replay: Option<String>,
 }
 
 fn main() -> AppExit {
     let args = Args::parse();
+    let replay = match args.replay.map(read_command_log).transpose() {
+        Ok(replay) => replay,
+        Err(err) => {
+            eprintln!("Unable to read replay: {err}");
+            return AppExit::error();
+        }
+    };
 
     App::new()
         .add_plugins(AppPlugin {
             show_debug: args.debug,
+            level: args.level,
+            seed: args.seed,
+            replay,
         })
         .run()
 }
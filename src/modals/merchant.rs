@@ -1,13 +1,21 @@
 use bevy::color::palettes::css;
 use bevy::prelude::*;
 use bevy::ui::FocusPolicy;
+use rand::Rng;
 use sickle_ui::prelude::*;
 
+use crate::game::actors_list::PlayerActorList;
 use crate::game::constants::TEXT_SIZE;
 use crate::game::inventory::Inventory;
 use crate::game::inventory::Item;
 use crate::game::inventory::ITEM_TEMPLATES;
+use crate::game::replay::replay_commands;
+use crate::game::replay::CommandLog;
+use crate::game::replay::GameCommand;
+use crate::game::replay::Replay;
+use crate::game::replay::ReplayButtonPress;
 use crate::game::resources::VillageGold;
+use crate::game::rng::GameRng;
 use crate::game::selection::SelectedActor;
 use crate::game::actors_list::SellItemButton;
 use crate::game::MODAL_Z_LAYER;
@@ -38,6 +46,12 @@ impl Plugin for MerchantModalPlugin {
                     item_btn_interaction,
                     buy_btn_interaction,
                 ),
+            )
+            .add_systems(
+                PreUpdate,
+                press_replayed_merchant_buttons
+                    .after(replay_commands)
+                    .run_if(in_state(GameState::Merchant).and_then(resource_exists::<Replay>)),
             );
     }
 }
@@ -73,10 +87,11 @@ fn merchant_modal_layout(
     mut commands: Commands,
     icon_set: Res<IconSet>,
     mut merchant_items: ResMut<MerchantItems>,
+    mut game_rng: ResMut<GameRng>,
 ) {
     for item in merchant_items.items.iter_mut() {
         if item.is_none() {
-            let index = rand::random::<usize>() % ITEM_TEMPLATES.len();
+            let index = game_rng.gen_range(0..ITEM_TEMPLATES.len());
             *item = Some(&ITEM_TEMPLATES[index]);
         }
     }
@@ -236,6 +251,8 @@ fn buy_btn_interaction(
     mut merchant_items: ResMut<MerchantItems>,
     mut next_game_state: ResMut<NextState<GameState>>,
     mut gold: ResMut<VillageGold>,
+    player_actor_list: Res<PlayerActorList>,
+    mut command_log: ResMut<CommandLog>,
 ) {
     let Some(entity) = selected_unit.entity else {
         return;
@@ -249,14 +266,17 @@ fn buy_btn_interaction(
 
     for interaction in q_interactions.iter() {
         if let Interaction::Pressed = interaction {
-            if let Some(selected_item) = merchant_items
-                .selection
-                .and_then(|i| merchant_items.items[i].take())
-            {
+            let Some(offer) = merchant_items.selection else {
+                continue;
+            };
+            if let Some(selected_item) = merchant_items.items[offer].take() {
                 if selected_item.cost <= gold.0 {
                     gold.0 = gold.0.saturating_sub(selected_item.cost);
                     inventory.set(slot, *selected_item);
                     next_game_state.set(GameState::BuildingTurn);
+                    if let Some(hero) = player_actor_list.index_of(entity) {
+                        command_log.record(GameCommand::Buy { hero, offer });
+                    }
                 }
             }
         }
@@ -269,6 +289,8 @@ fn sell_btn_interaction(
     mut iq: Query<&mut Inventory>,
     mut next_game_state: ResMut<NextState<GameState>>,
     mut gold: ResMut<VillageGold>,
+    player_actor_list: Res<PlayerActorList>,
+    mut command_log: ResMut<CommandLog>,
 ) {
     let Some(entity) = selected.entity else {
         return;
@@ -285,6 +307,9 @@ fn sell_btn_interaction(
         if let Interaction::Pressed = interaction {
             if let Some(item) = inventory.take(i) {
                 gold.0 += item.cost / 2;
+                if let Some(hero) = player_actor_list.index_of(entity) {
+                    command_log.record(GameCommand::Sell { hero, slot: i });
+                }
             }
         }
     }
@@ -293,10 +318,58 @@ fn sell_btn_interaction(
 fn exit_mechant_btn_interaction(
     q_interactions: Query<&Interaction, (Changed<Interaction>, With<ExitMerchantButton>)>,
     mut next_game_state: ResMut<NextState<GameState>>,
+    mut command_log: ResMut<CommandLog>,
 ) {
     for interaction in q_interactions.iter() {
         if let Interaction::Pressed = interaction {
             next_game_state.set(GameState::BuildingTurn);
+            command_log.record(GameCommand::CloseModal);
+        }
+    }
+}
+
+fn press_replayed_merchant_buttons(
+    mut events: EventReader<ReplayButtonPress>,
+    mut merchant_items: ResMut<MerchantItems>,
+    selected_unit: Res<SelectedActor>,
+    mut q_inventories: Query<&mut Inventory>,
+    mut q_buttons: Query<(
+        &mut Interaction,
+        Has<BuyButton>,
+        Has<SellItemButton>,
+        Has<ExitMerchantButton>,
+    )>,
+) {
+    // Release the buttons pressed by the previous command.
+    for (mut interaction, ..) in q_buttons.iter_mut() {
+        if *interaction == Interaction::Pressed {
+            *interaction = Interaction::None;
+        }
+    }
+
+    for ReplayButtonPress(command) in events.read() {
+        match *command {
+            GameCommand::Buy { offer, .. } => merchant_items.selection = Some(offer),
+            GameCommand::Sell { slot, .. } => {
+                if let Some(mut inventory) = selected_unit
+                    .entity
+                    .and_then(|entity| q_inventories.get_mut(entity).ok())
+                {
+                    inventory.selected_item = Some(slot);
+                }
+            }
+            _ => {}
+        }
+        for (mut interaction, buy, sell, exit) in q_buttons.iter_mut() {
+            let pressed = match command {
+                GameCommand::Buy { .. } => buy,
+                GameCommand::Sell { .. } => sell,
+                GameCommand::CloseModal => exit,
+                _ => false,
+            };
+            if pressed {
+                *interaction = Interaction::Pressed;
+            }
         }
     }
 }
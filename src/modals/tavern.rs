@@ -1,10 +1,12 @@
 use bevy::color::palettes::css;
 use bevy::prelude::*;
 use bevy::ui::FocusPolicy;
+use serde::{Deserialize, Serialize};
 use sickle_ui::prelude::*;
 
 use crate::game::actors::player::spawn_player_unit;
 use crate::game::actors::stats::ActorName;
+use crate::game::actors::stats::Armour;
 use crate::game::actors::stats::Health;
 use crate::game::actors::stats::Movement;
 use crate::game::actors::AvailableActorNames;
@@ -15,8 +17,12 @@ use crate::game::constants::TAVERN_FONT_SIZE;
 use crate::game::constants::UPGRADE_COST;
 use crate::game::inventory::Inventory;
 use crate::game::inventory::MaxInventorySize;
+use crate::game::replay::CommandLog;
+use crate::game::replay::GameCommand;
+use crate::game::replay::ReplayButtonPress;
 use crate::game::resources::VillageGold;
 use crate::game::selection::ObjectPressedEvent;
+use crate::game::selection::SelectedActor;
 use crate::game::actors_list::PlayerActorList;
 use crate::game::MODAL_Z_LAYER;
 use crate::screen::playing::GameState;
@@ -36,11 +42,12 @@ pub struct ExitTavernButton;
 pub struct TavernButton(pub Entity);
 
 /// Each can be bought twice
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TavernUpgrade {
     AddMovement,
     AddHealth,
     AddItemSlot,
+    AddArmour,
 }
 
 #[derive(Component)]
@@ -52,6 +59,9 @@ pub struct HealthLabel;
 #[derive(Component)]
 pub struct ItemSlotLabel;
 
+#[derive(Component)]
+pub struct ArmourLabel;
+
 #[derive(Component)]
 pub struct NameLabel;
 
@@ -105,7 +115,7 @@ pub fn tavern_modal_layout(
                     .border_radius(BorderRadius::all(Val::Px(16.)))
                     .background_color(Color::BLACK)
                     .width(Val::Px(480.))
-                    .height(Val::Px(600.))
+                    .height(Val::Px(640.))
                     .justify_content(JustifyContent::Center);
                 ui.column(|ui| {
                     ui.style()
@@ -248,6 +258,36 @@ pub fn tavern_modal_layout(
                                     pressed: css::INDIAN_RED.into(),
                                 });
                             });
+
+                            ui.row(|ui| {
+                                ui.label(LabelConfig::from("Armour:"))
+                                    .style()
+                                    .font_size(TAVERN_FONT_SIZE);
+                                ui.label(LabelConfig::from(""))
+                                    .insert(ArmourLabel)
+                                    .style()
+                                    .font_size(TAVERN_FONT_SIZE);
+                                ui.row(|_| {}).style().width(Val::Px(20.));
+                                ui.container(ButtonBundle::default(), |ui| {
+                                    ui.insert(TavernUpgrade::AddArmour)
+                                        .style()
+                                        .margin(UiRect::all(Val::Px(2.)))
+                                        .padding(UiRect::all(Val::Px(2.)))
+                                        .border(UiRect::all(Val::Px(2.)))
+                                        .border_color(Color::WHITE)
+                                        .justify_content(JustifyContent::Start);
+                                    ui.label(LabelConfig::from(format!(
+                                        "+armour {UPGRADE_COST} gold"
+                                    )))
+                                    .style()
+                                    .font_size(TAVERN_FONT_SIZE);
+                                })
+                                .insert(InteractionPalette {
+                                    none: css::BLACK.into(),
+                                    hovered: css::DARK_RED.into(),
+                                    pressed: css::INDIAN_RED.into(),
+                                });
+                            });
                         });
                     });
                 });
@@ -285,10 +325,12 @@ pub fn tavern_modal_layout(
 pub fn exit_tavern_btn_interaction(
     q_interactions: Query<&Interaction, (Changed<Interaction>, With<ExitTavernButton>)>,
     mut next_game_state: ResMut<NextState<GameState>>,
+    mut command_log: ResMut<CommandLog>,
 ) {
     for interaction in q_interactions.iter() {
         if let Interaction::Pressed = interaction {
             next_game_state.set(GameState::BuildingTurn);
+            command_log.record(GameCommand::CloseModal);
         }
     }
 }
@@ -298,6 +340,7 @@ pub fn enter_tavern_modal(
     query: Query<&Tavern>,
     state: Res<State<GameState>>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut command_log: ResMut<CommandLog>,
 ) {
     let Some(ObjectPressedEvent(entity)) = events.read().last().copied() else {
         return;
@@ -305,6 +348,7 @@ pub fn enter_tavern_modal(
 
     if query.contains(entity) && *state.get() == GameState::BuildingTurn {
         next_state.set(GameState::Tavern);
+        command_log.record(GameCommand::OpenTavern);
     }
 }
 
@@ -319,7 +363,7 @@ impl Default for TavernSubject {
 
 pub fn update_slot_labels(
     subject: Res<TavernSubject>,
-    query: Query<(&ActorName, &Movement, &Health, &MaxInventorySize)>,
+    query: Query<(&ActorName, &Movement, &Health, &MaxInventorySize, &Armour)>,
     mut n_query: Query<
         &mut Text,
         (
@@ -327,6 +371,7 @@ pub fn update_slot_labels(
             Without<MovementLabel>,
             Without<HealthLabel>,
             Without<ItemSlotLabel>,
+            Without<ArmourLabel>,
         ),
     >,
 
@@ -337,6 +382,7 @@ pub fn update_slot_labels(
             With<MovementLabel>,
             Without<HealthLabel>,
             Without<ItemSlotLabel>,
+            Without<ArmourLabel>,
         ),
     >,
     mut h_query: Query<
@@ -346,6 +392,7 @@ pub fn update_slot_labels(
             Without<MovementLabel>,
             With<HealthLabel>,
             Without<ItemSlotLabel>,
+            Without<ArmourLabel>,
         ),
     >,
     mut s_query: Query<
@@ -355,10 +402,21 @@ pub fn update_slot_labels(
             Without<MovementLabel>,
             Without<HealthLabel>,
             With<ItemSlotLabel>,
+            Without<ArmourLabel>,
+        ),
+    >,
+    mut a_query: Query<
+        &mut Text,
+        (
+            Without<NameLabel>,
+            Without<MovementLabel>,
+            Without<HealthLabel>,
+            Without<ItemSlotLabel>,
+            With<ArmourLabel>,
         ),
     >,
 ) {
-    if let Ok((n, m, h, s)) = query.get(subject.0) {
+    if let Ok((n, m, h, s, a)) = query.get(subject.0) {
         for mut t in n_query.iter_mut() {
             t.sections[0].value = format!("{}", n.0);
         }
@@ -371,6 +429,9 @@ pub fn update_slot_labels(
         for mut t in s_query.iter_mut() {
             t.sections[0].value = format!("{}", s.0);
         }
+        for mut t in a_query.iter_mut() {
+            t.sections[0].value = format!("{}", a.0);
+        }
     }
 }
 
@@ -378,16 +439,18 @@ pub fn upgrade_buttons(
     mut gold: ResMut<VillageGold>,
     subject: Res<TavernSubject>,
     upgrade_query: Query<(&Interaction, &TavernUpgrade), Changed<Interaction>>,
-    mut stats_query: Query<(&mut Movement, &mut Health, &mut Inventory)>,
+    mut stats_query: Query<(&mut Movement, &mut Health, &mut Inventory, &mut Armour)>,
+    player_unit_list: Res<PlayerActorList>,
+    mut command_log: ResMut<CommandLog>,
 ) {
-    let Ok((mut m, mut h, mut s)) = stats_query.get_mut(subject.0) else {
+    let Ok((mut m, mut h, mut s, mut a)) = stats_query.get_mut(subject.0) else {
         return;
     };
 
     fn upgrade(value: &mut u32) -> bool {
         let n = *value;
         *value = (*value + 1).min(5);
-        n == *value
+        n != *value
     }
 
     for (i, u) in upgrade_query.iter() {
@@ -411,8 +474,12 @@ pub fn upgrade_buttons(
                             false
                         }
                     }
+                    TavernUpgrade::AddArmour => upgrade(&mut a.0),
                 } {
                     gold.0 -= UPGRADE_COST;
+                    if let Some(hero) = player_unit_list.index_of(subject.0) {
+                        command_log.record(GameCommand::Upgrade { hero, upgrade: *u });
+                    }
                 }
             }
         }
@@ -437,6 +504,7 @@ pub fn recruit_button(
     mut player_unit_list: ResMut<PlayerActorList>,
     mut names: ResMut<AvailableActorNames>,
     t_q: Query<Entity, With<TavernActorList>>,
+    mut command_log: ResMut<CommandLog>,
 ) {
     if player_unit_list.0.len() < 5 {
         for i in r_q.iter() {
@@ -446,6 +514,7 @@ pub fn recruit_button(
                     let name = names.next_name();
                     let id = spawn_player_unit(&mut commands, name.clone());
                     player_unit_list.0.push(id);
+                    command_log.record(GameCommand::Recruit);
                     for entity in t_q.iter() {
                         spawn_hero_button(&mut commands.ui_builder(entity), id, name.clone())
                     }
@@ -454,3 +523,39 @@ pub fn recruit_button(
         }
     }
 }
+
+pub fn press_replayed_tavern_buttons(
+    mut events: EventReader<ReplayButtonPress>,
+    mut subject: ResMut<TavernSubject>,
+    selected_unit: Res<SelectedActor>,
+    mut q_buttons: Query<(
+        &mut Interaction,
+        Option<&TavernUpgrade>,
+        Has<RecruitButton>,
+        Has<ExitTavernButton>,
+    )>,
+) {
+    // Release the buttons pressed by the previous command.
+    for (mut interaction, ..) in q_buttons.iter_mut() {
+        if *interaction == Interaction::Pressed {
+            *interaction = Interaction::None;
+        }
+    }
+
+    for ReplayButtonPress(command) in events.read() {
+        if let (GameCommand::Upgrade { .. }, Some(entity)) = (command, selected_unit.entity) {
+            subject.0 = entity;
+        }
+        for (mut interaction, upgrade, recruit, exit) in q_buttons.iter_mut() {
+            let pressed = match command {
+                GameCommand::Upgrade { upgrade: u, .. } => upgrade == Some(u),
+                GameCommand::Recruit => recruit,
+                GameCommand::CloseModal => exit,
+                _ => false,
+            };
+            if pressed {
+                *interaction = Interaction::Pressed;
+            }
+        }
+    }
+}
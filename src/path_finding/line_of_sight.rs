@@ -0,0 +1,103 @@
+//! Line of sight for ranged attacks.
+//!
+//! Lines between tiles are traced with Bresenham's algorithm, a target can be seen when none of the
+//! tiles in between is blocked. Projectiles fly straight along a [`TileDir`] and stop at the first
+//! blocked tile they reach.
+
+use super::tiles::{Tile, TileDir};
+
+/// Every tile on the line from `from` to `to`, both included.
+pub fn line(from: Tile, to: Tile) -> Vec<Tile> {
+    let (dx, dy) = ((to.x() - from.x()).abs(), -(to.y() - from.y()).abs());
+    let (step_x, step_y) = ((to.x() - from.x()).signum(), (to.y() - from.y()).signum());
+    let mut error = dx + dy;
+    let mut tile = from;
+    let mut tiles = vec![from];
+    while tile != to {
+        let doubled = 2 * error;
+        if dy <= doubled {
+            error += dy;
+            tile.0 += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            tile.1 += step_y;
+        }
+        tiles.push(tile);
+    }
+    tiles
+}
+
+/// A target can be seen if no tile between it and `from` is blocked.
+pub fn has_line_of_sight(from: Tile, to: Tile, is_blocked: impl Fn(Tile) -> bool) -> bool {
+    let tiles = line(from, to);
+    let between = tiles.len().saturating_sub(2);
+    tiles
+        .into_iter()
+        .skip(1)
+        .take(between)
+        .all(|tile| !is_blocked(tile))
+}
+
+/// Tiles a projectile flies through for up to `range` tiles, ending on the first blocked tile.
+pub fn projectile_path(
+    from: Tile,
+    direction: TileDir,
+    range: u32,
+    is_blocked: impl Fn(Tile) -> bool,
+) -> Vec<Tile> {
+    let mut tiles = Vec::new();
+    let mut tile = from;
+    for _ in 0..range {
+        tile = tile.step(direction);
+        tiles.push(tile);
+        if is_blocked(tile) {
+            break;
+        }
+    }
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line() {
+        assert_eq!(line(Tile(0, 0), Tile(0, 0)), vec![Tile(0, 0)]);
+        assert_eq!(
+            line(Tile(0, 0), Tile(3, 0)),
+            vec![Tile(0, 0), Tile(1, 0), Tile(2, 0), Tile(3, 0)]
+        );
+        assert_eq!(
+            line(Tile(2, 2), Tile(0, 0)),
+            vec![Tile(2, 2), Tile(1, 1), Tile(0, 0)]
+        );
+        assert_eq!(
+            line(Tile(0, 0), Tile(4, 2)),
+            vec![Tile(0, 0), Tile(1, 1), Tile(2, 1), Tile(3, 2), Tile(4, 2)]
+        );
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let blocker = Tile(1, 0);
+        let is_blocked = |tile| tile == blocker;
+        assert!(!has_line_of_sight(Tile(0, 0), Tile(3, 0), is_blocked));
+        assert!(has_line_of_sight(Tile(0, 0), Tile(0, 3), is_blocked));
+        // The target itself doesn't block
+        assert!(has_line_of_sight(Tile(0, 0), blocker, is_blocked));
+        assert!(has_line_of_sight(Tile(0, 0), Tile(0, 0), is_blocked));
+    }
+
+    #[test]
+    fn test_projectile_stops_at_blocker() {
+        let start = Tile(0, 0);
+        let blocker = start.step(TileDir::South).step(TileDir::South);
+        let path = projectile_path(start, TileDir::South, 4, |tile| tile == blocker);
+        assert_eq!(path, vec![start.step(TileDir::South), blocker]);
+
+        let path = projectile_path(start, TileDir::North, 4, |tile| tile == blocker);
+        assert_eq!(path.len(), 4);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod line_of_sight;
 pub mod tiles;
 
 use std::cmp::Reverse;
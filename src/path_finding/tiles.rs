@@ -2,6 +2,7 @@ use std::ops::{Add, Sub};
 
 use bevy::prelude::*;
 use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
 
 #[derive(Component, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum TileDir {
@@ -186,7 +187,7 @@ impl TileDir {
     }
 }
 
-#[derive(Component, Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Component, Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Tile(pub i32, pub i32);
 
 impl Tile {
@@ -444,7 +445,7 @@ impl Path {
     }
 }
 
-#[derive(Component, Default, Copy, Clone, PartialEq, Debug, Eq, Hash)]
+#[derive(Component, Default, Copy, Clone, PartialEq, Debug, Eq, Hash, Serialize, Deserialize)]
 pub struct TileDim(pub i32, pub i32);
 
 impl TileDim {
@@ -3,10 +3,13 @@
 pub mod playing;
 
 mod credits;
+mod editor;
+mod level_select;
 mod loading;
 mod lost;
 mod splash;
 mod title;
+mod won;
 
 use bevy::prelude::*;
 
@@ -19,8 +22,11 @@ pub(super) fn plugin(app: &mut App) {
         loading::plugin,
         title::plugin,
         credits::plugin,
+        level_select::plugin,
+        editor::plugin,
         playing::plugin,
         lost::plugin,
+        won::plugin,
     ));
 }
 
@@ -32,6 +38,9 @@ pub enum Screen {
     Loading,
     Title,
     Credits,
+    LevelSelect,
+    Editor,
     Playing,
     Lost,
+    Won,
 }
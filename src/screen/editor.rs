@@ -0,0 +1,448 @@
+//! A level editor that can be accessed from the title screen.
+//!
+//! Edits a copy of the selected level and saves it as a level json file listed in the level manifest.
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use bevy::ui::Val::*;
+
+use super::Screen;
+use crate::game::constants::{CURSOR_COLOR, DEPLOYMENT_ZONE_COLOR, SPAWN_ZONE_COLOR};
+use crate::game::construction::StructureType;
+use crate::game::level::level_asset::{LevelAsset, LevelRect, Levels, SpawnZone};
+use crate::game::level::{village_camera_translation, SelectedLevel, Terrain};
+use crate::game::map::VillageMap;
+use crate::game::picking::{pick_tile, PickedTile};
+use crate::game::rendering::{spawn_terrain_sprite, spawn_tile_cursor, TemporarySprite};
+use crate::game::tile_set::{tile_coord_translation, TileSet, TILE_ANCHOR};
+use crate::path_finding::tiles::{Tile, TileDim, TileRect};
+use crate::ui::palette::LABEL_TEXT;
+use crate::ui::prelude::*;
+use crate::VillageCamera;
+
+/// Size of the level created by the "New" button.
+const NEW_LEVEL_SIZE: TileDim = TileDim(10, 10);
+
+/// Structures that can be placed in a level asset.
+const EDITOR_STRUCTURES: [StructureType; 4] = [
+    StructureType::House,
+    StructureType::Tavern,
+    StructureType::ArcherTower,
+    StructureType::Blacksmith,
+];
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Editor>();
+    app.add_systems(OnEnter(Screen::Editor), enter_editor);
+
+    app.add_systems(
+        Update,
+        (
+            handle_editor_action,
+            type_level_name,
+            edit_level.after(pick_tile),
+            fit_map_to_level.run_if(resource_changed::<EditorLevel>),
+            show_level_name
+                .run_if(resource_changed::<EditorLevel>.or_else(resource_changed::<Editor>)),
+        )
+            .chain()
+            .run_if(in_state(Screen::Editor)),
+    );
+    app.add_systems(
+        PostUpdate,
+        (draw_editor_level, spawn_tile_cursor).run_if(in_state(Screen::Editor)),
+    );
+    app.register_type::<EditorAction>();
+}
+
+/// The level being edited.
+#[derive(Resource, Debug)]
+pub struct EditorLevel(pub LevelAsset);
+
+#[derive(Resource, Default, Debug)]
+struct Editor {
+    tool: EditorTool,
+    /// First corner of the zone being marked.
+    zone_start: Option<Tile>,
+    /// Typed keys go to the level name.
+    renaming: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+enum EditorTool {
+    /// Paint terrain while the mouse button is held.
+    Terrain(Terrain),
+    Structure(StructureType),
+    Erase,
+    /// Click two corners to mark the deployment zone.
+    DeploymentZone,
+    /// Click two corners to add a spawn zone.
+    SpawnZone,
+}
+
+impl Default for EditorTool {
+    fn default() -> Self {
+        Self::Terrain(Terrain::Grass)
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum EditorAction {
+    Tool(EditorTool),
+    /// Change the width and height of the level.
+    Resize(i32, i32),
+    ClearZones,
+    /// Type a new level name, finished with enter.
+    Rename,
+    New,
+    /// Writing files doesn't work in the browser.
+    #[cfg(not(target_family = "wasm"))]
+    Save,
+    Back,
+}
+
+/// Name of the level being edited.
+#[derive(Component)]
+struct EditorLevelName;
+
+fn enter_editor(
+    mut commands: Commands,
+    mut editor: ResMut<Editor>,
+    levels: Res<Levels>,
+    selected_level: Res<SelectedLevel>,
+    level_assets: Res<Assets<LevelAsset>>,
+    mut q_camera: Query<&mut Transform, With<VillageCamera>>,
+) {
+    let level = levels
+        .selected(&selected_level)
+        .and_then(|level| level_assets.get(&level.handle))
+        .cloned()
+        .unwrap_or_else(|| LevelAsset::new("new_level", NEW_LEVEL_SIZE));
+    // Tile picking needs a map from the first frame on.
+    let size = level.dim();
+    commands.insert_resource(VillageMap::new(size));
+    for mut transform in q_camera.iter_mut() {
+        transform.translation = village_camera_translation(size);
+    }
+    commands.insert_resource(EditorLevel(level));
+    *editor = Editor::default();
+
+    commands
+        .spawn((
+            Name::new("Editor Panel"),
+            NodeBundle {
+                style: Style {
+                    height: Percent(100.0),
+                    padding: UiRect::all(Px(10.0)),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Px(5.0),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(Screen::Editor),
+        ))
+        .with_children(|children| {
+            children.header("Terrain");
+            for terrain in [Terrain::Grass, Terrain::Gravel, Terrain::Water] {
+                children
+                    .button(format!("{terrain:?}"))
+                    .insert(EditorAction::Tool(EditorTool::Terrain(terrain)));
+            }
+
+            children.header("Structures");
+            for structure_type in EDITOR_STRUCTURES {
+                children
+                    .button(structure_type.name())
+                    .insert(EditorAction::Tool(EditorTool::Structure(structure_type)));
+            }
+            children
+                .button("Erase")
+                .insert(EditorAction::Tool(EditorTool::Erase));
+
+            children.header("Zones");
+            children
+                .button("Deploy")
+                .insert(EditorAction::Tool(EditorTool::DeploymentZone));
+            children
+                .button("Spawn")
+                .insert(EditorAction::Tool(EditorTool::SpawnZone));
+            children.button("Clear").insert(EditorAction::ClearZones);
+
+            children.header("Size");
+            children
+                .button("Width +")
+                .insert(EditorAction::Resize(1, 0));
+            children
+                .button("Width -")
+                .insert(EditorAction::Resize(-1, 0));
+            children
+                .button("Height +")
+                .insert(EditorAction::Resize(0, 1));
+            children
+                .button("Height -")
+                .insert(EditorAction::Resize(0, -1));
+
+            children.header("Level");
+            children.spawn((
+                Name::new("Level Name"),
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 18.0,
+                        color: LABEL_TEXT,
+                        ..default()
+                    },
+                ),
+                EditorLevelName,
+            ));
+            children.button("Rename").insert(EditorAction::Rename);
+            children.button("New").insert(EditorAction::New);
+            #[cfg(not(target_family = "wasm"))]
+            children.button("Save").insert(EditorAction::Save);
+            children.button("Back").insert(EditorAction::Back);
+        });
+}
+
+fn handle_editor_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&EditorAction>,
+    mut editor: ResMut<Editor>,
+    mut level: ResMut<EditorLevel>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                EditorAction::Tool(tool) => {
+                    editor.tool = *tool;
+                    editor.zone_start = None;
+                }
+                EditorAction::Resize(dx, dy) => {
+                    let dim = level.0.dim();
+                    let size = TileDim((dim.x() + dx).max(1), (dim.y() + dy).max(1));
+                    level.0.resize(size);
+                }
+                EditorAction::ClearZones => {
+                    level.0.spawn_zones.clear();
+                    level.0.deployment_zone = None;
+                }
+                EditorAction::Rename => editor.renaming = true,
+                EditorAction::New => {
+                    level.0 = LevelAsset::new("", NEW_LEVEL_SIZE);
+                    editor.renaming = true;
+                }
+                #[cfg(not(target_family = "wasm"))]
+                EditorAction::Save => match save_level(&level.0) {
+                    Ok(path) => info!("Saved level to {}", path.display()),
+                    Err(err) => error!("Unable to save level: {err}"),
+                },
+                EditorAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
+
+/// Level names are file names, only letters, digits, `-` and `_` can be typed.
+fn type_level_name(
+    mut keyboard_input: EventReader<KeyboardInput>,
+    mut editor: ResMut<Editor>,
+    mut level: ResMut<EditorLevel>,
+) {
+    for input in keyboard_input.read() {
+        if !editor.renaming || !input.state.is_pressed() {
+            continue;
+        }
+        match &input.logical_key {
+            Key::Enter | Key::Escape => editor.renaming = false,
+            Key::Backspace => {
+                level.0.name.pop();
+            }
+            Key::Space => level.0.name.push('_'),
+            Key::Character(text) => level.0.name.extend(
+                text.chars()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_'),
+            ),
+            _ => {}
+        }
+    }
+}
+
+fn show_level_name(
+    level: Res<EditorLevel>,
+    editor: Res<Editor>,
+    mut q_name: Query<&mut Text, With<EditorLevelName>>,
+) {
+    for mut text in q_name.iter_mut() {
+        text.sections[0].value = match editor.renaming {
+            true => format!("Name: {}_", level.0.name),
+            false => format!("Name: {}", level.0.name),
+        };
+    }
+}
+
+fn edit_level(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    picked_tile: Res<PickedTile>,
+    q_interactions: Query<&Interaction>,
+    mut editor: ResMut<Editor>,
+    mut level: ResMut<EditorLevel>,
+) {
+    // The pointer is over the editor panel.
+    if q_interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+    let Some(tile) = picked_tile.0 else {
+        return;
+    };
+
+    match editor.tool {
+        EditorTool::Terrain(terrain)
+            if mouse_button.pressed(MouseButton::Left)
+                && level.0.terrain_at(tile) != Some(terrain) =>
+        {
+            level.0.set_terrain(tile, terrain);
+        }
+        EditorTool::Structure(structure_type) if mouse_button.just_pressed(MouseButton::Left) => {
+            level
+                .0
+                .set_structure(tile, Some(structure_type.tile_texture()));
+        }
+        EditorTool::Erase
+            if mouse_button.pressed(MouseButton::Left) && level.0.structure_at(tile).is_some() =>
+        {
+            level.0.set_structure(tile, None);
+        }
+        EditorTool::DeploymentZone | EditorTool::SpawnZone
+            if mouse_button.just_pressed(MouseButton::Left) =>
+        {
+            let Some(start) = editor.zone_start.take() else {
+                editor.zone_start = Some(tile);
+                return;
+            };
+            let rect = TileRect(start, tile);
+            let area = LevelRect {
+                min: rect.min(),
+                max: rect.max(),
+            };
+            if editor.tool == EditorTool::DeploymentZone {
+                level.0.deployment_zone = Some(area);
+            } else {
+                let name = format!("spawn {}", level.0.spawn_zones.len() + 1);
+                level.0.spawn_zones.push(SpawnZone { name, area });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Keep the map used for picking and the camera in sync with the level size.
+fn fit_map_to_level(
+    mut commands: Commands,
+    level: Res<EditorLevel>,
+    village_map: Option<Res<VillageMap>>,
+    mut q_camera: Query<&mut Transform, With<VillageCamera>>,
+) {
+    let size = level.0.dim();
+    if village_map.is_some_and(|village_map| village_map.size() == size) {
+        return;
+    }
+
+    commands.insert_resource(VillageMap::new(size));
+    for mut transform in q_camera.iter_mut() {
+        transform.translation = village_camera_translation(size);
+    }
+}
+
+fn draw_editor_level(
+    mut commands: Commands,
+    level: Res<EditorLevel>,
+    editor: Res<Editor>,
+    tile_set: Res<TileSet>,
+) {
+    let level = &level.0;
+    let in_deployment_zone = |tile| {
+        level
+            .deployment_zone
+            .is_some_and(|zone| zone.tile_rect().contains(tile))
+    };
+    let in_spawn_zone = |tile| {
+        level
+            .spawn_zones
+            .iter()
+            .any(|zone| zone.area.tile_rect().contains(tile))
+    };
+
+    for tile in level.bounds() {
+        let color = if editor.zone_start == Some(tile) {
+            CURSOR_COLOR.into()
+        } else if in_deployment_zone(tile) {
+            DEPLOYMENT_ZONE_COLOR.into()
+        } else if in_spawn_zone(tile) {
+            SPAWN_ZONE_COLOR.into()
+        } else {
+            Color::WHITE
+        };
+        let terrain = level.terrain_at(tile).unwrap_or_default();
+        spawn_terrain_sprite(&mut commands, &tile_set, tile, terrain, color);
+
+        if let Some(structure) = level.structure_at(tile) {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        anchor: TILE_ANCHOR,
+                        ..default()
+                    },
+                    texture: tile_set.get(structure),
+                    transform: Transform::from_translation(tile_coord_translation(
+                        tile.x() as f32,
+                        tile.y() as f32,
+                        2.,
+                    )),
+                    ..default()
+                },
+                TemporarySprite,
+            ));
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn save_level(level: &LevelAsset) -> Result<std::path::PathBuf, EditorSaveError> {
+    use crate::game::level::level_asset::{LevelManifest, LEVEL_MANIFEST_PATH};
+    use bevy::asset::io::file::FileAssetReader;
+
+    if level.name.is_empty() {
+        return Err(EditorSaveError::MissingName);
+    }
+    level.validate()?;
+    let assets = FileAssetReader::get_base_path().join("assets");
+    let path = assets.join("levels").join(format!("{}.json", level.name));
+    std::fs::write(&path, serde_json::to_string_pretty(level)?)?;
+
+    // List the level so that it shows up on the level select screen.
+    let manifest_path = assets.join(LEVEL_MANIFEST_PATH);
+    let mut manifest: LevelManifest =
+        serde_json::from_str(&std::fs::read_to_string(&manifest_path)?)?;
+    manifest.add_level(&level.name);
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+    Ok(path)
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+enum EditorSaveError {
+    #[error("Level has no name")]
+    MissingName,
+    #[error("Level is not valid: {0}")]
+    Invalid(#[from] crate::game::level::level_asset::LevelAssetLoaderError),
+    #[error("Could not serialize level: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("Could not write level file: {0}")]
+    Io(#[from] std::io::Error),
+}
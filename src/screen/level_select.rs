@@ -0,0 +1,100 @@
+//! A level select screen that can be accessed from the title screen.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::level::{
+        generator::random_level_name, level_asset::Levels, SelectedLevel, UnlockedLevels,
+    },
+    game::save::{read_progress, SaveError},
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Startup, load_progress);
+    app.add_systems(OnEnter(Screen::LevelSelect), enter_level_select);
+
+    app.add_systems(
+        Update,
+        handle_level_select_action.run_if(in_state(Screen::LevelSelect)),
+    );
+    app.register_type::<LevelSelectAction>();
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum LevelSelectAction {
+    /// Index into [`Levels`].
+    Play(usize),
+    /// Play a newly generated village.
+    Random,
+    Back,
+}
+
+/// Levels unlocked in earlier sessions stay unlocked.
+fn load_progress(mut unlocked_levels: ResMut<UnlockedLevels>) {
+    match read_progress() {
+        Ok(progress) => *unlocked_levels = progress,
+        Err(SaveError::NotFound) => {}
+        Err(err) => error!("Unable to load progress: {err}"),
+    }
+}
+
+fn enter_level_select(
+    mut commands: Commands,
+    levels: Res<Levels>,
+    unlocked_levels: Res<UnlockedLevels>,
+) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::LevelSelect))
+        .with_children(|children| {
+            children.header("Levels");
+            // Generated villages are only listed through the random village button.
+            let manifest_levels = levels
+                .0
+                .iter()
+                .enumerate()
+                .filter(|(_, level)| level.seed.is_none());
+            for (index, level) in manifest_levels {
+                if index < unlocked_levels.0 {
+                    children
+                        .button(level.display_name.clone())
+                        .insert(LevelSelectAction::Play(index));
+                    children.label(format!("Difficulty {}", level.difficulty));
+                } else {
+                    children.label(format!("{} (locked)", level.display_name));
+                }
+            }
+
+            children
+                .button("Random village")
+                .insert(LevelSelectAction::Random);
+
+            children.button("Back").insert(LevelSelectAction::Back);
+        });
+}
+
+fn handle_level_select_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut selected_level: ResMut<SelectedLevel>,
+    mut button_query: InteractionQuery<&LevelSelectAction>,
+    levels: Res<Levels>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                LevelSelectAction::Play(index) => {
+                    selected_level.0 = levels.0.get(*index).map(|level| level.name.clone());
+                    next_screen.set(Screen::Playing);
+                }
+                LevelSelectAction::Random => {
+                    selected_level.0 = Some(random_level_name(rand::random()));
+                    next_screen.set(Screen::Playing);
+                }
+                LevelSelectAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
@@ -5,7 +5,10 @@ use bevy::prelude::*;
 
 use super::Screen;
 use crate::{
-    game::assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
+    game::{
+        assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
+        level::{level_asset::Levels, SelectedLevel},
+    },
     ui::prelude::*,
 };
 
@@ -33,6 +36,7 @@ fn all_assets_loaded(
     image_handles: Res<HandleMap<ImageKey>>,
     sfx_handles: Res<HandleMap<SfxKey>>,
     soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
+    levels: Res<Levels>,
 ) -> bool {
     *elapsed += time.delta();
     // if loading takes too long, start
@@ -40,8 +44,16 @@ fn all_assets_loaded(
         || image_handles.all_loaded(&asset_server)
             && sfx_handles.all_loaded(&asset_server)
             && soundtrack_handles.all_loaded(&asset_server)
+            && levels.all_loaded(&asset_server)
 }
 
-fn continue_to_title(mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Title);
+fn continue_to_title(
+    mut next_screen: ResMut<NextState<Screen>>,
+    selected_level: Res<SelectedLevel>,
+) {
+    // A level picked on the command line skips the menus.
+    match selected_level.0 {
+        Some(_) => next_screen.set(Screen::Playing),
+        None => next_screen.set(Screen::Title),
+    }
 }
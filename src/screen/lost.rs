@@ -14,22 +14,15 @@ use sickle_ui::{
     },
 };
 
-use crate::{
-    game::actors::Structure,
-    ui::{
-        interaction::InteractionPalette,
-        palette::{HEADER_SIZE, LABEL_SIZE},
-    },
+use crate::ui::{
+    interaction::InteractionPalette,
+    palette::{HEADER_SIZE, LABEL_SIZE},
 };
 
 use super::Screen;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Lost), show_lost_scren)
-        .add_systems(
-            PostUpdate,
-            check_lost_status.run_if(in_state(Screen::Playing)),
-        )
         .add_systems(Update, back_btn_interaction.run_if(in_state(Screen::Lost)));
 }
 
@@ -94,15 +87,6 @@ fn show_lost_scren(mut commands: Commands) {
         .insert(StateScoped(Screen::Lost));
 }
 
-fn check_lost_status(
-    q_structures: Query<(), With<Structure>>,
-    mut next_screen: ResMut<NextState<Screen>>,
-) {
-    if q_structures.is_empty() {
-        next_screen.set(Screen::Lost);
-    }
-}
-
 fn back_btn_interaction(
     q_interactions: Query<&Interaction, (With<ReturnToMenuButton>, Changed<Interaction>)>,
     mut next_screen: ResMut<NextState<Screen>>,
@@ -6,11 +6,14 @@ use bevy::prelude::*;
 use sickle_ui::prelude::*;
 
 use super::Screen;
+use crate::game::actors::status::BlockAction;
 use crate::game::actors::AvailableActorNames;
 use crate::game::constants::{INITIAL_GOLD, INITIAL_POPULATION, UNIT_LIST_ZINDEX};
 use crate::game::construction::{
-    build_btn_interaction, building_panel_layout, spawn_in_progress_building, update_build_panel,
-    update_building_progress, update_building_progress_labels, BuildingPanel, StructureCosts,
+    build_btn_interaction, building_panel_layout, press_replayed_build_buttons,
+    rotate_btn_interaction, spawn_in_progress_building, update_build_panel,
+    update_building_progress, update_building_progress_labels, update_rotate_button, BuildingPanel,
+    StructureCosts,
 };
 use crate::game::cycle::{EndDeployment, EndTurn, Season, TimeOfDay, Turn};
 use crate::game::deployment::{
@@ -18,11 +21,17 @@ use crate::game::deployment::{
 };
 use crate::game::events::{EndDayTurn, SelectStructureTypeEvent};
 use crate::game::resources::{
-    SelectedStructueType, VillageEmployment, VillageGold, VillagePopulation,
+    SelectedStructueType, SelectedStructureRotation, VillageEmployment, VillageGold,
+    VillagePopulation,
 };
 
 use crate::game::actors::player::{add_starting_player_units, move_unit, reset_unit_turn_states};
+use crate::game::level::generate_selected_level;
+use crate::game::replay::{replay_commands, CommandLog, GameCommand, Replay};
+use crate::game::rng::seed_game_rng;
 use crate::game::selection::{dispatch_object_pressed, SelectedTiles};
+use crate::game::trap::{trap_btn_interaction, update_trap_buttons, SelectedTrapType};
+use crate::game::undo::UndoHeroAction;
 
 use crate::game::actors_list::{
     actor_list_layout, inventory_list_layout, inventory_list_layout_vis,
@@ -33,8 +42,8 @@ use crate::game::WatchRes;
 use crate::game::{assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack};
 
 use crate::modals::tavern::{
-    enter_tavern_modal, exit_tavern_btn_interaction, recruit_button, tavern_button,
-    tavern_modal_layout, update_slot_labels, upgrade_buttons, TavernSubject,
+    enter_tavern_modal, exit_tavern_btn_interaction, press_replayed_tavern_buttons, recruit_button,
+    tavern_button, tavern_modal_layout, update_slot_labels, upgrade_buttons, TavernSubject,
 };
 
 use crate::modals::merchant::MerchantModalPlugin;
@@ -44,40 +53,31 @@ use crate::ui::interaction::apply_interaction_palette;
 use crate::ui::{palette::*, prelude::*};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(MerchantModalPlugin)
-        .init_state::<GameState>()
-        .enable_state_scoped_entities::<GameState>()
+    app.add_plugins((GameplayPlugin, MerchantModalPlugin))
         .init_resource::<DisplayCache>()
-        .init_resource::<AvailableActorNames>()
-        .init_resource::<PlayerActorList>()
-        .init_resource::<StructureCosts>()
-        .init_resource::<SelectedStructueType>()
         .init_resource::<TavernSubject>()
         .init_resource::<ItemSlotIcons>()
         .add_event::<SelectStructureTypeEvent>()
         .add_systems(OnEnter(Screen::Playing), enter_playing)
-        .add_systems(OnEnter(Screen::Playing), add_starting_player_units)
         .add_systems(OnEnter(GameState::Tavern), tavern_modal_layout)
         .add_systems(
             Update,
             exit_tavern_btn_interaction.run_if(in_state(GameState::Tavern)),
         )
-        .add_systems(OnExit(Screen::Playing), exit_playing)
-        .add_systems(OnEnter(Screen::Playing), building_panel_layout)
-        .add_systems(
-            Update,
-            reset_unit_turn_states.run_if(in_state(Screen::Playing)),
-        )
         .add_systems(
-            OnEnter(GameState::Deployment),
-            (deployment_setup, deployment_zone_visualization).chain(),
+            PreUpdate,
+            press_replayed_tavern_buttons
+                .after(replay_commands)
+                .run_if(in_state(GameState::Tavern).and_then(resource_exists::<Replay>)),
         )
         .add_systems(
-            OnExit(GameState::Deployment),
-            |mut selected_tiles: ResMut<SelectedTiles>| {
-                selected_tiles.tiles.clear();
-            },
+            PreUpdate,
+            press_replayed_build_buttons
+                .after(replay_commands)
+                .run_if(in_state(GameState::BuildingTurn).and_then(resource_exists::<Replay>)),
         )
+        .add_systems(OnExit(Screen::Playing), exit_playing)
+        .add_systems(OnEnter(Screen::Playing), building_panel_layout)
         .add_systems(
             OnEnter(GameState::Deployment),
             (
@@ -112,12 +112,13 @@ pub(super) fn plugin(app: &mut App) {
             hide_all_with::<EndTurnButton>,
         )
         .add_systems(OnExit(GameState::EnemyTurn), show_all_with::<EndTurnButton>)
-        .add_systems(Update, move_unit.run_if(in_state(GameState::BattleTurn)))
         .add_systems(
-            OnExit(TimeOfDay::Day),
-            |mut s: ResMut<SelectedStructueType>| {
-                s.0 = None;
-            },
+            OnEnter(GameState::BattleTurn),
+            (show_all_with::<UndoButton>, show_all_with::<BlockButton>),
+        )
+        .add_systems(
+            OnExit(GameState::BattleTurn),
+            (hide_all_with::<UndoButton>, hide_all_with::<BlockButton>),
         )
         .add_systems(
             Update,
@@ -129,17 +130,25 @@ pub(super) fn plugin(app: &mut App) {
         )
         .add_systems(
             Update,
-            spawn_in_progress_building
-                .run_if(in_state(Screen::Playing).and_then(in_state(GameState::BuildingTurn))),
+            (
+                rotate_btn_interaction.run_if(in_state(GameState::BuildingTurn)),
+                update_rotate_button.run_if(resource_changed::<SelectedStructureRotation>),
+            )
+                .chain(),
         )
         .add_systems(
             Update,
             (
-                update_building_progress.run_if(in_state(Screen::Playing)),
-                update_building_progress_labels
-                    .run_if(in_state(Screen::Playing))
-                    .after(update_building_progress),
-            ),
+                trap_btn_interaction.run_if(in_state(GameState::BuildingTurn)),
+                update_trap_buttons.run_if(resource_changed::<SelectedTrapType>),
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            update_building_progress_labels
+                .run_if(in_state(Screen::Playing))
+                .after(update_building_progress),
         )
         .add_systems(Update, enter_tavern_modal.after(dispatch_object_pressed))
         .add_systems(
@@ -175,6 +184,8 @@ pub(super) fn plugin(app: &mut App) {
         (
             // exit_btn_interaction,
             end_turn_btn_interaction,
+            undo_btn_interaction,
+            block_btn_interaction,
             fight_btn_interaction,
             open_merchant_btn_interaction,
             update_actor_list_container
@@ -186,6 +197,58 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
+/// Rules of the playing screen that do not depend on the ui.
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>()
+            .enable_state_scoped_entities::<GameState>()
+            .init_resource::<AvailableActorNames>()
+            .init_resource::<PlayerActorList>()
+            .init_resource::<StructureCosts>()
+            .init_resource::<SelectedStructueType>()
+            .init_resource::<SelectedStructureRotation>()
+            .add_systems(
+                OnEnter(Screen::Playing),
+                add_starting_player_units
+                    .after(generate_selected_level)
+                    .after(seed_game_rng),
+            )
+            .add_systems(
+                Update,
+                reset_unit_turn_states.run_if(in_state(Screen::Playing)),
+            )
+            .add_systems(
+                OnEnter(GameState::Deployment),
+                (deployment_setup, deployment_zone_visualization).chain(),
+            )
+            .add_systems(
+                OnExit(GameState::Deployment),
+                |mut selected_tiles: ResMut<SelectedTiles>| {
+                    selected_tiles.tiles.clear();
+                },
+            )
+            .add_systems(Update, move_unit.run_if(in_state(GameState::BattleTurn)))
+            .add_systems(
+                OnExit(TimeOfDay::Day),
+                |mut s: ResMut<SelectedStructueType>, mut r: ResMut<SelectedStructureRotation>| {
+                    s.0 = None;
+                    r.0 = false;
+                },
+            )
+            .add_systems(
+                Update,
+                spawn_in_progress_building
+                    .run_if(in_state(Screen::Playing).and_then(in_state(GameState::BuildingTurn))),
+            )
+            .add_systems(
+                Update,
+                update_building_progress.run_if(in_state(Screen::Playing)),
+            );
+    }
+}
+
 fn economy_status_layout(ui: &mut UiBuilder<Entity>) {
     ui.column(|ui| {
         ui.style().justify_content(JustifyContent::Center);
@@ -292,6 +355,44 @@ fn enter_playing(
                     .width(Val::Px(100.0))
                     .height(Val::Px(100.0));
 
+                    ui.container(ButtonBundle::default(), |ui| {
+                        ui.label(LabelConfig::from("Undo"))
+                            .style()
+                            .font_size(LABEL_SIZE);
+                    })
+                    .insert((
+                        InteractionPalette {
+                            none: css::SLATE_GRAY.into(),
+                            hovered: css::DARK_SLATE_GRAY.into(),
+                            pressed: css::LIGHT_SLATE_GRAY.into(),
+                        },
+                        UndoButton,
+                    ))
+                    .style()
+                    .display(Display::None)
+                    .margin(UiRect::bottom(Val::Px(10.)))
+                    .padding(UiRect::all(Val::Px(10.)))
+                    .border_radius(BorderRadius::all(Val::Px(5.)));
+
+                    ui.container(ButtonBundle::default(), |ui| {
+                        ui.label(LabelConfig::from("Block"))
+                            .style()
+                            .font_size(LABEL_SIZE);
+                    })
+                    .insert((
+                        InteractionPalette {
+                            none: css::SLATE_GRAY.into(),
+                            hovered: css::DARK_SLATE_GRAY.into(),
+                            pressed: css::LIGHT_SLATE_GRAY.into(),
+                        },
+                        BlockButton,
+                    ))
+                    .style()
+                    .display(Display::None)
+                    .margin(UiRect::bottom(Val::Px(10.)))
+                    .padding(UiRect::all(Val::Px(10.)))
+                    .border_radius(BorderRadius::all(Val::Px(5.)));
+
                     ui.container(ButtonBundle::default(), |ui| {
                         ui.label(LabelConfig::from("End Turn"))
                             .style()
@@ -375,6 +476,28 @@ fn end_turn_btn_interaction(
     }
 }
 
+fn undo_btn_interaction(
+    q_interactions: Query<&Interaction, (Changed<Interaction>, With<UndoButton>)>,
+    mut undo_evt: EventWriter<UndoHeroAction>,
+) {
+    for interaction in q_interactions.iter() {
+        if let Interaction::Pressed = interaction {
+            undo_evt.send(UndoHeroAction);
+        }
+    }
+}
+
+fn block_btn_interaction(
+    q_interactions: Query<&Interaction, (Changed<Interaction>, With<BlockButton>)>,
+    mut block_evt: EventWriter<BlockAction>,
+) {
+    for interaction in q_interactions.iter() {
+        if let Interaction::Pressed = interaction {
+            block_evt.send(BlockAction);
+        }
+    }
+}
+
 fn fight_btn_interaction(
     q_interactions: Query<&Interaction, (Changed<Interaction>, With<FightButton>)>,
     mut end_deployment_evt: EventWriter<EndDeployment>,
@@ -390,11 +513,13 @@ fn open_merchant_btn_interaction(
     q_interactions: Query<&Interaction, (Changed<Interaction>, With<OpenMerchantButton>)>,
     state: Res<State<TimeOfDay>>,
     mut next_game_state: ResMut<NextState<GameState>>,
+    mut command_log: ResMut<CommandLog>,
 ) {
     for interaction in q_interactions.iter() {
         if let Interaction::Pressed = interaction {
             if *state.get() == TimeOfDay::Day {
                 next_game_state.set(GameState::Merchant);
+                command_log.record(GameCommand::OpenMerchant);
             }
         }
     }
@@ -423,6 +548,14 @@ pub struct EndTurnButton;
 #[derive(Component)]
 pub struct OpenMerchantButton;
 
+/// When clicked take back the last hero action of this turn
+#[derive(Component)]
+pub struct UndoButton;
+
+/// When clicked the selected hero spends its action to block
+#[derive(Component)]
+pub struct BlockButton;
+
 /// When clicked end deployment
 #[derive(Component)]
 pub struct FightButton;
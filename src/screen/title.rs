@@ -5,6 +5,8 @@ use bevy::prelude::*;
 use super::Screen;
 use crate::game::assets::SoundtrackKey;
 use crate::game::audio::soundtrack::PlaySoundtrack;
+use crate::game::level::SelectedLevel;
+use crate::game::save::{has_save, read_save, PendingSave};
 use crate::ui::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
@@ -17,8 +19,13 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 #[reflect(Component)]
 enum TitleAction {
+    /// Resume the last saved village.
+    Continue,
     Play,
     Credits,
+    /// Level files can only be saved natively.
+    #[cfg(not(target_family = "wasm"))]
+    Editor,
     /// Exit doesn't work well with embedded applications.
     #[cfg(not(target_family = "wasm"))]
     Exit,
@@ -32,16 +39,25 @@ fn enter_title(mut commands: Commands) {
         .insert(StateScoped(Screen::Title))
         .with_children(|children| {
             children.title("Cycle of Valor");
+            if has_save() {
+                children
+                    .title_button("Continue")
+                    .insert(TitleAction::Continue);
+            }
             children.title_button("Play").insert(TitleAction::Play);
             children
                 .title_button("Credits")
                 .insert(TitleAction::Credits);
             #[cfg(not(target_family = "wasm"))]
+            children.title_button("Editor").insert(TitleAction::Editor);
+            #[cfg(not(target_family = "wasm"))]
             children.title_button("Exit").insert(TitleAction::Exit);
         });
 }
 
 fn handle_title_action(
+    mut pending_save: ResMut<PendingSave>,
+    mut selected_level: ResMut<SelectedLevel>,
     mut next_screen: ResMut<NextState<Screen>>,
     mut button_query: InteractionQuery<&TitleAction>,
     #[cfg(not(target_family = "wasm"))] mut app_exit: EventWriter<AppExit>,
@@ -49,8 +65,18 @@ fn handle_title_action(
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
             match action {
-                TitleAction::Play => next_screen.set(Screen::Playing),
+                TitleAction::Continue => match read_save() {
+                    Ok(save) => {
+                        selected_level.0.clone_from(&save.level);
+                        pending_save.0 = Some(save);
+                        next_screen.set(Screen::Playing);
+                    }
+                    Err(err) => error!("Unable to load save: {err}"),
+                },
+                TitleAction::Play => next_screen.set(Screen::LevelSelect),
                 TitleAction::Credits => next_screen.set(Screen::Credits),
+                #[cfg(not(target_family = "wasm"))]
+                TitleAction::Editor => next_screen.set(Screen::Editor),
 
                 #[cfg(not(target_family = "wasm"))]
                 TitleAction::Exit => {
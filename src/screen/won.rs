@@ -0,0 +1,131 @@
+use bevy::{color::palettes::css, prelude::*, ui::FocusPolicy};
+use sickle_ui::{
+    ui_builder::{UiBuilderExt, UiRoot},
+    ui_style::generated::{
+        SetBackgroundColorExt, SetBorderRadiusExt, SetFlexGrowExt, SetFlexShrinkExt,
+        SetFocusPolicyExt, SetFontColorExt, SetFontSizeExt, SetHeightExt, SetJustifyContentExt,
+        SetJustifyItemsExt, SetJustifySelfExt, SetPaddingExt, SetWidthExt,
+    },
+    widgets::layout::{
+        column::UiColumnExt,
+        container::UiContainerExt,
+        label::{LabelConfig, UiLabelExt},
+        row::UiRowExt,
+    },
+};
+
+use crate::{
+    game::{level::UnlockedLevels, save::write_progress, victory::RunStats},
+    ui::{
+        interaction::InteractionPalette,
+        palette::{HEADER_SIZE, LABEL_SIZE},
+    },
+};
+
+use super::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Won), (show_won_screen, save_progress))
+        .add_systems(Update, back_btn_interaction.run_if(in_state(Screen::Won)));
+}
+
+#[derive(Component)]
+struct ReturnToMenuButton;
+
+fn show_won_screen(mut commands: Commands, run_stats: Res<RunStats>) {
+    let victory = match run_stats.victory {
+        Some(condition) => format!("Goal reached: {condition}"),
+        None => "The village stands.".to_string(),
+    };
+    let stats = [
+        format!("Nights survived: {}", run_stats.nights_survived),
+        format!("Enemies killed: {}", run_stats.enemies_killed),
+        format!("Gold earned: {}", run_stats.gold_earned),
+    ];
+
+    commands
+        .ui_builder(UiRoot)
+        .column(|ui| {
+            ui.style()
+                .width(Val::Percent(100.0))
+                .height(Val::Percent(100.0))
+                .focus_policy(FocusPolicy::Block)
+                .background_color(Color::BLACK)
+                .justify_self(JustifySelf::Center)
+                .justify_content(JustifyContent::Center)
+                .justify_items(JustifyItems::Center);
+
+            ui.column(|_| {}).style().flex_grow(1.0);
+
+            ui.column(|ui| {
+                ui.row(|ui| {
+                    ui.row(|_| {}).style().flex_grow(1.0);
+
+                    ui.column(|ui| {
+                        ui.label(LabelConfig::from("You won!"))
+                            .style()
+                            .font_size(HEADER_SIZE);
+
+                        ui.label(LabelConfig::from(victory))
+                            .style()
+                            .font_size(LABEL_SIZE);
+
+                        ui.column(|_| {}).style().height(Val::Px(20.0));
+
+                        for stat in stats {
+                            ui.label(LabelConfig::from(stat))
+                                .style()
+                                .font_size(LABEL_SIZE);
+                        }
+
+                        ui.column(|_| {}).style().height(Val::Px(40.0));
+
+                        ui.container(ButtonBundle::default(), |ui| {
+                            ui.label(LabelConfig::from("Back to Main Menu"))
+                                .style()
+                                .font_size(LABEL_SIZE)
+                                .font_color(Color::BLACK);
+                        })
+                        .insert((
+                            InteractionPalette {
+                                none: Color::WHITE,
+                                hovered: Color::WHITE.darker(0.2),
+                                pressed: Color::WHITE,
+                            },
+                            ReturnToMenuButton,
+                        ))
+                        .style()
+                        .background_color(css::BLUE.into())
+                        .border_radius(BorderRadius::all(Val::Px(12.0)))
+                        .padding(UiRect::all(Val::Px(18.0)));
+                    });
+
+                    ui.row(|_| {}).style().flex_grow(1.0);
+                });
+            })
+            .style()
+            .flex_grow(0.0)
+            .flex_shrink(1.0);
+
+            ui.column(|_| {}).style().flex_grow(1.0);
+        })
+        .insert(StateScoped(Screen::Won));
+}
+
+/// Keep the level unlocked by this win for later sessions.
+fn save_progress(unlocked_levels: Res<UnlockedLevels>) {
+    if let Err(err) = write_progress(&unlocked_levels) {
+        error!("Unable to save progress: {err}");
+    }
+}
+
+fn back_btn_interaction(
+    q_interactions: Query<&Interaction, (With<ReturnToMenuButton>, Changed<Interaction>)>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    for interaction in q_interactions.iter() {
+        if let Interaction::Pressed = interaction {
+            next_screen.set(Screen::Title);
+        }
+    }
+}
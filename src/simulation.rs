@@ -0,0 +1,271 @@
+//! Headless version of the game loop for integration tests and balancing.
+//!
+//! [`SimulationPlugin`] runs the game rules without a window, renderer, audio or ui.
+//! Player decisions are fed in as [`SimulationInput`] events instead of mouse clicks.
+
+use std::time::Duration;
+
+use bevy::asset::AssetPlugin;
+use bevy::input::{InputPlugin, InputSystem};
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+
+use crate::game::actors::enemy_asset::EnemyDefsHandle;
+use crate::game::actors::status::BlockAction;
+use crate::game::construction::StructureType;
+use crate::game::cycle::{EndDeployment, EndTurn, TimeOfDay};
+use crate::game::events::EndDayTurn;
+use crate::game::inventory::Inventory;
+use crate::game::level::level_asset::Levels;
+use crate::game::level::SelectedLevel;
+use crate::game::picking::PickedTile;
+use crate::game::replay::{CommandLog, Replay};
+use crate::game::resources::{SelectedStructueType, SelectedStructureRotation};
+use crate::game::rng::GameSeed;
+use crate::game::selection::SelectedActor;
+use crate::game::trap::{SelectedTrapType, TrapType};
+use crate::game::undo::UndoHeroAction;
+use crate::game::{self};
+use crate::path_finding::tiles::Tile;
+use crate::screen::playing::{GameState, GameplayPlugin};
+use crate::screen::Screen;
+use crate::ui::icon_set::IconSetUiPlugin;
+
+/// Simulated time that passes every update.
+pub const SIMULATION_TIME_STEP: Duration = Duration::from_millis(100);
+/// Maximum number of updates [`Simulation`] waits for something to happen.
+pub const MAX_SIMULATION_UPDATES: usize = 10_000;
+/// Seed of the [`GameRng`](crate::game::rng::GameRng) unless another one is given,
+/// so every simulation plays out the same.
+pub const DEFAULT_SIMULATION_SEED: u64 = 0;
+
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            StatesPlugin,
+            InputPlugin,
+        ))
+        // Tile and icon handles are still requested by name, they are just never decoded.
+        .init_asset::<Image>()
+        .init_resource::<ClearColor>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(SIMULATION_TIME_STEP))
+        .init_state::<Screen>()
+        .enable_state_scoped_entities::<Screen>()
+        .add_plugins((game::rules_plugin, GameplayPlugin, IconSetUiPlugin))
+        .add_event::<SimulationInput>()
+        .add_systems(PreUpdate, apply_simulation_input.after(InputSystem));
+    }
+}
+
+/// A single player decision, equivalent to a click in the windowed game.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum SimulationInput {
+    /// Left click on a tile of the village map.
+    ClickTile(Tile),
+    /// Pick a structure in the building panel.
+    SelectStructure(Option<StructureType>),
+    /// Pick a trap in the building panel.
+    SelectTrap(Option<TrapType>),
+    /// Press the "rotate" button of the building panel.
+    RotateStructure,
+    /// Pick an item slot of the selected hero.
+    SelectItem(usize),
+    /// Press the "End Turn" button.
+    EndTurn,
+    /// Press the "Fight" button.
+    Fight,
+    /// Press the "Undo" button.
+    Undo,
+    /// Press the "Block" button.
+    Block,
+}
+
+fn apply_simulation_input(
+    mut inputs: EventReader<SimulationInput>,
+    mut mouse_button: ResMut<ButtonInput<MouseButton>>,
+    mut picked_tile: ResMut<PickedTile>,
+    mut selected_structure_type: ResMut<SelectedStructueType>,
+    mut selected_trap_type: ResMut<SelectedTrapType>,
+    mut rotation: ResMut<SelectedStructureRotation>,
+    selected_unit: Res<SelectedActor>,
+    mut q_inventories: Query<&mut Inventory>,
+    time_of_day: Res<State<TimeOfDay>>,
+    mut end_turn_evt: EventWriter<EndTurn>,
+    mut day_turn_evt: EventWriter<EndDayTurn>,
+    mut end_deployment_evt: EventWriter<EndDeployment>,
+    mut undo_evt: EventWriter<UndoHeroAction>,
+    mut block_evt: EventWriter<BlockAction>,
+) {
+    for input in inputs.read() {
+        match *input {
+            SimulationInput::ClickTile(tile) => {
+                picked_tile.0 = Some(tile);
+                mouse_button.reset(MouseButton::Left);
+                mouse_button.press(MouseButton::Left);
+            }
+            SimulationInput::SelectStructure(structure_type) => {
+                selected_structure_type.0 = structure_type;
+            }
+            SimulationInput::SelectTrap(trap_type) => {
+                selected_structure_type.0 = None;
+                selected_trap_type.0 = trap_type;
+            }
+            SimulationInput::RotateStructure => {
+                rotation.0 = !rotation.0;
+            }
+            SimulationInput::SelectItem(slot) => {
+                let Some(mut inventory) = selected_unit
+                    .entity
+                    .and_then(|entity| q_inventories.get_mut(entity).ok())
+                else {
+                    continue;
+                };
+                if inventory.get(slot).is_some() {
+                    inventory.selected_item = Some(slot);
+                }
+            }
+            SimulationInput::EndTurn => {
+                end_turn_evt.send(EndTurn);
+                if *time_of_day.get() == TimeOfDay::Day {
+                    day_turn_evt.send(EndDayTurn);
+                }
+            }
+            SimulationInput::Fight => {
+                end_deployment_evt.send(EndDeployment);
+            }
+            SimulationInput::Undo => {
+                undo_evt.send(UndoHeroAction);
+            }
+            SimulationInput::Block => {
+                block_evt.send(BlockAction);
+            }
+        }
+    }
+}
+
+/// Headless [`App`] with helpers for driving a game from tests.
+pub struct Simulation {
+    pub app: App,
+}
+
+impl Simulation {
+    /// Build the simulation and start playing the first level once the levels and enemies are loaded.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the levels or enemies do not load within [`MAX_SIMULATION_UPDATES`].
+    pub fn new() -> Self {
+        Self::start(SelectedLevel(None), DEFAULT_SIMULATION_SEED)
+    }
+
+    /// Same as [`Simulation::new`] but plays the level with the given name.
+    pub fn with_level(name: &str) -> Self {
+        Self::start(
+            SelectedLevel(Some(name.to_string())),
+            DEFAULT_SIMULATION_SEED,
+        )
+    }
+
+    /// Same as [`Simulation::with_level`] with a different seed for all gameplay randomness.
+    pub fn with_level_and_seed(name: &str, seed: u64) -> Self {
+        Self::start(SelectedLevel(Some(name.to_string())), seed)
+    }
+
+    /// Play back a recorded game until all its commands are played.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the replay does not finish within [`MAX_SIMULATION_UPDATES`].
+    pub fn replay(log: &CommandLog) -> Self {
+        let mut simulation = Self::start(SelectedLevel(log.level.clone()), log.seed);
+        simulation.world_mut().insert_resource(Replay::new(log));
+        let finished = simulation.run_until(|world| {
+            !world.contains_resource::<Replay>()
+                && *world.resource::<State<GameState>>().get() != GameState::EnemyTurn
+        });
+        assert!(finished, "Replay did not finish");
+        simulation
+    }
+
+    /// Commands recorded since the game started.
+    pub fn command_log(&self) -> &CommandLog {
+        self.world().resource::<CommandLog>()
+    }
+
+    fn start(selected_level: SelectedLevel, seed: u64) -> Self {
+        let mut app = App::new();
+        app.add_plugins(SimulationPlugin)
+            .insert_resource(selected_level)
+            .insert_resource(GameSeed(Some(seed)));
+        let mut simulation = Self { app };
+
+        let loaded = simulation.run_until(|world| {
+            // Give the asset loading tasks a chance to make progress.
+            std::thread::sleep(Duration::from_millis(1));
+            let asset_server = world.resource::<AssetServer>();
+            world.resource::<Levels>().all_loaded(asset_server)
+                && asset_server.is_loaded_with_dependencies(&world.resource::<EnemyDefsHandle>().0)
+        });
+        assert!(loaded, "Levels or enemies did not load");
+
+        simulation
+            .world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Playing);
+        simulation.update();
+        simulation
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    pub fn update(&mut self) {
+        self.app.update();
+    }
+
+    /// Update until `done` returns true, returns false if it never did.
+    pub fn run_until(&mut self, mut done: impl FnMut(&World) -> bool) -> bool {
+        for _ in 0..MAX_SIMULATION_UPDATES {
+            if done(self.world()) {
+                return true;
+            }
+            self.update();
+        }
+        false
+    }
+
+    /// Send an input and wait until the game is waiting for the player again.
+    pub fn input(&mut self, input: SimulationInput) {
+        self.world_mut().send_event(input);
+        // One update to read the input and one to apply the resulting state transitions.
+        self.update();
+        self.update();
+        let settled = self
+            .run_until(|world| *world.resource::<State<GameState>>().get() != GameState::EnemyTurn);
+        assert!(settled, "Enemy turn did not end");
+    }
+
+    pub fn game_state(&self) -> GameState {
+        *self.world().resource::<State<GameState>>().get()
+    }
+
+    pub fn time_of_day(&self) -> TimeOfDay {
+        *self.world().resource::<State<TimeOfDay>>().get()
+    }
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
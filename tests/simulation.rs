@@ -0,0 +1,1343 @@
+//! Whole game loop tests, run headless through the `SimulationPlugin`.
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+
+use bevy_jam_5::game::actors::damage::{DamageEvent, DamageType};
+use bevy_jam_5::game::actors::enemy::{spawn_enemy, FinalBoss};
+use bevy_jam_5::game::actors::enemy_asset::{EnemyDefs, EnemyDefsHandle, EnemySpawn};
+use bevy_jam_5::game::actors::intent::EnemyIntent;
+use bevy_jam_5::game::actors::push::PushEvent;
+use bevy_jam_5::game::actors::stats::{ActorName, Armour, Health};
+use bevy_jam_5::game::actors::status::{InflictStatus, StatusEffect, StatusEffects, StatusKind};
+use bevy_jam_5::game::actors::{ActorTurnState, EnemyActor, Structure};
+use bevy_jam_5::game::actors_list::PlayerActorList;
+use bevy_jam_5::game::components::{Abilities, Ability, ArcherTower};
+use bevy_jam_5::game::construction::{spawn_structure, BuildingSite, StructureType};
+use bevy_jam_5::game::cycle::{
+    DayCycle, MoonPhase, Season, TimeOfDay, Turn, DAY_PER_CYCLE, ECLIPSE_DAY, TURN_PER_DAY,
+};
+use bevy_jam_5::game::game_params::Game;
+use bevy_jam_5::game::level::generator::random_level_name;
+use bevy_jam_5::game::level::level_asset::Levels;
+use bevy_jam_5::game::level::Terrain;
+use bevy_jam_5::game::level::UnlockedLevels;
+use bevy_jam_5::game::map::{Footprint, VillageMap};
+use bevy_jam_5::game::replay::GameCommand;
+use bevy_jam_5::game::resources::{VillageEmployment, VillageGold, VillagePopulation};
+use bevy_jam_5::game::save::{save_village, PendingSave};
+use bevy_jam_5::game::tile_set::TileSet;
+use bevy_jam_5::game::tower::{Garrison, TOWER_GARRISON_VILLAGERS};
+use bevy_jam_5::game::trap::{spawn_trap, Trap, TrapTriggered, TrapType};
+use bevy_jam_5::game::victory::{RunStats, VictoryCondition, VictoryConditions};
+use bevy_jam_5::path_finding::tiles::{Tile, TileDim, TileDir};
+use bevy_jam_5::screen::playing::GameState;
+use bevy_jam_5::screen::Screen;
+use bevy_jam_5::simulation::{Simulation, SimulationInput};
+use bevy_jam_5::ui::icon_set::IconSet;
+
+/// Number of finished structures standing on the map.
+fn count_structures(simulation: &mut Simulation) -> usize {
+    simulation.world_mut().run_system_once(
+        |village_map: Res<VillageMap>,
+         q_structures: Query<(), (With<Structure>, Without<BuildingSite>)>| {
+            village_map
+                .actors
+                .footprints()
+                .filter(|(entity, _)| q_structures.contains(*entity))
+                .count()
+        },
+    )
+}
+
+/// Stats of the enemy with this name from the loaded enemy definitions.
+fn enemy(simulation: &Simulation, name: &str) -> EnemySpawn {
+    let handle = &simulation.world().resource::<EnemyDefsHandle>().0;
+    simulation
+        .world()
+        .resource::<Assets<EnemyDefs>>()
+        .get(handle)
+        .and_then(|enemy_defs| enemy_defs.get(name))
+        .cloned()
+        .unwrap()
+}
+
+/// Replace the randomly spawned enemies of this night.
+fn replace_enemies(simulation: &mut Simulation, enemies: Vec<(EnemySpawn, Tile)>) {
+    simulation.world_mut().run_system_once_with(
+        enemies,
+        |In(enemies): In<Vec<(EnemySpawn, Tile)>>,
+         mut commands: Commands,
+         mut village_map: ResMut<VillageMap>,
+         tile_set: Res<TileSet>,
+         q_enemies: Query<Entity, With<EnemyActor>>| {
+            for entity in q_enemies.iter() {
+                village_map.actors.remove_entity(entity);
+                commands.entity(entity).despawn_recursive();
+            }
+            for (enemy, tile) in enemies {
+                let id = spawn_enemy(&mut commands, &tile_set, &enemy, tile);
+                village_map.actors.set(tile, id);
+            }
+        },
+    );
+}
+
+fn end_turns_until(simulation: &mut Simulation, game_state: GameState) {
+    for _ in 0..20 {
+        if simulation.game_state() == game_state {
+            return;
+        }
+        simulation.input(SimulationInput::EndTurn);
+    }
+    panic!("Never reached {game_state:?}");
+}
+
+/// Deploy every hero on the first free deployment tiles and start the battle.
+fn start_battle(simulation: &mut Simulation) -> Vec<Entity> {
+    end_turns_until(simulation, GameState::Deployment);
+    let mut deployment_tiles: Vec<Tile> = simulation
+        .world()
+        .resource::<VillageMap>()
+        .deployment_zone
+        .iter()
+        .copied()
+        .collect();
+    deployment_tiles.sort_by_key(|tile| (tile.y(), tile.x()));
+    let heroes = simulation.world().resource::<PlayerActorList>().0.clone();
+    for tile in deployment_tiles
+        .iter()
+        .filter(|tile| {
+            !simulation
+                .world()
+                .resource::<VillageMap>()
+                .actors
+                .is_occupied(**tile)
+        })
+        .copied()
+        .take(heroes.len())
+        .collect::<Vec<_>>()
+    {
+        simulation.input(SimulationInput::ClickTile(tile));
+    }
+    simulation.input(SimulationInput::Fight);
+    assert_eq!(simulation.game_state(), GameState::BattleTurn);
+    heroes
+}
+
+#[test]
+fn summer_day_ends_in_deployment() {
+    let mut simulation = Simulation::new();
+    assert_eq!(simulation.game_state(), GameState::BuildingTurn);
+
+    for _ in 0..DayCycle::from(Season::Summer).day {
+        assert_eq!(simulation.time_of_day(), TimeOfDay::Day);
+        simulation.input(SimulationInput::EndTurn);
+    }
+
+    assert_eq!(simulation.game_state(), GameState::Deployment);
+    assert_eq!(simulation.time_of_day(), TimeOfDay::Night);
+}
+
+#[test]
+fn house_is_built_after_its_construction_turns() {
+    let mut simulation = Simulation::with_level("debug_level");
+
+    let tile = simulation
+        .world_mut()
+        .run_system_once(|game: Game| {
+            let mut tiles: Vec<Tile> = game.find_tiles_that_can_be_built_on().into_iter().collect();
+            tiles.sort_by_key(|tile| (tile.y(), tile.x()));
+            tiles.first().copied()
+        })
+        .expect("debug_level has room to build");
+    let gold = simulation.world().resource::<VillageGold>().0;
+
+    simulation.input(SimulationInput::SelectStructure(Some(StructureType::House)));
+    simulation.input(SimulationInput::ClickTile(tile));
+
+    assert_eq!(simulation.world().resource::<VillageGold>().0, gold - 50);
+    let site = simulation.world().resource::<VillageMap>().actors.get(tile);
+    assert!(site.is_some_and(|site| simulation.world().get::<BuildingSite>(site).is_some()));
+
+    for _ in 0..4 {
+        simulation.input(SimulationInput::EndTurn);
+    }
+
+    let house = simulation
+        .world()
+        .resource::<VillageMap>()
+        .actors
+        .get(tile)
+        .expect("House stands on the building site");
+    assert!(simulation.world().get::<BuildingSite>(house).is_none());
+    assert_eq!(
+        simulation.world().get::<StructureType>(house),
+        Some(&StructureType::House)
+    );
+}
+
+#[test]
+fn two_werewolves_destroy_an_undefended_house_by_night_two() {
+    let mut simulation = Simulation::with_level("debug_level");
+
+    // Nobody defends the village.
+    simulation.world_mut().run_system_once(
+        |mut commands: Commands, mut player_actor_list: ResMut<PlayerActorList>| {
+            for entity in player_actor_list.0.drain(..) {
+                commands.entity(entity).despawn_recursive();
+            }
+        },
+    );
+
+    let structures = count_structures(&mut simulation);
+
+    for _night in 0..2 {
+        end_turns_until(&mut simulation, GameState::Deployment);
+        // Next to the house at (2, 2).
+        let werewolf = enemy(&simulation, "werewolf");
+        replace_enemies(
+            &mut simulation,
+            vec![(werewolf.clone(), Tile(0, 2)), (werewolf, Tile(2, 0))],
+        );
+        simulation.input(SimulationInput::Fight);
+        assert_eq!(simulation.game_state(), GameState::BattleTurn);
+        end_turns_until(&mut simulation, GameState::BuildingTurn);
+
+        if count_structures(&mut simulation) < structures {
+            return;
+        }
+    }
+
+    panic!("No house was destroyed after two nights");
+}
+
+#[test]
+fn every_level_in_the_manifest_can_be_played() {
+    let mut names = Vec::new();
+    let simulation = Simulation::new();
+    for level in simulation.world().resource::<Levels>().0.iter() {
+        names.push(level.name.clone());
+    }
+    assert!(names.len() > 1);
+
+    for name in names {
+        let mut simulation = Simulation::with_level(&name);
+        assert!(
+            count_structures(&mut simulation) > 0,
+            "{name} has no structures"
+        );
+        assert!(
+            simulation
+                .world_mut()
+                .run_system_once(|game: Game| game.all_structures_reachable()),
+            "{name} has unreachable structures"
+        );
+    }
+}
+
+#[test]
+fn level_sets_starting_gold_heroes_and_zones() {
+    let mut simulation = Simulation::with_level("riverside");
+    assert_eq!(simulation.world().resource::<VillageGold>().0, 600);
+
+    let heroes = simulation.world().resource::<PlayerActorList>().0.clone();
+    assert_eq!(heroes.len(), 2);
+    assert_eq!(
+        simulation.world().get::<Health>(heroes[1]).unwrap().value,
+        4
+    );
+
+    end_turns_until(&mut simulation, GameState::Deployment);
+    let village_map = simulation.world().resource::<VillageMap>();
+    assert!(village_map.deployment_zone.contains(&Tile(3, 2)));
+    assert!(!village_map.deployment_zone.contains(&Tile(2, 2)));
+    // Enemies come from the river banks.
+    simulation.world_mut().run_system_once(
+        |village_map: Res<VillageMap>, q_enemies: Query<Entity, With<EnemyActor>>| {
+            for entity in q_enemies.iter() {
+                let tile = village_map.actors.locate(entity).unwrap().origin;
+                assert!(tile.x() <= 1 || tile.x() >= 10, "Enemy spawned at {tile:?}");
+            }
+        },
+    );
+}
+
+#[test]
+fn random_village_is_reproducible() {
+    let terrain = |simulation: &Simulation| {
+        let village_map = simulation.world().resource::<VillageMap>();
+        village_map
+            .bounds()
+            .into_iter()
+            .map(|tile| village_map.get_terrain(tile))
+            .collect::<Vec<_>>()
+    };
+
+    let mut first = Simulation::with_level(&random_level_name(1234));
+    let second = Simulation::with_level(&random_level_name(1234));
+    assert_eq!(terrain(&first), terrain(&second));
+    assert!(count_structures(&mut first) > 0);
+    assert!(first
+        .world_mut()
+        .run_system_once(|game: Game| game.all_structures_reachable()));
+}
+
+#[test]
+fn same_seed_plays_out_the_same() {
+    fn play(seed: u64) -> (Vec<String>, Vec<Tile>) {
+        let mut simulation = Simulation::with_level_and_seed("debug_level", seed);
+        let heroes = simulation.world_mut().run_system_once(
+            |player_actor_list: Res<PlayerActorList>, q_names: Query<&ActorName>| {
+                player_actor_list
+                    .0
+                    .iter()
+                    .map(|entity| q_names.get(*entity).unwrap().0.clone())
+                    .collect::<Vec<_>>()
+            },
+        );
+        end_turns_until(&mut simulation, GameState::Deployment);
+        let enemies = simulation.world_mut().run_system_once(
+            |village_map: Res<VillageMap>, q_enemies: Query<Entity, With<EnemyActor>>| {
+                let mut tiles: Vec<Tile> = q_enemies
+                    .iter()
+                    .filter_map(|entity| village_map.actors.locate(entity))
+                    .map(|footprint| footprint.origin)
+                    .collect();
+                tiles.sort_by_key(|tile| (tile.y(), tile.x()));
+                tiles
+            },
+        );
+        (heroes, enemies)
+    }
+
+    let first = play(99);
+    assert!(!first.1.is_empty());
+    assert_eq!(first, play(99));
+}
+
+#[test]
+fn recorded_game_replays_the_same() {
+    let hero_tiles = |simulation: &mut Simulation| {
+        simulation.world_mut().run_system_once(
+            |player_actor_list: Res<PlayerActorList>, village_map: Res<VillageMap>| {
+                player_actor_list
+                    .0
+                    .iter()
+                    .map(|entity| {
+                        village_map
+                            .actors
+                            .locate(*entity)
+                            .map(|footprint| footprint.origin)
+                    })
+                    .collect::<Vec<_>>()
+            },
+        )
+    };
+
+    let mut simulation = Simulation::with_level("debug_level");
+    let tile = simulation
+        .world_mut()
+        .run_system_once(|game: Game| {
+            let mut tiles: Vec<Tile> = game.find_tiles_that_can_be_built_on().into_iter().collect();
+            tiles.sort_by_key(|tile| (tile.y(), tile.x()));
+            tiles.first().copied()
+        })
+        .expect("debug_level has room to build");
+    simulation.input(SimulationInput::SelectStructure(Some(StructureType::House)));
+    simulation.input(SimulationInput::ClickTile(tile));
+
+    end_turns_until(&mut simulation, GameState::Deployment);
+    let mut deployment_tiles: Vec<Tile> = {
+        let village_map = simulation.world().resource::<VillageMap>();
+        village_map
+            .deployment_zone
+            .iter()
+            .copied()
+            .filter(|tile| !village_map.actors.is_occupied(*tile))
+            .collect()
+    };
+    deployment_tiles.sort_by_key(|tile| (tile.y(), tile.x()));
+    let heroes = simulation.world().resource::<PlayerActorList>().0.len();
+    for tile in deployment_tiles.into_iter().take(heroes) {
+        simulation.input(SimulationInput::ClickTile(tile));
+    }
+    simulation.input(SimulationInput::Fight);
+    simulation.input(SimulationInput::EndTurn);
+
+    let log = simulation.command_log().clone();
+    assert!(log.commands.contains(&GameCommand::Build {
+        structure: StructureType::House,
+        tile,
+        rotated: false,
+    }));
+    assert!(log.commands.contains(&GameCommand::Fight));
+
+    let mut replayed = Simulation::replay(&log);
+    assert_eq!(replayed.command_log(), &log);
+    assert_eq!(
+        replayed.world().resource::<VillageGold>().0,
+        simulation.world().resource::<VillageGold>().0
+    );
+    assert_eq!(hero_tiles(&mut replayed), hero_tiles(&mut simulation));
+    assert_eq!(
+        count_structures(&mut replayed),
+        count_structures(&mut simulation)
+    );
+}
+
+#[test]
+fn undo_takes_back_a_hero_move() {
+    let mut simulation = Simulation::with_level("debug_level");
+    let heroes = start_battle(&mut simulation);
+
+    let hero = heroes[0];
+    let (start, target) = {
+        let village_map = simulation.world().resource::<VillageMap>();
+        let start = village_map.actors.locate(hero).unwrap().origin;
+        let mut reachable: Vec<Tile> = village_map
+            .flood(start, 1, &TileDir::EDGES, &Abilities::default())
+            .into_iter()
+            .filter(|tile| *tile != start)
+            .collect();
+        reachable.sort_by_key(|tile| (tile.y(), tile.x()));
+        (start, reachable[0])
+    };
+    simulation.input(SimulationInput::ClickTile(start));
+    simulation.input(SimulationInput::ClickTile(target));
+    let located = |simulation: &Simulation| {
+        simulation
+            .world()
+            .resource::<VillageMap>()
+            .actors
+            .locate(hero)
+            .map(|footprint| footprint.origin)
+    };
+    assert_eq!(located(&simulation), Some(target));
+
+    simulation.input(SimulationInput::Undo);
+    assert_eq!(located(&simulation), Some(start));
+    assert!(!simulation
+        .world()
+        .get::<ActorTurnState>(hero)
+        .unwrap()
+        .has_moved());
+
+    // The hero can move again after the undo.
+    simulation.input(SimulationInput::ClickTile(target));
+    assert_eq!(located(&simulation), Some(target));
+}
+
+#[test]
+fn runner_hero_moves_twice() {
+    let mut simulation = Simulation::with_level("debug_level");
+    let heroes = start_battle(&mut simulation);
+    let hero = heroes[0];
+    simulation
+        .world_mut()
+        .entity_mut(hero)
+        .insert(Abilities::new(&[Ability::Runner]));
+
+    let step = |simulation: &mut Simulation| {
+        let (start, target) = {
+            let village_map = simulation.world().resource::<VillageMap>();
+            let start = village_map.actors.locate(hero).unwrap().origin;
+            let mut reachable: Vec<Tile> = village_map
+                .flood(start, 1, &TileDir::EDGES, &Abilities::default())
+                .into_iter()
+                .filter(|tile| *tile != start)
+                .collect();
+            reachable.sort_by_key(|tile| (tile.y(), tile.x()));
+            (start, reachable[0])
+        };
+        simulation.input(SimulationInput::ClickTile(start));
+        simulation.input(SimulationInput::ClickTile(target));
+        target
+    };
+    let located = |simulation: &Simulation| {
+        simulation
+            .world()
+            .resource::<VillageMap>()
+            .actors
+            .locate(hero)
+            .map(|footprint| footprint.origin)
+    };
+
+    let first = step(&mut simulation);
+    assert_eq!(located(&simulation), Some(first));
+    let second = step(&mut simulation);
+    assert_eq!(located(&simulation), Some(second));
+    let turn_state = simulation.world().get::<ActorTurnState>(hero).unwrap();
+    assert_eq!(turn_state.moves, 2);
+    assert!(!turn_state.can_act(&Abilities::new(&[Ability::Runner])));
+}
+
+#[test]
+fn enemies_follow_their_previewed_intent() {
+    let mut simulation = Simulation::with_level("debug_level");
+    start_battle(&mut simulation);
+    let werewolf = enemy(&simulation, "werewolf");
+    replace_enemies(&mut simulation, vec![(werewolf, Tile(0, 2))]);
+    simulation.update();
+
+    let (entity, intent) = simulation
+        .world_mut()
+        .query::<(Entity, &EnemyIntent)>()
+        .single(simulation.world());
+    let intent = intent.clone();
+    assert_eq!(intent.path[0], Tile(0, 2));
+    // Planning the intent does not move the enemy yet.
+    let locate = |simulation: &Simulation| {
+        simulation
+            .world()
+            .resource::<VillageMap>()
+            .actors
+            .locate(entity)
+            .map(|footprint| footprint.origin)
+    };
+    assert_eq!(locate(&simulation), Some(Tile(0, 2)));
+
+    simulation.input(SimulationInput::EndTurn);
+    assert_eq!(simulation.game_state(), GameState::BattleTurn);
+    assert_eq!(locate(&simulation), Some(intent.destination()));
+    // The next battle turn gets a new intent.
+    assert!(simulation.world().get::<EnemyIntent>(entity).is_some());
+}
+
+#[test]
+fn hit_enemies_plan_again() {
+    let mut simulation = Simulation::with_level("debug_level");
+    start_battle(&mut simulation);
+    let werewolf = enemy(&simulation, "werewolf");
+    let hit_points = werewolf.hit_points;
+    replace_enemies(&mut simulation, vec![(werewolf, Tile(0, 2))]);
+    simulation.update();
+    let (entity, intent) = simulation
+        .world_mut()
+        .query::<(Entity, &EnemyIntent)>()
+        .single(simulation.world());
+    let before = intent.clone();
+
+    // Hurt badly enough to flee, the werewolf no longer means to attack.
+    simulation.world_mut().send_event(DamageEvent::new(
+        None,
+        entity,
+        hit_points - 1,
+        DamageType::Physical,
+    ));
+    simulation.update();
+    simulation.update();
+    let after = simulation.world().get::<EnemyIntent>(entity).unwrap();
+    assert_ne!(*after, before);
+    assert_eq!(after.attack, None);
+}
+
+#[test]
+fn pushed_enemies_crash_into_houses_and_drown_in_water() {
+    let mut simulation = Simulation::with_level("debug_level");
+    start_battle(&mut simulation);
+    let werewolf = enemy(&simulation, "werewolf");
+    let hit_points = werewolf.hit_points;
+    // Two tiles from the house at (2, 2) and next to the lake.
+    replace_enemies(
+        &mut simulation,
+        vec![(werewolf.clone(), Tile(0, 2)), (werewolf, Tile(7, 7))],
+    );
+    let village_map = simulation.world().resource::<VillageMap>();
+    let crashing = village_map.actors.get(Tile(0, 2)).unwrap();
+    let drowning = village_map.actors.get(Tile(7, 7)).unwrap();
+    let house = village_map.actors.get(Tile(2, 2)).unwrap();
+    let health =
+        |simulation: &Simulation, entity| simulation.world().get::<Health>(entity).unwrap().value;
+    let house_health = health(&simulation, house);
+
+    // West steps towards larger x.
+    for target in [crashing, drowning] {
+        simulation.world_mut().send_event(PushEvent {
+            source: None,
+            target,
+            direction: TileDir::West,
+            distance: 2,
+        });
+    }
+    simulation.update();
+    simulation.update();
+
+    let village_map = simulation.world().resource::<VillageMap>();
+    assert_eq!(
+        village_map.actors.locate(crashing),
+        Some(Footprint::single(Tile(1, 2)))
+    );
+    // Drowned enemies are taken off the map like any other dead actor.
+    assert_eq!(village_map.actors.locate(drowning), None);
+    assert_eq!(health(&simulation, crashing), hit_points - 1);
+    assert_eq!(health(&simulation, house), house_health - 1);
+}
+
+#[test]
+fn pushed_swimmers_climb_out_of_water_or_drown() {
+    let mut simulation = Simulation::with_level("debug_level");
+    start_battle(&mut simulation);
+    let swimmer = EnemySpawn {
+        abilities: vec![Ability::Swimmer],
+        ..enemy(&simulation, "werewolf")
+    };
+    // A ditch with land behind it, and the lake with only the map border behind it.
+    simulation
+        .world_mut()
+        .resource_mut::<VillageMap>()
+        .set_terrain(Tile(1, 4), Terrain::Water);
+    replace_enemies(
+        &mut simulation,
+        vec![(swimmer.clone(), Tile(0, 4)), (swimmer, Tile(7, 7))],
+    );
+    let village_map = simulation.world().resource::<VillageMap>();
+    let crossing = village_map.actors.get(Tile(0, 4)).unwrap();
+    let drowning = village_map.actors.get(Tile(7, 7)).unwrap();
+
+    for target in [crossing, drowning] {
+        simulation.world_mut().send_event(PushEvent {
+            source: None,
+            target,
+            direction: TileDir::West,
+            distance: 1,
+        });
+    }
+    simulation.update();
+    simulation.update();
+
+    let village_map = simulation.world().resource::<VillageMap>();
+    assert_eq!(
+        village_map.actors.locate(crossing),
+        Some(Footprint::single(Tile(2, 4)))
+    );
+    assert_eq!(village_map.actors.locate(drowning), None);
+}
+
+#[test]
+fn villagers_man_a_tower_that_shoots_once_per_turn() {
+    let mut simulation = Simulation::with_level("debug_level");
+    let tower_tile = Tile(0, 5);
+    let tower = simulation.world_mut().run_system_once(
+        move |mut commands: Commands,
+              mut village_map: ResMut<VillageMap>,
+              tile_set: Res<TileSet>| {
+            let id = spawn_structure(
+                &mut commands,
+                &tile_set,
+                Footprint::single(tower_tile),
+                StructureType::ArcherTower,
+            );
+            village_map.actors.set(tower_tile, id);
+            id
+        },
+    );
+    let employment = |simulation: &Simulation| simulation.world().resource::<VillageEmployment>().0;
+    let idle_employment = employment(&simulation);
+
+    simulation.input(SimulationInput::ClickTile(tower_tile));
+    assert_eq!(
+        simulation.world().get::<Garrison>(tower),
+        Some(&Garrison::Villagers)
+    );
+    assert_eq!(
+        employment(&simulation),
+        idle_employment + TOWER_GARRISON_VILLAGERS
+    );
+
+    start_battle(&mut simulation);
+    let werewolf = enemy(&simulation, "werewolf");
+    let hit_points = werewolf.hit_points;
+    // Three tiles straight north of the tower.
+    replace_enemies(&mut simulation, vec![(werewolf, Tile(0, 2))]);
+    let target = simulation
+        .world()
+        .resource::<VillageMap>()
+        .actors
+        .get(Tile(0, 2))
+        .unwrap();
+    let health = |simulation: &Simulation| simulation.world().get::<Health>(target).unwrap().value;
+
+    for _ in 0..2 {
+        simulation.input(SimulationInput::ClickTile(tower_tile));
+        simulation.input(SimulationInput::SelectItem(0));
+        simulation.input(SimulationInput::ClickTile(Tile(0, 2)));
+    }
+    // Only the first shot of the turn lands.
+    assert_eq!(health(&simulation), hit_points - 1);
+    assert!(simulation
+        .command_log()
+        .commands
+        .contains(&GameCommand::TowerShot {
+            tower: tower_tile,
+            target: Tile(0, 2),
+        }));
+}
+
+#[test]
+fn towers_placed_by_the_level_can_be_manned() {
+    let mut simulation = Simulation::with_level("crossroads");
+    let tower_tile = Tile(6, 2);
+    let tower = simulation
+        .world()
+        .resource::<VillageMap>()
+        .actors
+        .get(tower_tile)
+        .expect("crossroads has a tower");
+    assert!(simulation.world().get::<ArcherTower>(tower).is_some());
+    assert_eq!(simulation.world().get::<Tile>(tower), Some(&tower_tile));
+
+    simulation.input(SimulationInput::ClickTile(tower_tile));
+    assert_eq!(
+        simulation.world().get::<Garrison>(tower),
+        Some(&Garrison::Villagers)
+    );
+}
+
+#[test]
+fn moat_turns_its_tile_into_water() {
+    let mut simulation = Simulation::with_level("debug_level");
+    // A pond beside the house at (2, 2).
+    simulation
+        .world_mut()
+        .resource_mut::<VillageMap>()
+        .set_terrain(Tile(1, 3), Terrain::Water);
+    let tile = Tile(2, 3);
+    let (can_dig, can_dig_dry) = simulation.world_mut().run_system_once(move |game: Game| {
+        (
+            game.can_build(Footprint::single(tile), StructureType::Moat),
+            game.can_build(Footprint::single(Tile(3, 2)), StructureType::Moat),
+        )
+    });
+    assert!(can_dig);
+    // Moats have to be dug next to water.
+    assert!(!can_dig_dry);
+
+    simulation.input(SimulationInput::SelectStructure(Some(StructureType::Moat)));
+    simulation.input(SimulationInput::ClickTile(tile));
+    for _ in 0..2 {
+        simulation.input(SimulationInput::EndTurn);
+    }
+
+    let village_map = simulation.world().resource::<VillageMap>();
+    assert_eq!(village_map.get_terrain(tile), Some(Terrain::Water));
+    assert!(village_map.actors.get(tile).is_none());
+}
+
+#[test]
+fn rotated_blacksmith_covers_two_tiles() {
+    let mut simulation = Simulation::with_level("debug_level");
+    let footprint = simulation
+        .world_mut()
+        .run_system_once(|game: Game| {
+            let mut tiles: Vec<Tile> = game.find_tiles_that_can_be_built_on().into_iter().collect();
+            tiles.sort_by_key(|tile| (tile.y(), tile.x()));
+            tiles
+                .into_iter()
+                .map(|tile| StructureType::Blacksmith.footprint(tile, true))
+                .find(|footprint| game.can_build(*footprint, StructureType::Blacksmith))
+        })
+        .expect("debug_level has room for a blacksmith");
+    assert_eq!(footprint.size, TileDim(1, 2));
+
+    simulation.input(SimulationInput::RotateStructure);
+    simulation.input(SimulationInput::SelectStructure(Some(
+        StructureType::Blacksmith,
+    )));
+    simulation.input(SimulationInput::ClickTile(footprint.origin));
+
+    let village_map = simulation.world().resource::<VillageMap>();
+    let site = village_map
+        .actors
+        .get(footprint.origin)
+        .expect("building site placed");
+    assert!(simulation.world().get::<BuildingSite>(site).is_some());
+    assert_eq!(village_map.actors.locate(site), Some(footprint));
+    assert!(footprint
+        .tiles()
+        .all(|tile| village_map.actors.get(tile) == Some(site)));
+    assert!(simulation
+        .command_log()
+        .commands
+        .contains(&GameCommand::Build {
+            structure: StructureType::Blacksmith,
+            tile: footprint.origin,
+            rotated: true,
+        }));
+}
+
+#[test]
+fn bear_trap_holds_the_enemy_that_steps_on_it() {
+    let mut simulation = Simulation::with_level("debug_level");
+    let traps = |simulation: &Simulation| simulation.world().resource::<VillageMap>().traps.clone();
+    let gold = simulation.world().resource::<VillageGold>().0;
+
+    // Landmines have to be unlocked by a blacksmith first.
+    simulation.input(SimulationInput::SelectTrap(Some(TrapType::Landmine)));
+    simulation.input(SimulationInput::ClickTile(Tile(1, 1)));
+    assert!(traps(&simulation).is_empty());
+
+    simulation.input(SimulationInput::SelectTrap(Some(TrapType::BearTrap)));
+    simulation.input(SimulationInput::ClickTile(Tile(1, 1)));
+    assert!(traps(&simulation).contains_key(&Tile(1, 1)));
+    assert_eq!(
+        simulation.world().resource::<VillageGold>().0,
+        gold - TrapType::BearTrap.gold()
+    );
+    assert!(simulation
+        .command_log()
+        .commands
+        .contains(&GameCommand::PlaceTrap {
+            trap: TrapType::BearTrap,
+            tile: Tile(1, 1),
+        }));
+
+    start_battle(&mut simulation);
+    let werewolf = enemy(&simulation, "werewolf");
+    let hit_points = werewolf.hit_points;
+    // Too far from the heroes to reach them in one move.
+    replace_enemies(&mut simulation, vec![(werewolf, Tile(0, 6))]);
+    simulation.update();
+    let (entity, intent) = simulation
+        .world_mut()
+        .query::<(Entity, &EnemyIntent)>()
+        .single(simulation.world());
+    let trap_tile = intent.path[1];
+    assert_ne!(intent.destination(), trap_tile);
+
+    // Lay another trap on the first step of the werewolf's path.
+    let trap = simulation.world_mut().run_system_once(
+        move |mut commands: Commands,
+              mut village_map: ResMut<VillageMap>,
+              icon_set: Res<IconSet>| {
+            let id = spawn_trap(&mut commands, &icon_set, trap_tile, TrapType::BearTrap);
+            village_map.traps.insert(trap_tile, id);
+            id
+        },
+    );
+    simulation.input(SimulationInput::EndTurn);
+
+    assert_eq!(
+        simulation
+            .world()
+            .resource::<VillageMap>()
+            .actors
+            .locate(entity),
+        Some(Footprint::single(trap_tile))
+    );
+    assert_eq!(
+        simulation.world().get::<Health>(entity).unwrap().value,
+        hit_points - 1
+    );
+    assert!(simulation
+        .world()
+        .get::<StatusEffects>(entity)
+        .unwrap()
+        .has(StatusKind::Webbed));
+    // Bear traps are re-armed at dawn instead of being used up.
+    assert!(!simulation.world().get::<Trap>(trap).unwrap().armed);
+    end_turns_until(&mut simulation, GameState::BuildingTurn);
+    assert!(simulation.world().get::<Trap>(trap).unwrap().armed);
+}
+
+#[test]
+fn landmine_hurts_the_enemies_around_it_but_spares_heroes() {
+    let mut simulation = Simulation::with_level("debug_level");
+    let heroes = start_battle(&mut simulation);
+    let werewolf = enemy(&simulation, "werewolf");
+    let hit_points = werewolf.hit_points;
+    let mine_tile = Tile(1, 8);
+    replace_enemies(
+        &mut simulation,
+        vec![(werewolf.clone(), mine_tile), (werewolf, Tile(1, 9))],
+    );
+    let hero = heroes[0];
+    let hero_health = simulation.world().get::<Health>(hero).unwrap().value;
+    let trap = simulation.world_mut().run_system_once_with(
+        hero,
+        move |In(hero): In<Entity>,
+              mut commands: Commands,
+              mut village_map: ResMut<VillageMap>,
+              icon_set: Res<IconSet>| {
+            village_map.actors.remove_entity(hero);
+            village_map.actors.set(Tile(0, 8), hero);
+            let id = spawn_trap(&mut commands, &icon_set, mine_tile, TrapType::Landmine);
+            village_map.traps.insert(mine_tile, id);
+            id
+        },
+    );
+    let werewolves: Vec<Entity> = simulation
+        .world_mut()
+        .query_filtered::<Entity, With<EnemyActor>>()
+        .iter(simulation.world())
+        .collect();
+    let target = simulation
+        .world()
+        .resource::<VillageMap>()
+        .actors
+        .get(mine_tile)
+        .unwrap();
+    simulation
+        .world_mut()
+        .send_event(TrapTriggered { trap, target });
+    simulation.update();
+
+    assert_eq!(werewolves.len(), 2);
+    for werewolf in werewolves {
+        assert_eq!(
+            simulation.world().get::<Health>(werewolf).unwrap().value,
+            hit_points - TrapType::Landmine.damage()
+        );
+    }
+    assert_eq!(
+        simulation.world().get::<Health>(hero).unwrap().value,
+        hero_health
+    );
+    // Landmines are used up.
+    assert!(simulation.world().get_entity(trap).is_none());
+}
+
+#[test]
+fn poison_wears_down_a_hero_until_it_wears_off() {
+    let mut simulation = Simulation::with_level("debug_level");
+    let heroes = start_battle(&mut simulation);
+    replace_enemies(&mut simulation, Vec::new());
+    let hero = heroes[0];
+    simulation.world_mut().send_event(InflictStatus {
+        target: hero,
+        effect: StatusEffect::new(StatusKind::Poison, 1),
+    });
+    simulation.update();
+    let health = |simulation: &Simulation| simulation.world().get::<Health>(hero).unwrap().value;
+    let before = health(&simulation);
+
+    simulation.input(SimulationInput::EndTurn);
+    assert_eq!(health(&simulation), before - 1);
+    assert!(!simulation
+        .world()
+        .get::<StatusEffects>(hero)
+        .unwrap()
+        .has(StatusKind::Poison));
+
+    simulation.input(SimulationInput::EndTurn);
+    assert_eq!(health(&simulation), before - 1);
+}
+
+#[test]
+fn webbed_hero_sits_out_a_turn() {
+    let mut simulation = Simulation::with_level("debug_level");
+    let heroes = start_battle(&mut simulation);
+    replace_enemies(&mut simulation, Vec::new());
+    let hero = heroes[0];
+    simulation.world_mut().send_event(InflictStatus {
+        target: hero,
+        effect: StatusEffect::new(StatusKind::Webbed, 2),
+    });
+    simulation.update();
+    let turn_state = |simulation: &Simulation| {
+        simulation
+            .world()
+            .get::<ActorTurnState>(hero)
+            .unwrap()
+            .clone()
+    };
+    assert!(!turn_state(&simulation).can_move(&Abilities::default()));
+
+    simulation.input(SimulationInput::EndTurn);
+    assert!(!turn_state(&simulation).can_act(&Abilities::default()));
+
+    simulation.input(SimulationInput::EndTurn);
+    assert!(turn_state(&simulation).can_move(&Abilities::default()));
+}
+
+#[test]
+fn blocking_hero_shrugs_off_the_next_hit() {
+    let mut simulation = Simulation::with_level("debug_level");
+    let heroes = start_battle(&mut simulation);
+    replace_enemies(&mut simulation, Vec::new());
+    let hero = heroes[0];
+    let tile = simulation
+        .world()
+        .resource::<VillageMap>()
+        .actors
+        .locate(hero)
+        .unwrap()
+        .origin;
+    simulation.input(SimulationInput::ClickTile(tile));
+    simulation.input(SimulationInput::Block);
+    assert!(!simulation
+        .world()
+        .get::<ActorTurnState>(hero)
+        .unwrap()
+        .can_act(&Abilities::default()));
+    let health = |simulation: &Simulation| simulation.world().get::<Health>(hero).unwrap().value;
+    let before = health(&simulation);
+
+    simulation
+        .world_mut()
+        .send_event(DamageEvent::new(None, hero, 1, DamageType::Physical));
+    simulation.update();
+    assert_eq!(health(&simulation), before);
+
+    simulation
+        .world_mut()
+        .send_event(DamageEvent::new(None, hero, 1, DamageType::Physical));
+    simulation.update();
+    assert_eq!(health(&simulation), before - 1);
+}
+
+#[test]
+fn armour_absorbs_damage_until_a_hero_falls() {
+    let mut simulation = Simulation::with_level("debug_level");
+    let heroes = start_battle(&mut simulation);
+    replace_enemies(&mut simulation, Vec::new());
+    let hero = heroes[0];
+    simulation.world_mut().entity_mut(hero).insert(Armour(1));
+    let before = simulation.world().get::<Health>(hero).unwrap().value;
+
+    simulation
+        .world_mut()
+        .send_event(DamageEvent::new(None, hero, 2, DamageType::Physical));
+    simulation.update();
+    assert_eq!(simulation.world().get::<Armour>(hero), Some(&Armour(0)));
+    assert_eq!(
+        simulation.world().get::<Health>(hero).unwrap().value,
+        before - 1
+    );
+
+    simulation
+        .world_mut()
+        .send_event(DamageEvent::new(None, hero, before, DamageType::Physical));
+    simulation.update();
+    assert!(simulation
+        .world()
+        .resource::<VillageMap>()
+        .actors
+        .locate(hero)
+        .is_none());
+    // Fallen heroes get back on their feet with 1 health
+    assert_eq!(simulation.world().get::<Health>(hero).unwrap().value, 1);
+}
+
+#[test]
+fn drain_heals_by_the_damage_dealt() {
+    let mut simulation = Simulation::with_level("debug_level");
+    let heroes = start_battle(&mut simulation);
+    let dracula = enemy(&simulation, "dracula");
+    replace_enemies(&mut simulation, vec![(dracula, Tile(0, 2))]);
+    simulation.update();
+    let dracula = simulation
+        .world()
+        .resource::<VillageMap>()
+        .actors
+        .get(Tile(0, 2))
+        .unwrap();
+    let health = |simulation: &Simulation| simulation.world().get::<Health>(dracula).unwrap().value;
+    simulation
+        .world_mut()
+        .send_event(DamageEvent::new(None, dracula, 3, DamageType::Physical));
+    simulation.update();
+    let before = health(&simulation);
+
+    // Armour takes half of the bite, only the health the hero lost is drained.
+    let hero = heroes[0];
+    simulation.world_mut().entity_mut(hero).insert(Armour(1));
+    simulation.world_mut().send_event(DamageEvent::new(
+        Some(dracula),
+        hero,
+        2,
+        DamageType::Physical,
+    ));
+    simulation.update();
+    simulation.update();
+    assert_eq!(health(&simulation), before + 1);
+}
+
+#[test]
+fn eclipse_lasts_until_the_final_boss_dies() {
+    let mut simulation = Simulation::with_level("debug_level");
+    // Skip ahead to the last daylight turn before the eclipse.
+    let day_turns = DayCycle::from(Season::Winter).day;
+    simulation.world_mut().resource_mut::<Turn>().0 = ECLIPSE_DAY * TURN_PER_DAY + day_turns - 1;
+    simulation.update();
+    start_battle(&mut simulation);
+    assert_eq!(
+        *simulation.world().resource::<MoonPhase>(),
+        MoonPhase::Eclipse
+    );
+
+    simulation.input(SimulationInput::EndTurn);
+    assert_eq!(simulation.time_of_day(), TimeOfDay::Night);
+    // The wave would overrun the village before the night is over, only the final boss is kept
+    // and taken off the map.
+    simulation.world_mut().run_system_once(
+        |mut commands: Commands,
+         mut village_map: ResMut<VillageMap>,
+         q_enemies: Query<(Entity, Has<FinalBoss>), With<EnemyActor>>| {
+            for (entity, is_final_boss) in q_enemies.iter() {
+                village_map.actors.remove_entity(entity);
+                if !is_final_boss {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+        },
+    );
+    for _ in 1..TURN_PER_DAY {
+        simulation.input(SimulationInput::EndTurn);
+    }
+    assert_eq!(simulation.time_of_day(), TimeOfDay::Night);
+    assert_eq!(simulation.game_state(), GameState::BattleTurn);
+
+    let (boss, health) = simulation
+        .world_mut()
+        .query_filtered::<(Entity, &Health), With<FinalBoss>>()
+        .single(simulation.world());
+    let amount = health.value;
+    simulation
+        .world_mut()
+        .send_event(DamageEvent::new(None, boss, amount, DamageType::Poison));
+    simulation.update();
+    simulation.update();
+    assert_eq!(
+        *simulation.world().resource::<State<Screen>>().get(),
+        Screen::Won
+    );
+    let run_stats = simulation.world().resource::<RunStats>();
+    assert!(run_stats.final_boss_defeated);
+    assert_eq!(run_stats.enemies_killed, 1);
+    assert_eq!(run_stats.victory, Some(VictoryCondition::DefeatFinalBoss));
+}
+
+#[test]
+fn deaths_are_reported_once() {
+    let mut simulation = Simulation::with_level("debug_level");
+    start_battle(&mut simulation);
+    let werewolf = enemy(&simulation, "werewolf");
+    let hit_points = werewolf.hit_points;
+    replace_enemies(&mut simulation, vec![(werewolf, Tile(0, 2))]);
+    let entity = simulation
+        .world()
+        .resource::<VillageMap>()
+        .actors
+        .get(Tile(0, 2))
+        .unwrap();
+    let kills = |simulation: &Simulation| simulation.world().resource::<RunStats>().enemies_killed;
+
+    simulation.world_mut().send_event(DamageEvent::new(
+        None,
+        entity,
+        hit_points,
+        DamageType::Poison,
+    ));
+    simulation.update();
+    simulation.update();
+    assert_eq!(kills(&simulation), 1);
+
+    // Touching the health of the dead enemy while it despawns is not another death.
+    if let Some(mut health) = simulation.world_mut().get_mut::<Health>(entity) {
+        health.set_changed();
+    }
+    simulation.update();
+    simulation.update();
+    assert_eq!(kills(&simulation), 1);
+}
+
+#[test]
+fn reaching_the_population_target_wins() {
+    let mut simulation = Simulation::with_level("riverside");
+    assert_eq!(
+        simulation.world().resource::<VictoryConditions>().0[0],
+        VictoryCondition::Population { target: 150 }
+    );
+    simulation.world_mut().resource_mut::<VillagePopulation>().0 = 150;
+    simulation.update();
+    simulation.update();
+    assert_eq!(
+        *simulation.world().resource::<State<Screen>>().get(),
+        Screen::Won
+    );
+    assert_eq!(
+        simulation.world().resource::<RunStats>().victory,
+        Some(VictoryCondition::Population { target: 150 })
+    );
+}
+
+#[test]
+fn winning_a_level_unlocks_the_next_one() {
+    let mut simulation = Simulation::with_level("debug_level");
+    assert_eq!(
+        *simulation.world().resource::<UnlockedLevels>(),
+        UnlockedLevels(1)
+    );
+    simulation.world_mut().resource_mut::<VictoryConditions>().0 =
+        vec![VictoryCondition::Population { target: 1 }];
+    simulation.update();
+    simulation.update();
+    assert_eq!(
+        *simulation.world().resource::<State<Screen>>().get(),
+        Screen::Won
+    );
+    assert_eq!(
+        *simulation.world().resource::<UnlockedLevels>(),
+        UnlockedLevels(2)
+    );
+}
+
+#[test]
+fn surviving_the_nights_wins_and_counts_them() {
+    let mut simulation = Simulation::with_level("debug_level");
+    simulation
+        .world_mut()
+        .insert_resource(VictoryConditions(vec![VictoryCondition::SurviveCycles {
+            cycles: 1,
+        }]));
+    let gold = simulation.world().resource::<VillageGold>().0;
+    start_battle(&mut simulation);
+    replace_enemies(&mut simulation, Vec::new());
+    end_turns_until(&mut simulation, GameState::BuildingTurn);
+
+    let run_stats = simulation.world().resource::<RunStats>();
+    assert_eq!(run_stats.nights_survived, 1);
+    assert!(run_stats.gold_earned > 0);
+    assert!(simulation.world().resource::<VillageGold>().0 > gold);
+    assert_eq!(
+        *simulation.world().resource::<State<Screen>>().get(),
+        Screen::Playing
+    );
+
+    simulation.world_mut().resource_mut::<Turn>().0 = DAY_PER_CYCLE * TURN_PER_DAY;
+    simulation.update();
+    simulation.update();
+    assert_eq!(
+        *simulation.world().resource::<State<Screen>>().get(),
+        Screen::Won
+    );
+}
+
+#[test]
+fn losing_every_structure_loses() {
+    let mut simulation = Simulation::with_level("debug_level");
+    simulation.world_mut().run_system_once(
+        |mut commands: Commands,
+         mut village_map: ResMut<VillageMap>,
+         q_structures: Query<Entity, With<Structure>>| {
+            for entity in q_structures.iter() {
+                village_map.actors.remove_entity(entity);
+                commands.entity(entity).despawn_recursive();
+            }
+        },
+    );
+    simulation.update();
+    simulation.update();
+    assert_eq!(
+        *simulation.world().resource::<State<Screen>>().get(),
+        Screen::Lost
+    );
+}
+
+#[test]
+fn a_village_of_walls_alone_is_lost() {
+    let mut simulation = Simulation::with_level("debug_level");
+    simulation.world_mut().run_system_once(
+        |mut commands: Commands,
+         mut village_map: ResMut<VillageMap>,
+         tile_set: Res<TileSet>,
+         q_structures: Query<Entity, With<Structure>>| {
+            for entity in q_structures.iter() {
+                village_map.actors.remove_entity(entity);
+                commands.entity(entity).despawn_recursive();
+            }
+            let tile = Tile(4, 4);
+            let id = spawn_structure(
+                &mut commands,
+                &tile_set,
+                Footprint::single(tile),
+                StructureType::Wall,
+            );
+            village_map.actors.set(tile, id);
+        },
+    );
+    // Walls do not extend the area that can be built on.
+    let build_sites = simulation
+        .world_mut()
+        .run_system_once(|game: Game| game.find_tiles_that_can_be_built_on());
+    assert!(build_sites.is_empty());
+
+    simulation.update();
+    simulation.update();
+    assert_eq!(
+        *simulation.world().resource::<State<Screen>>().get(),
+        Screen::Lost
+    );
+}
+
+#[test]
+fn level_tavern_keeps_its_footprint_through_a_save() {
+    let mut simulation = Simulation::with_level("crossroads");
+    let structure_footprints = |simulation: &mut Simulation| {
+        simulation.world_mut().run_system_once(
+            |village_map: Res<VillageMap>, q_structures: Query<Entity, With<Structure>>| {
+                let mut footprints: Vec<Footprint> = q_structures
+                    .iter()
+                    .filter_map(|entity| village_map.actors.locate(entity))
+                    .collect();
+                footprints.sort_by_key(|footprint| (footprint.origin.y(), footprint.origin.x()));
+                footprints
+            },
+        )
+    };
+    // The tavern stands right beside the house at (7, 7).
+    let tavern = StructureType::Tavern.footprint(Tile(7, 8), false);
+    let before = structure_footprints(&mut simulation);
+    assert!(before.contains(&tavern));
+    assert!(before.contains(&Footprint::single(Tile(7, 7))));
+    assert_eq!(before.len(), 7);
+
+    let save = simulation.world_mut().run_system_once(save_village);
+    assert!(save
+        .structures
+        .iter()
+        .any(|structure| structure.tile == tavern.origin && structure.size == tavern.size));
+
+    // Continue the saved village, as from the title screen.
+    simulation.world_mut().resource_mut::<PendingSave>().0 = Some(save);
+    simulation
+        .world_mut()
+        .resource_mut::<NextState<Screen>>()
+        .set(Screen::Title);
+    simulation.update();
+    simulation
+        .world_mut()
+        .resource_mut::<NextState<Screen>>()
+        .set(Screen::Playing);
+    simulation.update();
+    simulation.update();
+
+    assert_eq!(structure_footprints(&mut simulation), before);
+}
+
+#[test]
+fn run_stats_survive_a_save() {
+    let mut simulation = Simulation::with_level("debug_level");
+    simulation
+        .world_mut()
+        .resource_mut::<RunStats>()
+        .enemies_killed = 5;
+    let save = simulation.world_mut().run_system_once(save_village);
+
+    // Continue the saved village, as from the title screen.
+    simulation.world_mut().resource_mut::<PendingSave>().0 = Some(save);
+    simulation
+        .world_mut()
+        .resource_mut::<NextState<Screen>>()
+        .set(Screen::Title);
+    simulation.update();
+    simulation
+        .world_mut()
+        .resource_mut::<NextState<Screen>>()
+        .set(Screen::Playing);
+    simulation.update();
+    simulation.update();
+
+    assert_eq!(simulation.world().resource::<RunStats>().enemies_killed, 5);
+}